@@ -0,0 +1,48 @@
+use rs_class::ops::Target;
+use sysinfo::Pid;
+
+pub type State = super::DialogState<Pid>;
+
+/// Offers to attach to the running process of the target of a loaded project.
+#[derive(Debug)]
+pub struct AttachDialog {
+    state: State,
+    target: Target,
+    pid: Pid,
+}
+
+impl AttachDialog {
+    pub fn new(target: Target, pid: Pid) -> Self {
+        Self {
+            state: State::Open,
+            target,
+            pid,
+        }
+    }
+}
+
+impl super::Dialog<Pid> for AttachDialog {
+    fn show(&mut self, ctx: &egui::Context) {
+        egui::Modal::new("attach_dialog".into()).show(ctx, |ui| {
+            ui.heading(format!("{} is running", self.target.name));
+            if let Some(path) = &self.target.path {
+                ui.label(path.display().to_string());
+            }
+            ui.label(format!(
+                "Attach to the process {} targeted by the project ?",
+                self.pid
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Attach").clicked() {
+                    self.state = State::Selected(self.pid);
+                } else if ui.button("Not now").clicked() {
+                    self.state = State::Cancelled;
+                }
+            })
+        });
+    }
+
+    fn state(&self) -> &State {
+        &self.state
+    }
+}
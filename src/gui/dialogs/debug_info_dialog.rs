@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use egui::RichText;
+use rs_class::ops::Module;
+
+pub type State = super::DialogState<Vec<PathBuf>>;
+
+/// Offers to load the types of the modules of the attached process which have debug information.
+#[derive(Debug)]
+pub struct DebugInfoDialog {
+    state: State,
+    // (Module, Checked)
+    modules: Vec<(Module, bool)>,
+}
+
+impl DebugInfoDialog {
+    pub fn new(modules: Vec<Module>) -> Self {
+        Self {
+            state: State::Open,
+            modules: modules.into_iter().map(|m| (m, true)).collect(),
+        }
+    }
+}
+
+impl super::Dialog<Vec<PathBuf>> for DebugInfoDialog {
+    fn show(&mut self, ctx: &egui::Context) {
+        let modal = egui::Modal::new("debug_info_dialog".into()).show(ctx, |ui| {
+            ui.heading("Debug information found");
+            ui.label("Load the types of these modules ?");
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for (module, checked) in &mut self.modules {
+                        ui.checkbox(
+                            checked,
+                            RichText::new(format!("{:#014x} | {}", module.base, module.name))
+                                .monospace(),
+                        )
+                        .on_hover_text(module.path.display().to_string());
+                    }
+                });
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("Load types").clicked() {
+                    self.state = State::Selected(
+                        self.modules
+                            .iter()
+                            .filter(|(_, checked)| *checked)
+                            .map(|(m, _)| m.path.clone())
+                            .collect(),
+                    );
+                }
+                if ui.button("Skip").clicked() {
+                    self.state = State::Cancelled;
+                }
+            });
+        });
+        if modal.should_close() && self.state == State::Open {
+            self.state = State::Cancelled;
+        }
+    }
+
+    fn state(&self) -> &State {
+        &self.state
+    }
+}
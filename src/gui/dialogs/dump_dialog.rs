@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+// The dump file, and the address of the memory of raw dumps
+pub type State = super::DialogState<(PathBuf, u64)>;
+
+/// Asks for a memory dump to read instead of a running process.
+#[derive(Debug)]
+pub struct DumpDialog {
+    state: State,
+    path: String,
+    raw_base: u64,
+    file_dialog: egui_file_dialog::FileDialog,
+    picking: bool,
+}
+
+impl DumpDialog {
+    pub fn new() -> Self {
+        let mut file_dialog = egui_file_dialog::FileDialog::new();
+        if let Some(p) = dirs::document_dir() {
+            file_dialog.config_mut().initial_directory = p;
+        }
+        Self {
+            state: State::Open,
+            path: String::new(),
+            raw_base: 0,
+            file_dialog,
+            picking: false,
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Open a memory dump");
+        egui::Grid::new("dump_options")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Dump");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.path)
+                            .hint_text("Core dump, minidump or raw memory"),
+                    );
+                    if ui.button("Browse").clicked() {
+                        self.file_dialog.pick_file();
+                        self.picking = true;
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Raw base address").on_hover_text(
+                    "Address of the first byte of the file, when it is neither \
+                     a core dump nor a minidump",
+                );
+                ui.add(egui::DragValue::new(&mut self.raw_base).hexadecimal(1, false, true));
+                ui.end_row();
+            });
+
+        let path = self.path.trim();
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            let open_button = egui::Button::new("Open");
+            if ui.add_enabled(!path.is_empty(), open_button).clicked() {
+                self.state = State::Selected((PathBuf::from(path), self.raw_base));
+            }
+            if ui.button("Cancel").clicked() {
+                self.state = State::Cancelled;
+            }
+        });
+    }
+}
+
+impl super::Dialog<(PathBuf, u64)> for DumpDialog {
+    fn show(&mut self, ctx: &egui::Context) {
+        // The modal would cover the file picker
+        if self.picking {
+            self.file_dialog.update(ctx);
+            if let egui_file_dialog::DialogState::Open = self.file_dialog.state() {
+                return;
+            }
+            self.picking = false;
+            if let Some(path) = self.file_dialog.take_picked() {
+                self.path = path.display().to_string();
+            }
+        }
+
+        let modal = egui::Modal::new("dump_dialog".into()).show(ctx, |ui| self.ui(ui));
+        if modal.should_close() && self.state == State::Open {
+            self.state = State::Cancelled;
+        }
+    }
+
+    fn state(&self) -> &State {
+        &self.state
+    }
+}
@@ -0,0 +1,52 @@
+use std::{path::PathBuf, sync::Arc};
+
+pub type State = super::DialogState<PathBuf>;
+
+#[derive(Debug)]
+pub struct ExportDialog {
+    state: State,
+    file_dialog: egui_file_dialog::FileDialog,
+}
+
+impl ExportDialog {
+    pub fn new() -> Self {
+        let mut fd: egui_file_dialog::FileDialog = egui_file_dialog::FileDialog::new()
+            .add_file_filter(
+                "Ghidra XML",
+                Arc::new(|path| path.extension().is_some_and(|ext| ext == "xml")),
+            )
+            .default_file_filter("Ghidra XML");
+        fd.config_mut().default_file_name = "types.xml".into();
+
+        if let Some(p) = dirs::document_dir() {
+            fd.config_mut().initial_directory = p;
+        }
+
+        fd.save_file();
+
+        Self {
+            state: Default::default(),
+            file_dialog: fd,
+        }
+    }
+}
+
+impl super::Dialog<PathBuf> for ExportDialog {
+    fn show(&mut self, ctx: &egui::Context) {
+        self.file_dialog.update(ctx);
+        use egui_file_dialog::DialogState::{Cancelled, Open, Picked};
+        match self.file_dialog.state() {
+            Open => {}
+            Picked(p) => self.state = State::Selected(p.clone()),
+            Cancelled => {
+                println!("User did not choose a file, export is cancelled");
+                self.state = State::Cancelled;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn state(&self) -> &super::DialogState<PathBuf> {
+        &self.state
+    }
+}
@@ -0,0 +1,55 @@
+use std::{path::PathBuf, sync::Arc};
+
+pub type State = super::DialogState<PathBuf>;
+
+#[derive(Debug)]
+pub struct ImportDialog {
+    state: State,
+    file_dialog: egui_file_dialog::FileDialog,
+}
+
+impl ImportDialog {
+    /// Opens a file picker filtered on the given extensions.
+    pub fn new(filter_name: &str, extensions: &'static [&'static str]) -> Self {
+        let mut fd: egui_file_dialog::FileDialog = egui_file_dialog::FileDialog::new()
+            .add_file_filter(
+                filter_name,
+                Arc::new(move |path| {
+                    path.extension()
+                        .is_some_and(|ext| extensions.iter().any(|e| ext == *e))
+                }),
+            )
+            .default_file_filter(filter_name);
+
+        if let Some(p) = dirs::document_dir() {
+            fd.config_mut().initial_directory = p;
+        }
+
+        fd.pick_file();
+
+        Self {
+            state: Default::default(),
+            file_dialog: fd,
+        }
+    }
+}
+
+impl super::Dialog<PathBuf> for ImportDialog {
+    fn show(&mut self, ctx: &egui::Context) {
+        self.file_dialog.update(ctx);
+        use egui_file_dialog::DialogState::{Cancelled, Open, Picked};
+        match self.file_dialog.state() {
+            Open => {}
+            Picked(p) => self.state = State::Selected(p.clone()),
+            Cancelled => {
+                self.state = State::Cancelled;
+                println!("User did not choose a file, import is cancelled");
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn state(&self) -> &super::DialogState<PathBuf> {
+        &self.state
+    }
+}
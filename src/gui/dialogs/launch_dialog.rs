@@ -0,0 +1,206 @@
+use std::path::PathBuf;
+
+use rs_class::ops::LaunchOptions;
+
+pub type State = super::DialogState<LaunchOptions>;
+
+// Field filled by the file picker
+#[derive(Debug, Clone, Copy)]
+enum Picking {
+    Executable,
+    WorkingDir,
+}
+
+/// Asks how to start a process, which is attached right away.
+#[derive(Debug)]
+pub struct LaunchDialog {
+    state: State,
+    path: String,
+    args: String,
+    working_dir: String,
+    // One KEY=VALUE per line
+    env: String,
+    suspended: bool,
+    file_dialog: egui_file_dialog::FileDialog,
+    picking: Option<Picking>,
+}
+
+impl LaunchDialog {
+    /// Opens the dialog filled with the options of the previous launch.
+    pub fn new(options: &LaunchOptions) -> Self {
+        Self {
+            state: State::Open,
+            path: options.path.display().to_string(),
+            args: options
+                .args
+                .iter()
+                .map(
+                    |arg| match arg.contains(char::is_whitespace) || arg.is_empty() {
+                        true => format!("\"{}\"", arg.replace('"', "\\\"")),
+                        false => arg.replace('"', "\\\""),
+                    },
+                )
+                .collect::<Vec<_>>()
+                .join(" "),
+            working_dir: options
+                .working_dir
+                .as_ref()
+                .map_or(String::new(), |p| p.display().to_string()),
+            env: options
+                .env
+                .iter()
+                .map(|(k, v)| format!("{k}={v}\n"))
+                .collect(),
+            suspended: options.suspended,
+            file_dialog: egui_file_dialog::FileDialog::new(),
+            picking: None,
+        }
+    }
+
+    fn options(&self) -> Result<LaunchOptions, String> {
+        let path = self.path.trim();
+        if path.is_empty() {
+            return Err("No executable selected.".into());
+        }
+        let env = self
+            .env
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| match line.split_once('=') {
+                Some((k, v)) if !k.is_empty() => Ok((k.to_owned(), v.to_owned())),
+                _ => Err(format!("Invalid environment variable: {line}")),
+            })
+            .collect::<Result<_, _>>()?;
+        let working_dir = self.working_dir.trim();
+        Ok(LaunchOptions {
+            path: PathBuf::from(path),
+            args: split_args(&self.args),
+            working_dir: (!working_dir.is_empty()).then(|| PathBuf::from(working_dir)),
+            env,
+            suspended: self.suspended,
+        })
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Launch an executable");
+        egui::Grid::new("launch_options")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Executable");
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.path);
+                    if ui.button("Browse").clicked() {
+                        self.file_dialog.pick_file();
+                        self.picking = Some(Picking::Executable);
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Arguments");
+                ui.text_edit_singleline(&mut self.args)
+                    .on_hover_text("Separated by spaces, quote the arguments containing spaces");
+                ui.end_row();
+
+                ui.label("Working directory");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.working_dir)
+                            .hint_text("Current directory"),
+                    );
+                    if ui.button("Browse").clicked() {
+                        self.file_dialog.pick_directory();
+                        self.picking = Some(Picking::WorkingDir);
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Environment");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.env)
+                        .hint_text("KEY=VALUE, one per line")
+                        .desired_rows(3),
+                );
+                ui.end_row();
+            });
+
+        #[cfg(target_os = "linux")]
+        let suspended_hint = "Stop the process with ptrace once its executable is loaded, \
+            before any of its code runs. Resume it from the side panel.";
+        #[cfg(not(target_os = "linux"))]
+        let suspended_hint = "Create the process with its main thread suspended. \
+            Resume it from the side panel.";
+        ui.checkbox(&mut self.suspended, "Start suspended")
+            .on_hover_text(suspended_hint);
+
+        let options = self.options();
+        if let Err(err) = &options {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            let launch_button = egui::Button::new("Launch");
+            if ui.add_enabled(options.is_ok(), launch_button).clicked() {
+                if let Ok(options) = options {
+                    self.state = State::Selected(options);
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.state = State::Cancelled;
+            }
+        });
+    }
+}
+
+impl super::Dialog<LaunchOptions> for LaunchDialog {
+    fn show(&mut self, ctx: &egui::Context) {
+        // The modal would cover the file picker
+        if let Some(picking) = self.picking {
+            self.file_dialog.update(ctx);
+            match self.file_dialog.state() {
+                egui_file_dialog::DialogState::Open => return,
+                _ => self.picking = None,
+            }
+            if let Some(path) = self.file_dialog.take_picked() {
+                let path = path.display().to_string();
+                match picking {
+                    Picking::Executable => self.path = path,
+                    Picking::WorkingDir => self.working_dir = path,
+                }
+            }
+        }
+
+        let modal = egui::Modal::new("launch_dialog".into()).show(ctx, |ui| self.ui(ui));
+        if modal.should_close() && self.state == State::Open {
+            self.state = State::Cancelled;
+        }
+    }
+
+    fn state(&self) -> &State {
+        &self.state
+    }
+}
+
+// Splits a command line on whitespace, double quotes group words and \" is a quote
+fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'"') => {
+                arg.get_or_insert_default().push('"');
+                chars.next();
+            }
+            '"' => {
+                quoted = !quoted;
+                arg.get_or_insert_default();
+            }
+            c if c.is_whitespace() && !quoted => args.extend(arg.take()),
+            c => arg.get_or_insert_default().push(c),
+        }
+    }
+    args.extend(arg);
+    args
+}
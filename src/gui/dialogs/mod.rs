@@ -1,8 +1,16 @@
+pub mod attach_dialog;
+pub mod debug_info_dialog;
+pub mod dump_dialog;
+pub mod export_dialog;
+pub mod import_dialog;
+pub mod launch_dialog;
 pub mod load_dialog;
 pub mod process_dialog;
 pub mod prompt_save_dialog;
 pub mod save_dialog;
+pub mod signature_dialog;
 pub mod type_selection_dialog;
+pub mod typedef_dialog;
 
 #[derive(Copy, Debug, Clone, Eq, PartialEq, Default)]
 pub enum DialogState<T>
@@ -4,59 +4,204 @@
  *
 */
 
-use std::{cell::RefCell, collections::HashSet, rc::Rc};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    rc::Rc,
+    thread::JoinHandle,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use egui::RichText;
-use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate::All, RefreshKind, System};
-
-/*
- * From ReClassEx : https://github.com/ajkhoury/ReClassEx/blob/master/ReClass/DialogProcSelect.cpp
- */
-lazy_static::lazy_static! {
-    static ref common_processes : HashSet<&'static str> =
-    [
-        "svchost.exe", "System", "conhost.exe", "wininit.exe", "smss.exe", "winint.exe", "wlanext.exe", "Code.exe", "taskhostw.exe", "SearchIndexer.exe", "Idle", "SearchApp.exe", "dllhost.exe",
-        "spoolsv.exe", "spoolsv.exe", "notepad.exe", "explorer.exe", "itunes.exe",
-        "sqlservr.exe", "nvtray.exe","nvxdsync.exe", "lsass.exe", "jusched.exe",
-        "conhost.exe", "chrome.exe", "firefox.exe", "winamp.exe", "TrustedInstaller.exe",
-        "WinRAR.exe", "calc.exe", "taskhostex.exe", "Taskmgr.exe","dwm.exe","SpotifyWebHelper.exe",
-        "plugin-container.exe", "services.exe", "devenv.exe", "flux.exe", "skype.exe", "spotify.exe",
-        "csrss.exe", "taskeng.exe", "spotifyhelper.exe", "vcpkgsrv.exe", "msbuild.exe", "cmd.exe", "taskhost.exe",
-        "SettingSyncHost.exe", "SkyDrive.exe", "ctfmon.exe", "RuntimeBroker.exe", "BTTray.exe", "BTStackServer.exe",
-        "Bluetooth Headset Helper.exe", "winlogon.exe", "PnkBstrA.exe", "armsvc.exe", "MSIAfterburner.exe", "vmnat.exe",
-        "vmware-authd.exe", "vmnetdhcp.exe", "pia_manager.exe", "SpotifyWebHelper.exe", "Dropbox.exe", "Viber.exe", "idaq.exe",
-        "idaq64.exe", "CoreSync.exe", "SpotifyCrashService.exe", "RzSynapse.exe", "acrotray.exe",
-        "CCLibrary.exe", "pia_tray.exe", "rubyw.exe", "netsession_win.exe", "NvBackend.exe", "TeamViewer_Service.exe",
-        "DisplayFusionHookAppWIN6032.exe", "DisplayFusionHookAppWIN6064.exe", "GameScannerService.exe", "AdobeUpdateService.exe",
-        "steamwebhelper.exe", "c2c_service.exe", "Sync Server.exe", "NvNetworkService.exe", "Creative Cloud.exe", "foobar2000.exe",
-        "code.exe", "ReClass.exe", "ReClass64.exe", "Discord.exe", "node.exe", "TeamViewer.exe", "Everything.exe"
-    ].into();
-}
+use rs_class::ops::{Process, SystemProcess};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate::All, RefreshKind, System, Users};
 
 use super::DialogState;
 pub type State = DialogState<Pid>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Pid,
+    Name,
+    User,
+    Architecture,
+    Memory,
+    Started,
+    CommandLine,
+}
+
+const COLUMNS: [(Column, &str); 7] = [
+    (Column::Pid, "PID"),
+    (Column::Name, "Name"),
+    (Column::User, "User"),
+    (Column::Architecture, "Arch"),
+    (Column::Memory, "Memory"),
+    (Column::Started, "Started"),
+    (Column::CommandLine, "Command line"),
+];
+
+// Snapshot of a process taken on refresh, so that sorting does not query the system
+#[derive(Debug)]
+struct Row {
+    pid: Pid,
+    name: String,
+    user: String,
+    architecture: Option<&'static str>,
+    memory: u64,
+    start_time: u64,
+    command_line: String,
+}
+
+impl Row {
+    fn compare(&self, other: &Row, column: Column) -> Ordering {
+        match column {
+            Column::Pid => self.pid.cmp(&other.pid),
+            Column::Name => self.name.to_lowercase().cmp(&other.name.to_lowercase()),
+            Column::User => self.user.cmp(&other.user),
+            Column::Architecture => self.architecture.cmp(&other.architecture),
+            Column::Memory => self.memory.cmp(&other.memory),
+            Column::Started => self.start_time.cmp(&other.start_time),
+            Column::CommandLine => self.command_line.cmp(&other.command_line),
+        }
+        .then_with(|| self.pid.cmp(&other.pid))
+    }
+
+    fn matches(&self, search: &str) -> bool {
+        search.is_empty()
+            || self.pid.to_string().contains(search)
+            || [&self.name, &self.user, &self.command_line]
+                .iter()
+                .any(|s| s.to_lowercase().contains(search))
+    }
+}
+
 #[derive(Debug)]
 pub struct ProcessDialog {
     state: State,
     system: System,
     selected_process_id: Option<Pid>,
+
+    rows: Vec<Row>,
+    search: String,
+    sort: Column,
+    ascending: bool,
+    show_hidden: bool,
+    only_openable: bool,
+    // Whether the processes can be opened, probed on a worker thread when filtering on it
+    openable: Option<HashMap<Pid, bool>>,
+    openable_worker: Option<JoinHandle<HashMap<Pid, bool>>>,
+    // Names of the hidden processes, saved in the settings
+    ignored: Vec<String>,
+    new_ignored: String,
 }
-impl Default for ProcessDialog {
-    fn default() -> Self {
-        Self {
+
+impl ProcessDialog {
+    pub fn new(ignored: &[String]) -> Self {
+        let mut dialog = Self {
             state: Default::default(),
             system: System::new_with_specifics(
                 RefreshKind::nothing().with_processes(ProcessRefreshKind::everything()),
             ),
             selected_process_id: None,
-        }
+            rows: Vec::new(),
+            search: String::new(),
+            sort: Column::Pid,
+            ascending: true,
+            show_hidden: false,
+            only_openable: false,
+            openable: None,
+            openable_worker: None,
+            ignored: ignored.to_vec(),
+            new_ignored: String::new(),
+        };
+        dialog.update_rows();
+        dialog
+    }
+
+    /// Ignore list, as edited in the dialog.
+    pub fn get_ignored(&self) -> &[String] {
+        &self.ignored
     }
-}
 
-impl ProcessDialog {
     fn refresh(&mut self) {
         self.system.refresh_processes(All, true);
+        // A running probe is left to finish on its own
+        self.openable = None;
+        self.openable_worker = None;
+        self.update_rows();
+    }
+
+    fn update_rows(&mut self) {
+        let users = Users::new_with_refreshed_list();
+        let mut architectures: HashMap<&Path, Option<&'static str>> = HashMap::new();
+        self.rows = self
+            .system
+            .processes()
+            .iter()
+            // Threads are listed with their process
+            .filter(|(_, process)| process.thread_kind().is_none())
+            .map(|(pid, process)| Row {
+                pid: *pid,
+                name: process.name().to_string_lossy().into_owned(),
+                user: process
+                    .user_id()
+                    .and_then(|uid| users.get_user_by_id(uid))
+                    .map(|u| u.name().to_owned())
+                    .unwrap_or_default(),
+                architecture: process.exe().and_then(|exe| {
+                    *architectures
+                        .entry(exe)
+                        .or_insert_with(|| architecture(exe))
+                }),
+                memory: process.memory(),
+                start_time: process.start_time(),
+                command_line: process
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            })
+            .collect();
+        self.sort_rows();
+    }
+
+    fn sort_rows(&mut self) {
+        let (column, ascending) = (self.sort, self.ascending);
+        self.rows.sort_by(|a, b| match ascending {
+            true => a.compare(b, column),
+            false => b.compare(a, column),
+        });
+    }
+
+    // Probes whether the listed processes can be opened, once per refresh
+    fn update_openable(&mut self) {
+        if self
+            .openable_worker
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            let worker = self.openable_worker.take();
+            self.openable = worker.map(|w| w.join().unwrap_or_default());
+        }
+        if self.only_openable && self.openable.is_none() && self.openable_worker.is_none() {
+            let pids: Vec<Pid> = self.rows.iter().map(|row| row.pid).collect();
+            self.openable_worker = Some(std::thread::spawn(move || {
+                pids.into_iter()
+                    .map(|pid| (pid, Process::new(pid).open().is_ok()))
+                    .collect()
+            }));
+        }
+    }
+
+    // Processes not probed yet are kept
+    fn is_openable(&self, pid: Pid) -> bool {
+        self.openable
+            .as_ref()
+            .is_none_or(|openable| openable.get(&pid) != Some(&false))
     }
 
     fn ui(&mut self, ctx: &egui::Context, is_open: &mut bool) {
@@ -75,29 +220,60 @@ impl ProcessDialog {
         ui.heading("Select a process");
         let viewport_rect = ui.input(|is| is.viewport().inner_rect);
 
-        egui::ScrollArea::vertical()
+        ui.horizontal(|ui| {
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("Search PID, name, user or command line"),
+            );
+            if ui.memory(|m| m.focused().is_none()) {
+                search.request_focus();
+            }
+            ui.checkbox(&mut self.show_hidden, "Show hidden");
+            ui.checkbox(&mut self.only_openable, "Only openable")
+                .on_hover_text(
+                    "Hide the processes that cannot be opened, e.g. for lack of permissions",
+                );
+            self.update_openable();
+            if self.only_openable && self.openable_worker.is_some() {
+                ui.spinner();
+            }
+        });
+
+        let search = self.search.trim().to_lowercase();
+        let visible: Vec<usize> = (0..self.rows.len())
+            .filter(|&i| {
+                let row = &self.rows[i];
+                row.matches(&search) && (self.show_hidden || !self.ignored.contains(&row.name))
+            })
+            .collect();
+        let visible: Vec<usize> = match self.only_openable {
+            true => visible
+                .into_iter()
+                .filter(|&i| self.is_openable(self.rows[i].pid))
+                .collect(),
+            false => visible,
+        };
+
+        egui::ScrollArea::both()
             .max_height(viewport_rect.map_or(f32::MAX, |r| r.height() / 2.0))
             .show(ui, |ui| {
-                for (pid, process) in self.system.processes() {
-                    if common_processes.contains(&process.name().to_str().unwrap_or_default()) {
-                        continue;
-                    }
-                    let label_response = ui.selectable_value(
-                        &mut self.selected_process_id,
-                        Some(*pid),
-                        RichText::new(format!(
-                            "{:>6} | {}",
-                            pid.as_u32(),
-                            process.name().to_str().unwrap_or_default()
-                        ))
-                        .monospace(),
-                    );
-                    if label_response.double_clicked() {
-                        self.state = State::Selected(pid.to_owned());
-                    }
-                }
+                egui::Grid::new("processes")
+                    .striped(true)
+                    .num_columns(COLUMNS.len())
+                    .show(ui, |ui| {
+                        self.header(ui);
+                        let now = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map_or(0, |d| d.as_secs());
+                        for i in visible {
+                            self.row(ui, i, now);
+                        }
+                    });
             });
 
+        egui::CollapsingHeader::new(format!("Ignored processes ({})", self.ignored.len()))
+            .show(ui, |ui| self.ignore_list(ui));
+
         ui.add_space(10.0);
 
         let shared_self = Rc::new(RefCell::new(self));
@@ -129,6 +305,157 @@ impl ProcessDialog {
             },
         );
     }
+
+    // Column titles, a click sorts by the column and a second one reverses the order
+    fn header(&mut self, ui: &mut egui::Ui) {
+        for (column, title) in COLUMNS {
+            let title = match (self.sort == column, self.ascending) {
+                (true, true) => format!("{title} ⏶"),
+                (true, false) => format!("{title} ⏷"),
+                (false, _) => title.to_owned(),
+            };
+            if ui
+                .add(egui::Button::new(RichText::new(title).strong()).frame(false))
+                .clicked()
+            {
+                match self.sort == column {
+                    true => self.ascending = !self.ascending,
+                    false => {
+                        self.sort = column;
+                        self.ascending = true;
+                    }
+                }
+                self.sort_rows();
+            }
+        }
+        ui.end_row();
+    }
+
+    fn row(&mut self, ui: &mut egui::Ui, i: usize, now: u64) {
+        let row = &self.rows[i];
+        let hidden = self.ignored.contains(&row.name);
+        let text = |s: String| match hidden {
+            true => RichText::new(s).monospace().weak(),
+            false => RichText::new(s).monospace(),
+        };
+
+        ui.label(text(format!("{:>6}", row.pid.as_u32())));
+        let label_response = ui.selectable_value(
+            &mut self.selected_process_id,
+            Some(row.pid),
+            text(row.name.clone()),
+        );
+        ui.label(text(row.user.clone()));
+        ui.label(text(row.architecture.unwrap_or("?").to_owned()));
+        ui.label(text(format_bytes(row.memory)));
+        ui.label(text(format_age(now.saturating_sub(row.start_time))));
+        ui.add(egui::Label::new(text(row.command_line.clone())).truncate())
+            .on_hover_text(&row.command_line);
+        ui.end_row();
+
+        if label_response.double_clicked() {
+            self.state = State::Selected(row.pid);
+        }
+        let name = row.name.clone();
+        label_response.context_menu(|ui| match hidden {
+            true => {
+                if ui.button(format!("Stop ignoring {name}")).clicked() {
+                    self.ignored.retain(|n| *n != name);
+                    ui.close_menu();
+                }
+            }
+            false => {
+                if ui.button(format!("Ignore {name}")).clicked() {
+                    self.ignored.push(name.clone());
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    fn ignore_list(&mut self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .id_salt("ignored")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                let mut removed = None;
+                for (i, name) in self.ignored.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .small_button("🗑")
+                            .on_hover_text("Stop ignoring")
+                            .clicked()
+                        {
+                            removed = Some(i);
+                        }
+                        ui.monospace(name);
+                    });
+                }
+                if let Some(i) = removed {
+                    self.ignored.remove(i);
+                }
+            });
+        ui.horizontal(|ui| {
+            let response =
+                ui.add(egui::TextEdit::singleline(&mut self.new_ignored).hint_text("Process name"));
+            let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let name = self.new_ignored.trim();
+            let valid = !name.is_empty() && !self.ignored.iter().any(|n| n == name);
+            if (ui.add_enabled(valid, egui::Button::new("Ignore")).clicked() || entered) && valid {
+                self.ignored.push(name.to_owned());
+                self.new_ignored.clear();
+            }
+        });
+    }
+}
+
+// Architecture of an executable, from the machine of its ELF or PE header
+fn architecture(path: &Path) -> Option<&'static str> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut header = [0u8; 0x40];
+    file.read_exact(&mut header).ok()?;
+    let machine = if header.starts_with(b"\x7fELF") {
+        u16::from_le_bytes([header[18], header[19]])
+    } else if header.starts_with(b"MZ") {
+        let pe_offset =
+            u32::from_le_bytes([header[0x3C], header[0x3D], header[0x3E], header[0x3F]]);
+        let mut pe_header = [0u8; 6];
+        file.seek(SeekFrom::Start(pe_offset.into())).ok()?;
+        file.read_exact(&mut pe_header).ok()?;
+        if !pe_header.starts_with(b"PE\0\0") {
+            return None;
+        }
+        u16::from_le_bytes([pe_header[4], pe_header[5]])
+    } else {
+        return None;
+    };
+    match machine {
+        3 | 0x14C => Some("x86"),
+        62 | 0x8664 => Some("x64"),
+        40 | 0x1C0 | 0x1C4 => Some("arm"),
+        183 | 0xAA64 => Some("arm64"),
+        _ => None,
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h {}m ago", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d {}h ago", seconds / 86400, seconds % 86400 / 3600),
+    }
 }
 
 impl super::Dialog<Pid> for ProcessDialog {
@@ -1,7 +1,5 @@
 use std::{path::PathBuf, sync::Arc};
 
-use rs_class::ops::SystemProcess;
-
 pub type State = super::DialogState<PathBuf>;
 
 #[derive(Debug)]
@@ -19,9 +17,9 @@ impl SaveDialog {
             )
             .default_file_filter("rsclass");
         if let Some(s) = app
-            .selected_process
+            .reader
             .as_ref()
-            .and_then(|p| app.system.process(p.pid()))
+            .and_then(|r| app.system.process(r.pid()))
             .and_then(|p| p.name().to_str())
             .map(PathBuf::from)
             .and_then(|path| {
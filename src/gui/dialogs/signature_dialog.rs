@@ -0,0 +1,124 @@
+use rs_class::scan::signature::Signature;
+
+// The signature to use, None when it is removed
+pub type State = super::DialogState<Option<Signature>>;
+
+/// Edits the signature locating the root of a struct, resolved on every attach.
+#[derive(Debug)]
+pub struct SignatureDialog {
+    state: State,
+    struct_name: String,
+    pattern: String,
+    module: String,
+    rip_relative: bool,
+    operand_offset: usize,
+    instruction_length: usize,
+    offset: i64,
+    has_signature: bool,
+}
+
+impl SignatureDialog {
+    pub fn new(struct_name: String, signature: Option<&Signature>) -> Self {
+        let rip = signature.and_then(Signature::get_rip_relative);
+        Self {
+            state: State::Open,
+            struct_name,
+            pattern: signature.map_or(String::new(), |s| s.get_pattern().to_string()),
+            module: signature
+                .and_then(Signature::get_module)
+                .cloned()
+                .unwrap_or_default(),
+            rip_relative: rip.is_some(),
+            operand_offset: rip.map_or(3, |r| r.operand_offset),
+            instruction_length: rip.map_or(7, |r| r.instruction_length),
+            offset: signature.map_or(0, Signature::get_offset),
+            has_signature: signature.is_some(),
+        }
+    }
+
+    fn signature(&self) -> Result<Signature, String> {
+        let mut signature = Signature::new(&self.pattern).map_err(|e| e.to_string())?;
+        let module = self.module.trim();
+        if !module.is_empty() {
+            signature.set_module(Some(module.to_owned()));
+        }
+        if self.rip_relative {
+            signature = signature.with_rip_relative(self.operand_offset, self.instruction_length);
+        }
+        Ok(signature.with_offset(self.offset))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading(format!("Signature of {}", self.struct_name));
+        egui::Grid::new("signature_fields")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Pattern");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.pattern)
+                        .hint_text("48 8B 05 ?? ?? ?? ??")
+                        .desired_width(300.0),
+                );
+                ui.end_row();
+
+                ui.label("Module");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.module)
+                        .hint_text("Every executable region"),
+                );
+                ui.end_row();
+
+                ui.checkbox(&mut self.rip_relative, "RIP relative")
+                    .on_hover_text("Resolve to the address referenced by the matched instruction");
+                ui.add_enabled_ui(self.rip_relative, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Operand at");
+                        ui.add(egui::DragValue::new(&mut self.operand_offset).range(0..=15));
+                        ui.label("instruction length");
+                        ui.add(egui::DragValue::new(&mut self.instruction_length).range(1..=15));
+                    });
+                });
+                ui.end_row();
+
+                ui.label("Offset");
+                ui.add(egui::DragValue::new(&mut self.offset).hexadecimal(1, false, true));
+                ui.end_row();
+            });
+
+        let signature = self.signature();
+        if let Err(err) = &signature {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(signature.is_ok(), egui::Button::new("Save"))
+                .clicked()
+            {
+                self.state = State::Selected(signature.ok());
+            }
+            if ui
+                .add_enabled(self.has_signature, egui::Button::new("Remove"))
+                .clicked()
+            {
+                self.state = State::Selected(None);
+            }
+            if ui.button("Cancel").clicked() {
+                self.state = State::Cancelled;
+            }
+        });
+    }
+}
+
+impl super::Dialog<Option<Signature>> for SignatureDialog {
+    fn show(&mut self, ctx: &egui::Context) {
+        let modal = egui::Modal::new("signature_dialog".into()).show(ctx, |ui| self.ui(ui));
+        if modal.should_close() && self.state == State::Open {
+            self.state = State::Cancelled;
+        }
+    }
+
+    fn state(&self) -> &State {
+        &self.state
+    }
+}
@@ -1,24 +1,67 @@
 use egui::RichText;
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use rs_class::typing::{
+    search::{self, TypeKind, TypeQuery},
+    DataType, DataTypeEnum, StructEntry, TypeLibrary,
+};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 
 pub type State = super::DialogState<String>;
 
+const ROW_HEIGHT: f32 = 18.0;
+
+// Namespace of the category tree, with the number of types it holds
+#[derive(Debug, Default)]
+struct Category {
+    children: BTreeMap<String, Category>,
+    count: usize,
+}
+
+impl Category {
+    fn build(typedefs: &TypeLibrary) -> Self {
+        let mut root = Category::default();
+        for name in typedefs.keys() {
+            let mut node = &mut root;
+            node.count += 1;
+            for part in search::category(name) {
+                node = node.children.entry(part.to_owned()).or_default();
+                node.count += 1;
+            }
+        }
+        root
+    }
+}
+
 #[derive(Debug)]
 pub struct TypeSelectionDialog {
     state: State,
-    typedefs: Rc<RefCell<HashMap<String, crate::Typedef>>>,
+    typedefs: Rc<RefCell<TypeLibrary>>,
+    // Most recent first
+    recent: Vec<String>,
 
-    search_string: String,
+    query: TypeQuery,
+    min_size: usize,
+    max_size: usize,
     selected_string: Option<String>,
+    // Results of the last query, recomputed when it changes
+    results: Vec<String>,
+    results_query: Option<(TypeQuery, usize)>,
+    categories: Category,
 }
 
 impl TypeSelectionDialog {
-    pub fn new(typedefs: Rc<RefCell<HashMap<String, crate::Typedef>>>) -> Self {
+    pub fn new(typedefs: Rc<RefCell<TypeLibrary>>, recent: &[String]) -> Self {
+        let categories = Category::build(&typedefs.borrow());
         TypeSelectionDialog {
             state: State::Open,
-            search_string: Default::default(),
             typedefs,
+            recent: recent.to_vec(),
+            query: TypeQuery::default(),
+            min_size: 0,
+            max_size: 0,
             selected_string: Default::default(),
+            results: Vec::new(),
+            results_query: None,
+            categories,
         }
     }
 
@@ -50,7 +93,7 @@ impl TypeSelectionDialog {
             .default_size(viewport_rect.map_or(egui::vec2(f32::MAX, f32::MAX), |r| {
                 egui::vec2(r.height() * 0.75, r.width() * 0.75)
             }));
-        let window = egui::Modal::new("Process Selection Window".into()).area(area);
+        let window = egui::Modal::new("Type Selection Window".into()).area(area);
         if window
             .show(ctx, |ui| {
                 self.ui_in_window(ui);
@@ -62,30 +105,70 @@ impl TypeSelectionDialog {
     }
 
     fn ui_in_window(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Select a process");
+        ui.heading("Select a type");
 
-        ui.text_edit_singleline(&mut self.search_string);
-
-        egui::ScrollArea::vertical()
-            //.max_height(viewport_rect.map(|r| r.height()/2.0).unwrap_or(f32::MAX))
-            .show(ui, |ui| {
-                for (name, _dt) in self.typedefs.borrow().iter() {
-                    if !name
-                        .to_lowercase()
-                        .contains(&self.search_string.to_lowercase())
-                    {
-                        continue;
-                    }
-                    let label_response = ui.selectable_value(
-                        &mut self.selected_string,
-                        Some(name.clone()),
-                        RichText::new(name.to_string()),
-                    );
-                    if label_response.double_clicked() {
-                        self.state = State::Selected(name.clone());
+        let search = ui.add(
+            egui::TextEdit::singleline(&mut self.query.text)
+                .hint_text("Search names and descriptions"),
+        );
+        if self.results_query.is_none() {
+            search.request_focus();
+        }
+        ui.horizontal_wrapped(|ui| {
+            for kind in TypeKind::ALL {
+                let mut selected = self.query.kinds.contains(&kind);
+                if ui.toggle_value(&mut selected, kind.name()).changed() {
+                    match selected {
+                        true => self.query.kinds.push(kind),
+                        false => self.query.kinds.retain(|k| *k != kind),
                     }
                 }
+            }
+            ui.separator();
+            ui.label("Size:");
+            ui.add(egui::DragValue::new(&mut self.min_size).hexadecimal(1, false, true))
+                .on_hover_text("Minimum size, 0 for any");
+            ui.label("to");
+            ui.add(egui::DragValue::new(&mut self.max_size).hexadecimal(1, false, true))
+                .on_hover_text("Maximum size, 0 for any");
+        });
+        self.query.min_size = Some(self.min_size).filter(|&s| s > 0);
+        self.query.max_size = Some(self.max_size).filter(|&s| s > 0);
+        self.update_results();
+        ui.separator();
+
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                ui.set_width(180.0);
+                egui::ScrollArea::vertical()
+                    .id_salt("categories")
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        let all = format!("All ({})", self.categories.count);
+                        if ui
+                            .selectable_label(self.query.category.is_empty(), all)
+                            .clicked()
+                        {
+                            self.query.category.clear();
+                        }
+                        let mut path = Vec::new();
+                        category_tree(ui, &self.categories, &mut path, &mut self.query.category);
+                    });
             });
+            ui.separator();
+            ui.vertical(|ui| {
+                ui.set_width(260.0);
+                self.result_list(ui);
+            });
+            ui.separator();
+            ui.vertical(|ui| {
+                ui.set_min_width(300.0);
+                egui::ScrollArea::vertical()
+                    .id_salt("details")
+                    .max_height(400.0)
+                    .show(ui, |ui| self.details(ui));
+            });
+        });
 
         ui.add_space(10.0);
 
@@ -110,4 +193,162 @@ impl TypeSelectionDialog {
             },
         );
     }
+
+    fn update_results(&mut self) {
+        let typedefs = self.typedefs.borrow();
+        let key = (self.query.clone(), typedefs.len());
+        if self.results_query.as_ref() == Some(&key) {
+            return;
+        }
+        let results = search::search(&typedefs, &self.query);
+        // Recently used types first, unless they do not match
+        let mut recent: Vec<String> = Vec::new();
+        for name in &self.recent {
+            if results.contains(&name) {
+                recent.push(name.clone());
+            }
+        }
+        let others = results.into_iter().filter(|n| !recent.contains(n)).cloned();
+        self.results = recent.iter().cloned().chain(others).collect();
+        self.results_query = Some(key);
+        if !self
+            .selected_string
+            .as_ref()
+            .is_some_and(|s| self.results.contains(s))
+        {
+            self.selected_string = self.results.first().cloned();
+        }
+    }
+
+    fn result_list(&mut self, ui: &mut egui::Ui) {
+        ui.weak(format!("{} types", self.results.len()));
+        let recent_count = self
+            .results
+            .iter()
+            .take_while(|n| self.recent.contains(n))
+            .count();
+        egui::ScrollArea::vertical()
+            .id_salt("results")
+            .max_height(380.0)
+            .show_rows(ui, ROW_HEIGHT, self.results.len(), |ui, rows| {
+                for i in rows {
+                    let name = &self.results[i];
+                    let mut text = RichText::new(name.to_string());
+                    if i < recent_count {
+                        text = text.italics();
+                    }
+                    let label_response =
+                        ui.selectable_value(&mut self.selected_string, Some(name.clone()), text);
+                    let label_response = match i < recent_count {
+                        true => label_response.on_hover_text("Recently used"),
+                        false => label_response,
+                    };
+                    if label_response.double_clicked() {
+                        self.state = State::Selected(name.clone());
+                    }
+                }
+            });
+    }
+
+    fn details(&self, ui: &mut egui::Ui) {
+        let typedefs = self.typedefs.borrow();
+        let Some((name, (description, dt))) = self
+            .selected_string
+            .as_ref()
+            .and_then(|name| typedefs.get_key_value(name))
+        else {
+            ui.label("Select a type to see its layout.");
+            return;
+        };
+        ui.label(RichText::new(name).strong());
+        ui.label(format!(
+            "{}, {:#X} bytes",
+            TypeKind::of(dt).name(),
+            dt.get_size()
+        ));
+        if !description.is_empty() {
+            ui.label(description);
+        }
+        ui.separator();
+        match dt {
+            DataTypeEnum::StructDataType(s) => entry_grid(ui, s.get_entries()),
+            DataTypeEnum::UnionDataType(u) => entry_grid(ui, u.get_entries()),
+            DataTypeEnum::EnumDataType(e) => {
+                egui::Grid::new("enum_variants")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (variant, value) in e.get_variants() {
+                            ui.monospace(variant);
+                            ui.monospace(value.to_string());
+                            ui.end_row();
+                        }
+                    });
+            }
+            DataTypeEnum::FunctionDataType(f) => {
+                ui.monospace(f.prototype());
+            }
+            DataTypeEnum::PointerDataType(p) => {
+                ui.label(format!("Points to {}", p.get_pointed_datatype().get_name()));
+            }
+            DataTypeEnum::ArrayDataType(a) => {
+                ui.label(format!(
+                    "{} elements of {}",
+                    a.get_length(),
+                    a.get_element_datatype().get_name()
+                ));
+            }
+            dt => {
+                ui.label(dt.get_name());
+            }
+        }
+    }
+}
+
+// Offsets, names, types and sizes of the entries of a struct or union
+fn entry_grid(ui: &mut egui::Ui, entries: &[StructEntry]) {
+    egui::Grid::new("type_layout")
+        .striped(true)
+        .num_columns(4)
+        .show(ui, |ui| {
+            for header in ["Offset", "Name", "Type", "Size"] {
+                ui.label(RichText::new(header).strong());
+            }
+            ui.end_row();
+            for e in entries {
+                ui.monospace(format!("{:04X}", e.get_offset()));
+                ui.label(e.get_name());
+                ui.label(e.get_datatype().get_name());
+                ui.monospace(format!("{:#X}", e.get_size()));
+                ui.end_row();
+            }
+        });
+}
+
+// Collapsible namespaces, a click selects the namespace as the category filter
+fn category_tree(
+    ui: &mut egui::Ui,
+    node: &Category,
+    path: &mut Vec<String>,
+    selected: &mut Vec<String>,
+) {
+    for (name, child) in &node.children {
+        path.push(name.clone());
+        let is_selected = selected == path;
+        let label = format!("{name} ({})", child.count);
+        if child.children.is_empty() {
+            if ui.selectable_label(is_selected, label).clicked() {
+                *selected = path.clone();
+            }
+        } else {
+            let id = ui.make_persistent_id(path.join("::"));
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                .show_header(ui, |ui| {
+                    if ui.selectable_label(is_selected, label).clicked() {
+                        *selected = path.clone();
+                    }
+                })
+                .body(|ui| category_tree(ui, child, path, selected));
+        }
+        path.pop();
+    }
 }
@@ -0,0 +1,531 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::RichText;
+use rs_class::{
+    ops::{MemoryReader, ViewId},
+    symbols::Symbolizer,
+    typing::{
+        ArrayDataType, BooleanDataType, DataType, DataTypeEnum, Edit, Endianness, FloatDataType,
+        FloatPrecision, IntSize, IntegerDataType, PointerDataType, StrDataType, StrEncoding,
+        TypeLibrary,
+    },
+};
+
+use crate::gui::struct_editor::{
+    datatype_value, hex_bytes, release_links, resolve_address, stale_label,
+};
+
+pub type State = super::DialogState<()>;
+
+// Previews read at most this many bytes
+const MAX_PREVIEW_LEN: usize = 0x1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Integer,
+    Boolean,
+    Float,
+    String,
+    Pointer,
+    Array,
+    // Structs, unions, enums and functions, which are not edited here
+    Other,
+}
+
+const KINDS: [(Kind, &str); 6] = [
+    (Kind::Integer, "Integer"),
+    (Kind::Boolean, "Boolean"),
+    (Kind::Float, "Float"),
+    (Kind::String, "String"),
+    (Kind::Pointer, "Pointer"),
+    (Kind::Array, "Array"),
+];
+
+impl Kind {
+    fn of(dt: &DataTypeEnum) -> Self {
+        match dt {
+            DataTypeEnum::IntegerDataType(_) => Kind::Integer,
+            DataTypeEnum::BooleanDataType(_) => Kind::Boolean,
+            DataTypeEnum::FloatDataType(_) => Kind::Float,
+            DataTypeEnum::StrDataType(_) => Kind::String,
+            DataTypeEnum::PointerDataType(_) => Kind::Pointer,
+            DataTypeEnum::ArrayDataType(_) => Kind::Array,
+            _ => Kind::Other,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        KINDS
+            .iter()
+            .find(|(k, _)| *k == self)
+            .map_or("Other", |(_, label)| label)
+    }
+
+    // Type a typedef gets when its kind is picked
+    fn default_datatype(self) -> Option<DataTypeEnum> {
+        let byte = || {
+            IntegerDataType::default()
+                .with_size(IntSize::Integer8)
+                .with_hex(true)
+        };
+        Some(match self {
+            Kind::Integer => IntegerDataType::default().into(),
+            Kind::Boolean => BooleanDataType::default().into(),
+            Kind::Float => FloatDataType::default().into(),
+            Kind::String => StrDataType::default().with_size(32).into(),
+            Kind::Pointer => PointerDataType::new(byte().into()).into(),
+            Kind::Array => ArrayDataType::new(byte().into(), 16).into(),
+            Kind::Other => return None,
+        })
+    }
+}
+
+// Typedef being edited, applied to the library on demand
+#[derive(Debug)]
+struct Draft {
+    // Name of the edited typedef, None for a new one
+    original: Option<String>,
+    name: String,
+    description: String,
+    datatype: DataTypeEnum,
+}
+
+/// Creates, edits, duplicates and deletes typedefs, with a preview of the edited type
+/// at an address of the attached process. Changes are taken by the application as edits.
+#[derive(Debug)]
+pub struct TypedefDialog {
+    state: State,
+    typedefs: Rc<RefCell<TypeLibrary>>,
+    search: String,
+    draft: Option<Draft>,
+    error: Option<String>,
+    // Changes to apply to the project, since the last `take_edits`
+    edits: Vec<Edit>,
+    address_query: String,
+    view: Option<ViewId>,
+    // Pointers read to resolve a pointer path address
+    links: Vec<ViewId>,
+}
+
+impl TypedefDialog {
+    pub fn new(typedefs: Rc<RefCell<TypeLibrary>>) -> Self {
+        Self {
+            state: State::Open,
+            typedefs,
+            search: String::new(),
+            draft: None,
+            error: None,
+            edits: Vec::new(),
+            address_query: String::new(),
+            view: None,
+            links: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Edits made in the dialog, to be applied and recorded by the application.
+    pub fn take_edits(&mut self) -> Vec<Edit> {
+        std::mem::take(&mut self.edits)
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        mut reader: Option<&mut MemoryReader>,
+        symbolizer: &Symbolizer,
+    ) {
+        let modal = egui::Modal::new("typedef_dialog".into()).show(ctx, |ui| {
+            ui.heading("Typedefs");
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(220.0);
+                    self.list(ui);
+                });
+                ui.separator();
+                ui.vertical(|ui| {
+                    ui.set_min_width(360.0);
+                    self.details(ui, reader.as_deref_mut(), symbolizer);
+                });
+            });
+            ui.add_space(10.0);
+            egui::Sides::new().show(
+                ui,
+                |_| {},
+                |ui| {
+                    if ui.button("Close").clicked() {
+                        self.state = State::Cancelled;
+                    }
+                },
+            );
+        });
+        if modal.should_close() && self.state == State::Open {
+            self.state = State::Cancelled;
+        }
+        if self.state != State::Open {
+            if let (Some(reader), Some(view)) = (reader.as_deref_mut(), self.view.take()) {
+                reader.unwatch(view);
+            }
+            release_links(reader, &mut self.links);
+        }
+    }
+
+    fn list(&mut self, ui: &mut egui::Ui) {
+        ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search"));
+        let typedefs = self.typedefs.borrow();
+        let mut names: Vec<&String> = typedefs
+            .keys()
+            .filter(|n| n.to_lowercase().contains(&self.search.to_lowercase()))
+            .collect();
+        names.sort();
+        let mut opened = None;
+        egui::ScrollArea::vertical()
+            .max_height(360.0)
+            .show(ui, |ui| {
+                for name in names {
+                    let selected = self
+                        .draft
+                        .as_ref()
+                        .is_some_and(|d| d.original.as_ref() == Some(name));
+                    let description = &typedefs[name].0;
+                    let label = ui.selectable_label(selected, name);
+                    let label = match description.is_empty() {
+                        true => label,
+                        false => label.on_hover_text(description),
+                    };
+                    if label.clicked() {
+                        opened = Some(name.clone());
+                    }
+                }
+            });
+        if let Some(name) = opened {
+            let (description, datatype) = typedefs[&name].clone();
+            self.draft = Some(Draft {
+                original: Some(name.clone()),
+                name,
+                description,
+                datatype,
+            });
+            self.error = None;
+        }
+        ui.horizontal(|ui| {
+            if ui.button("new").clicked() {
+                self.draft = Some(Draft {
+                    original: None,
+                    name: unique_name(&typedefs, "NewType"),
+                    description: String::new(),
+                    datatype: IntegerDataType::default().into(),
+                });
+                self.error = None;
+            }
+            if ui
+                .add_enabled(self.draft.is_some(), egui::Button::new("duplicate"))
+                .clicked()
+            {
+                if let Some(draft) = &mut self.draft {
+                    draft.name = unique_name(&typedefs, &format!("{}_copy", draft.name));
+                    draft.original = None;
+                }
+            }
+            let original = self.draft.as_ref().and_then(|d| d.original.clone());
+            if ui
+                .add_enabled(original.is_some(), egui::Button::new("delete"))
+                .clicked()
+            {
+                if let Some(name) = original {
+                    let before = typedefs.get(&name).cloned();
+                    self.edits
+                        .push(Edit::new("delete typedef").with_typedef(name, before, None));
+                    self.draft = None;
+                }
+            }
+        });
+    }
+
+    fn details(
+        &mut self,
+        ui: &mut egui::Ui,
+        mut reader: Option<&mut MemoryReader>,
+        symbolizer: &Symbolizer,
+    ) {
+        let names = {
+            let typedefs = self.typedefs.borrow();
+            let mut names: Vec<String> = typedefs.keys().cloned().collect();
+            names.sort();
+            names
+        };
+        let Some(draft) = &mut self.draft else {
+            ui.label("Select a typedef, or create a new one.");
+            return;
+        };
+        egui::Grid::new("typedef_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut draft.name);
+                ui.end_row();
+                ui.label("Description:");
+                ui.text_edit_multiline(&mut draft.description);
+                ui.end_row();
+                ui.label("Kind:");
+                let mut kind = Kind::of(&draft.datatype);
+                egui::ComboBox::from_id_salt("typedef_kind")
+                    .selected_text(kind.label())
+                    .show_ui(ui, |ui| {
+                        for (k, label) in KINDS {
+                            ui.selectable_value(&mut kind, k, label);
+                        }
+                    });
+                if let Some(datatype) = kind
+                    .default_datatype()
+                    .filter(|_| kind != Kind::of(&draft.datatype))
+                {
+                    draft.datatype = datatype;
+                }
+                ui.end_row();
+                parameters(ui, &mut draft.datatype, &names, &self.typedefs.borrow());
+                ui.label("Size:");
+                ui.label(format!("{:#X}", draft.datatype.get_size()));
+                ui.end_row();
+            });
+
+        ui.horizontal(|ui| {
+            let label = match draft.original {
+                Some(_) => "apply",
+                None => "create",
+            };
+            if ui.button(label).clicked() {
+                match draft_edit(&self.typedefs.borrow(), draft) {
+                    Ok(edit) => {
+                        self.edits.push(edit);
+                        draft.original = Some(draft.name.trim().to_owned());
+                        self.error = None;
+                    }
+                    Err(err) => self.error = Some(err),
+                }
+            }
+            if let Some(err) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+        });
+        ui.separator();
+
+        ui.label(RichText::new("Preview").strong());
+        ui.add(
+            egui::TextEdit::singleline(&mut self.address_query)
+                .hint_text("0x1000, game.exe+0x10, [[game.exe+0x10]+0x8]"),
+        );
+        let address = resolve_address(
+            &self.address_query,
+            symbolizer,
+            reader.as_deref_mut(),
+            &mut self.links,
+        );
+        let Some(address) = address else {
+            return;
+        };
+        let Some(reader) = reader else {
+            ui.label("No process selected.");
+            return;
+        };
+        let size = draft.datatype.get_size();
+        let len = size.clamp(1, MAX_PREVIEW_LEN);
+        let view = match self.view {
+            Some(view) => {
+                reader.set_range(view, address, len);
+                view
+            }
+            None => *self.view.insert(reader.watch(address, len)),
+        };
+        stale_label(ui, reader, view);
+        let bytes = reader
+            .get(view)
+            .and_then(|d| d.read.get(address, len))
+            .filter(|_| size <= MAX_PREVIEW_LEN);
+        let value = bytes.map_or("??".into(), |b| {
+            datatype_value(&draft.datatype, b, symbolizer)
+        });
+        ui.monospace(value);
+        ui.weak(hex_bytes(bytes, len));
+    }
+}
+
+// Controls of the parameters of a type, in the rows of a grid
+fn parameters(ui: &mut egui::Ui, dt: &mut DataTypeEnum, names: &[String], typedefs: &TypeLibrary) {
+    match dt {
+        DataTypeEnum::IntegerDataType(i) => {
+            ui.label("Size:");
+            let mut size = i.get_int_size();
+            egui::ComboBox::from_id_salt("int_size")
+                .selected_text(format!("{} bytes", usize::from(size)))
+                .show_ui(ui, |ui| {
+                    for s in [
+                        IntSize::Integer8,
+                        IntSize::Integer16,
+                        IntSize::Integer32,
+                        IntSize::Integer64,
+                    ] {
+                        ui.selectable_value(&mut size, s, format!("{} bytes", usize::from(s)));
+                    }
+                });
+            i.set_size(size);
+            ui.end_row();
+            ui.label("");
+            ui.horizontal(|ui| {
+                let mut signed = i.is_signed();
+                if ui.checkbox(&mut signed, "signed").changed() {
+                    i.set_signed(signed);
+                }
+                let mut hex = i.is_hex();
+                if ui.checkbox(&mut hex, "hexadecimal").changed() {
+                    i.set_hex(hex);
+                }
+            });
+            ui.end_row();
+            let mut endianness = i.get_endianness();
+            endianness_row(ui, &mut endianness);
+            i.set_endianness(endianness);
+        }
+        DataTypeEnum::BooleanDataType(b) => {
+            ui.label("Size:");
+            let mut size = b.get_size();
+            if ui
+                .add(egui::DragValue::new(&mut size).range(1..=8))
+                .changed()
+            {
+                b.set_size(size);
+            }
+            ui.end_row();
+        }
+        DataTypeEnum::FloatDataType(f) => {
+            ui.label("Precision:");
+            let mut precision = f.get_precision();
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut precision, FloatPrecision::Simple, "simple");
+                ui.radio_value(&mut precision, FloatPrecision::Double, "double");
+            });
+            f.set_precision(precision);
+            ui.end_row();
+            let mut endianness = f.get_endianness();
+            endianness_row(ui, &mut endianness);
+            f.set_endianness(endianness);
+        }
+        DataTypeEnum::StrDataType(s) => {
+            ui.label("Length (bytes):");
+            let mut size = s.get_size();
+            if ui
+                .add(egui::DragValue::new(&mut size).range(1..=0x10000))
+                .changed()
+            {
+                s.set_size(size);
+            }
+            ui.end_row();
+            ui.label("Encoding:");
+            let mut encoding = s.get_encoding();
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut encoding, StrEncoding::Utf8, "UTF-8");
+                ui.radio_value(&mut encoding, StrEncoding::Utf16, "UTF-16");
+            });
+            s.set_encoding(encoding);
+            ui.end_row();
+        }
+        DataTypeEnum::PointerDataType(p) => {
+            ui.label("Points to:");
+            if let Some(name) = type_picker(ui, "pointer_target", p.get_pointed_datatype(), names) {
+                p.set_pointed_datatype(typedefs[&name].1.clone());
+            }
+            ui.end_row();
+        }
+        DataTypeEnum::ArrayDataType(a) => {
+            ui.label("Element:");
+            if let Some(name) = type_picker(ui, "array_element", a.get_element_datatype(), names) {
+                a.set_element_datatype(typedefs[&name].1.clone());
+            }
+            ui.end_row();
+            ui.label("Length:");
+            let mut length = a.get_length();
+            if ui
+                .add(egui::DragValue::new(&mut length).range(1..=0x10000))
+                .changed()
+            {
+                a.set_length(length);
+            }
+            ui.end_row();
+        }
+        _ => {
+            ui.label("");
+            ui.weak("The parameters of this kind are not editable here.");
+            ui.end_row();
+        }
+    }
+}
+
+fn endianness_row(ui: &mut egui::Ui, endianness: &mut Endianness) {
+    ui.label("Endianness:");
+    ui.horizontal(|ui| {
+        ui.radio_value(endianness, Endianness::Little, "little");
+        ui.radio_value(endianness, Endianness::Big, "big");
+    });
+    ui.end_row();
+}
+
+// Combo box of the typedefs, returns the picked name
+fn type_picker(
+    ui: &mut egui::Ui,
+    id: &str,
+    current: &DataTypeEnum,
+    names: &[String],
+) -> Option<String> {
+    let mut picked = None;
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(current.get_name())
+        .height(300.0)
+        .show_ui(ui, |ui| {
+            for name in names {
+                if ui.selectable_label(false, name).clicked() {
+                    picked = Some(name.clone());
+                }
+            }
+        });
+    picked
+}
+
+fn unique_name(typedefs: &TypeLibrary, base: &str) -> String {
+    (0..)
+        .map(|i| match i {
+            0 => base.to_owned(),
+            i => format!("{base}{i}"),
+        })
+        .find(|name| !typedefs.contains_key(name))
+        .expect("there are unused names")
+}
+
+// Edit creating, changing or renaming the typedef of a draft
+fn draft_edit(typedefs: &TypeLibrary, draft: &Draft) -> Result<Edit, String> {
+    let name = draft.name.trim().to_owned();
+    if name.is_empty() {
+        return Err("The name is empty.".into());
+    }
+    if typedefs.contains_key(&name) && draft.original.as_ref() != Some(&name) {
+        return Err(format!("{name} already exists."));
+    }
+    if draft.datatype.get_size() == 0 {
+        return Err("The type has no size.".into());
+    }
+    let typedef = (draft.description.clone(), draft.datatype.clone());
+    Ok(match &draft.original {
+        None => Edit::new("add typedef").with_typedef(name, None, Some(typedef)),
+        Some(original) if *original == name => {
+            let before = typedefs.get(&name).cloned();
+            Edit::new("edit typedef").with_typedef(name, before, Some(typedef))
+        }
+        Some(original) => {
+            let before = typedefs.get(original).cloned();
+            Edit::new("rename typedef")
+                .with_typedef(original.clone(), before, None)
+                .with_typedef(name, None, Some(typedef))
+        }
+    })
+}
@@ -0,0 +1,255 @@
+use egui::{Color32, RichText, Sense};
+use rs_class::{
+    ops::{MemoryReader, ViewId},
+    symbols::Symbolizer,
+    typing::{DataType, StructDataType},
+};
+
+use super::struct_editor::{release_links, resolve_address, stale_label, StructEditor};
+
+const BYTES_PER_ROW: usize = 16;
+// Background of the bytes of consecutive fields
+const FIELD_COLORS: [Color32; 6] = [
+    Color32::from_rgb(70, 110, 170),
+    Color32::from_rgb(150, 90, 60),
+    Color32::from_rgb(70, 140, 90),
+    Color32::from_rgb(140, 70, 140),
+    Color32::from_rgb(150, 140, 50),
+    Color32::from_rgb(60, 140, 140),
+];
+
+/// Bytes selected in the hex view, to be typed as a field of the applied struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldConversion {
+    pub tab: usize,
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// Hex and ASCII dump of the memory at an address, with the fields of a struct applied on it.
+#[derive(Debug)]
+pub struct HexView {
+    address_query: String,
+    len: usize,
+    view: Option<ViewId>,
+    // Pointers read to resolve a pointer path address
+    links: Vec<ViewId>,
+    // Same, for the root of the applied struct
+    struct_links: Vec<ViewId>,
+    // Struct tab laid over the bytes
+    applied_struct: Option<usize>,
+    // (anchor, end) offsets, both included
+    selection: Option<(usize, usize)>,
+}
+
+impl Default for HexView {
+    fn default() -> Self {
+        Self {
+            address_query: String::new(),
+            len: 0x100,
+            view: None,
+            links: Vec::new(),
+            struct_links: Vec::new(),
+            applied_struct: None,
+            selection: None,
+        }
+    }
+}
+
+impl HexView {
+    /// Forgets the watched range, which belongs to the reader of the previous process.
+    pub fn reset_view(&mut self) {
+        self.view = None;
+        self.links.clear();
+        self.struct_links.clear();
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        structs: &[StructDataType],
+        editor: &StructEditor,
+        mut reader: Option<&mut MemoryReader>,
+        symbolizer: &Symbolizer,
+    ) -> Option<FieldConversion> {
+        let mut conversion = None;
+        if self.applied_struct.is_some_and(|tab| tab >= structs.len()) {
+            self.applied_struct = None;
+        }
+        let applied = self.applied_struct.and_then(|tab| structs.get(tab));
+
+        let address = resolve_address(
+            &self.address_query,
+            symbolizer,
+            reader.as_deref_mut(),
+            &mut self.links,
+        );
+        // Root of the applied struct, its fields are laid over the bytes it covers
+        let root = match self
+            .applied_struct
+            .and_then(|tab| editor.address_query(tab))
+        {
+            Some(query) => resolve_address(
+                query,
+                symbolizer,
+                reader.as_deref_mut(),
+                &mut self.struct_links,
+            ),
+            None => {
+                release_links(reader.as_deref_mut(), &mut self.struct_links);
+                None
+            }
+        };
+        // Offset in the applied struct of the byte at an offset of the view
+        let struct_offset = |offset: usize| {
+            let (address, root, s) = (address?, root?, applied?);
+            let offset = (address + offset as u64).checked_sub(root)? as usize;
+            (offset < s.get_size()).then_some(offset)
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Address:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.address_query)
+                    .hint_text("0x1000, game.exe+0x10, [[game.exe+0x10]+0x8]"),
+            );
+            ui.label("Length:");
+            ui.add(
+                egui::DragValue::new(&mut self.len)
+                    .range(BYTES_PER_ROW..=0x10000)
+                    .speed(BYTES_PER_ROW as f64)
+                    .hexadecimal(1, false, true),
+            );
+            egui::ComboBox::from_label("Struct")
+                .selected_text(applied.map_or("None".into(), |s| s.get_name()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.applied_struct, None, "None");
+                    for (i, s) in structs.iter().enumerate() {
+                        ui.selectable_value(&mut self.applied_struct, Some(i), s.get_name());
+                    }
+                });
+            if let Some(query) = self
+                .applied_struct
+                .and_then(|tab| editor.address_query(tab))
+            {
+                if ui.button("go to struct").clicked() {
+                    self.address_query = query.to_owned();
+                }
+            }
+            // Only bytes of the applied struct can become its fields
+            let selection = self
+                .selection_range()
+                .and_then(|(start, end)| Some((struct_offset(start)?, struct_offset(end)?)));
+            if ui
+                .add_enabled(selection.is_some(), egui::Button::new("convert to field"))
+                .clicked()
+            {
+                if let (Some(tab), Some((start, end))) = (self.applied_struct, selection) {
+                    conversion = Some(FieldConversion {
+                        tab,
+                        offset: start,
+                        len: end - start + 1,
+                    });
+                }
+            }
+        });
+        ui.separator();
+
+        let Some(address) = address else {
+            ui.label("Enter an address to view its memory.");
+            return conversion;
+        };
+        let Some(reader) = reader else {
+            ui.label("No process selected.");
+            return conversion;
+        };
+        let view = match self.view {
+            Some(view) => {
+                reader.set_range(view, address, self.len);
+                view
+            }
+            None => *self.view.insert(reader.watch(address, self.len)),
+        };
+        stale_label(ui, reader, view);
+        let data = reader.get(view);
+
+        // Index of the field covering each byte of the view
+        let mut fields: Vec<Option<usize>> = vec![None; self.len];
+        if let (Some(s), Some(root)) = (applied, root) {
+            let struct_end = root + s.get_size() as u64;
+            for (idx, entry) in s.get_entries().iter().enumerate() {
+                let start = root + entry.get_offset() as u64;
+                let end = (start + entry.get_size() as u64).min(struct_end);
+                let first = start.saturating_sub(address).min(self.len as u64) as usize;
+                let last = end.saturating_sub(address).min(self.len as u64) as usize;
+                if first < last {
+                    fields[first..last].fill(Some(idx));
+                }
+            }
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let rows = self.len.div_ceil(BYTES_PER_ROW);
+        egui::ScrollArea::vertical().show_rows(ui, row_height, rows, |ui, rows| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+            for row in rows.map(|row| row * BYTES_PER_ROW) {
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("{:016X}", address + row as u64));
+                    ui.add_space(8.0);
+                    let mut ascii = String::new();
+                    let row_end = (row + BYTES_PER_ROW).min(self.len);
+                    for (offset, &field) in (row..row_end).zip(&fields[row..row_end]) {
+                        let byte = data
+                            .and_then(|d| d.read.get(address + offset as u64, 1))
+                            .map(|b| b[0]);
+                        ascii.push(match byte {
+                            Some(b) if b.is_ascii_graphic() || b == b' ' => b as char,
+                            Some(_) => '.',
+                            None => '?',
+                        });
+                        let text = byte.map_or("??".into(), |b| format!("{b:02X}"));
+                        let mut text = RichText::new(text).monospace();
+                        let field = applied.zip(field);
+                        if let Some((_, idx)) = field {
+                            text = text
+                                .background_color(FIELD_COLORS[idx % FIELD_COLORS.len()])
+                                .color(Color32::WHITE);
+                        }
+                        if self.is_selected(offset) {
+                            text = text
+                                .background_color(ui.visuals().selection.bg_fill)
+                                .color(ui.visuals().selection.stroke.color);
+                        }
+                        let response = ui.add(egui::Label::new(text).sense(Sense::click()));
+                        let response = match field {
+                            Some((s, idx)) => {
+                                response.on_hover_text(s.get_entries()[idx].get_name())
+                            }
+                            None => response,
+                        };
+                        if response.clicked() {
+                            let shift = ui.input(|i| i.modifiers.shift);
+                            self.selection = match self.selection {
+                                Some((anchor, _)) if shift => Some((anchor, offset)),
+                                _ => Some((offset, offset)),
+                            };
+                        }
+                    }
+                    ui.add_space(8.0);
+                    ui.monospace(ascii);
+                });
+            }
+        });
+        conversion
+    }
+
+    fn selection_range(&self) -> Option<(usize, usize)> {
+        self.selection
+            .map(|(anchor, end)| (anchor.min(end), anchor.max(end)))
+    }
+
+    fn is_selected(&self, offset: usize) -> bool {
+        self.selection_range()
+            .is_some_and(|(start, end)| (start..=end).contains(&offset))
+    }
+}
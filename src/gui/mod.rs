@@ -1,7 +1,18 @@
 pub mod dialogs;
+pub mod hex_view;
+pub mod pointer_scan;
+pub mod struct_editor;
+pub use dialogs::attach_dialog;
+pub use dialogs::debug_info_dialog;
+pub use dialogs::dump_dialog;
+pub use dialogs::export_dialog;
+pub use dialogs::import_dialog;
+pub use dialogs::launch_dialog;
 pub use dialogs::load_dialog;
 pub use dialogs::process_dialog;
 pub use dialogs::prompt_save_dialog;
 pub use dialogs::save_dialog;
+pub use dialogs::signature_dialog;
 pub use dialogs::type_selection_dialog;
+pub use dialogs::typedef_dialog;
 pub use dialogs::{Dialog, DialogState};
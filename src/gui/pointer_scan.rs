@@ -0,0 +1,320 @@
+use std::{path::PathBuf, sync::Arc, thread::JoinHandle};
+
+use egui::RichText;
+use egui_file_dialog::{DialogMode, FileDialog};
+use rs_class::{
+    ops::{Process, SystemProcess},
+    scan::{
+        pointer::{PointerScanResults, PointerScanner},
+        ScanError,
+    },
+    symbols::Symbolizer,
+    typing::{DataType, StructDataType},
+};
+use sysinfo::Pid;
+
+use super::struct_editor::{parse_address, StructEditor};
+
+const EXTENSION: &str = "ptrscan";
+const SAVE_OPERATION: &str = "save";
+const LOAD_OPERATION: &str = "load";
+
+/// Window looking for the pointer paths from a module to an address, to use as struct roots
+/// which survive restarts. Scans and revalidations run on a worker thread.
+#[derive(Debug)]
+pub struct PointerScanWindow {
+    open: bool,
+    target_query: String,
+    scanner: PointerScanner,
+    // Scan or revalidation of the results, in its own process handle
+    worker: Option<JoinHandle<Result<PointerScanResults, ScanError>>>,
+    results: Option<PointerScanResults>,
+    error: Option<String>,
+    // Struct tab whose root is set to a picked path
+    root_tab: Option<usize>,
+    file_dialog: FileDialog,
+}
+
+impl Default for PointerScanWindow {
+    fn default() -> Self {
+        let mut file_dialog = FileDialog::new()
+            .add_file_filter(
+                "Pointer scan",
+                Arc::new(|path| path.extension().is_some_and(|ext| ext == EXTENSION)),
+            )
+            .default_file_filter("Pointer scan");
+        if let Some(p) = dirs::document_dir() {
+            file_dialog.config_mut().initial_directory = p;
+        }
+        Self {
+            open: false,
+            target_query: String::new(),
+            scanner: PointerScanner::default(),
+            worker: None,
+            results: None,
+            error: None,
+            root_tab: None,
+            file_dialog,
+        }
+    }
+}
+
+impl PointerScanWindow {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Whether a scan or a revalidation is running.
+    pub fn is_busy(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        pid: Option<Pid>,
+        structs: &[StructDataType],
+        editor: &mut StructEditor,
+        symbolizer: &Symbolizer,
+    ) {
+        self.finish_worker();
+        self.update_file_dialog(ctx);
+        if self.root_tab.is_some_and(|tab| tab >= structs.len()) {
+            self.root_tab = None;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Pointer scan")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                self.settings(ui, pid, symbolizer);
+                ui.separator();
+                self.results(ui, structs, editor);
+            });
+        self.open = open;
+    }
+
+    fn settings(&mut self, ui: &mut egui::Ui, pid: Option<Pid>, symbolizer: &Symbolizer) {
+        let target = parse_address(&self.target_query, symbolizer);
+        egui::Grid::new("pointer_scan_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Target");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.target_query)
+                        .hint_text("0x1000, game.exe+0x10"),
+                );
+                ui.end_row();
+
+                ui.label("Max depth");
+                let mut depth = self.scanner.get_max_depth();
+                if ui
+                    .add(egui::DragValue::new(&mut depth).range(1..=8))
+                    .changed()
+                {
+                    self.scanner.set_max_depth(depth);
+                }
+                ui.end_row();
+
+                ui.label("Max offset");
+                let mut offset = self.scanner.get_max_offset();
+                if ui
+                    .add(egui::DragValue::new(&mut offset).hexadecimal(1, false, true))
+                    .changed()
+                {
+                    self.scanner.set_max_offset(offset);
+                }
+                ui.end_row();
+
+                ui.label("Max results");
+                let mut results = self.scanner.get_max_results();
+                if ui
+                    .add(egui::DragValue::new(&mut results).range(1..=1_000_000))
+                    .changed()
+                {
+                    self.scanner.set_max_results(results);
+                }
+                ui.end_row();
+
+                ui.label("Max pointers per level")
+                    .on_hover_text("Bounds the memory and time of deep scans");
+                let mut nodes = self.scanner.get_max_nodes();
+                if ui
+                    .add(egui::DragValue::new(&mut nodes).range(1..=100_000_000))
+                    .changed()
+                {
+                    self.scanner.set_max_nodes(nodes);
+                }
+                ui.end_row();
+            });
+
+        ui.horizontal(|ui| {
+            let idle = self.worker.is_none();
+            let scan = egui::Button::new("Scan");
+            if ui
+                .add_enabled(idle && pid.is_some() && target.is_some(), scan)
+                .clicked()
+            {
+                if let (Some(pid), Some(target)) = (pid, target) {
+                    let scanner = self.scanner.clone();
+                    self.error = None;
+                    self.worker = Some(std::thread::spawn(move || {
+                        let mut process = Process::new(pid);
+                        process.open()?;
+                        scanner.scan(&mut process, target)
+                    }));
+                }
+            }
+            let revalidate = egui::Button::new("Revalidate");
+            if ui
+                .add_enabled(idle && pid.is_some() && self.results.is_some(), revalidate)
+                .on_hover_text(
+                    "Keep the paths which still lead to the target, or to readable memory \
+                     when no target is given",
+                )
+                .clicked()
+            {
+                if let (Some(pid), Some(mut results)) = (pid, self.results.clone()) {
+                    self.error = None;
+                    self.worker = Some(std::thread::spawn(move || {
+                        let mut process = Process::new(pid);
+                        process.open()?;
+                        results.revalidate(&mut process, target);
+                        Ok(results)
+                    }));
+                }
+            }
+            if ui
+                .add_enabled(self.results.is_some(), egui::Button::new("Save"))
+                .clicked()
+            {
+                self.file_dialog
+                    .open(DialogMode::SaveFile, true, Some(SAVE_OPERATION));
+            }
+            if ui.add_enabled(idle, egui::Button::new("Load")).clicked() {
+                self.file_dialog
+                    .open(DialogMode::PickFile, true, Some(LOAD_OPERATION));
+            }
+            if !idle {
+                ui.spinner();
+            }
+        });
+        if pid.is_none() {
+            ui.weak("Open a process to scan it.");
+        }
+        if let Some(err) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+    }
+
+    fn results(
+        &mut self,
+        ui: &mut egui::Ui,
+        structs: &[StructDataType],
+        editor: &mut StructEditor,
+    ) {
+        let Some(results) = &self.results else {
+            ui.label("No results.");
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} paths to {:#X}",
+                results.paths.len(),
+                results.target
+            ));
+            egui::ComboBox::from_label("Root of")
+                .selected_text(
+                    self.root_tab
+                        .and_then(|tab| structs.get(tab))
+                        .map_or("None".into(), |s| s.get_name()),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.root_tab, None, "None");
+                    for (i, s) in structs.iter().enumerate() {
+                        ui.selectable_value(&mut self.root_tab, Some(i), s.get_name());
+                    }
+                });
+        });
+        if results.truncated {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "The pointer limit was reached, some paths may be missing",
+            );
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical().show_rows(ui, row_height, results.paths.len(), |ui, rows| {
+            for path in &results.paths[rows] {
+                ui.horizontal(|ui| {
+                    let text = path.to_string();
+                    if ui
+                        .add_enabled(self.root_tab.is_some(), egui::Button::new("use"))
+                        .on_hover_text("Locate the selected struct with this path")
+                        .clicked()
+                    {
+                        if let Some(tab) = self.root_tab {
+                            editor.set_address_query(tab, text.clone());
+                        }
+                    }
+                    if ui.button("copy").clicked() {
+                        ui.ctx().copy_text(text.clone());
+                    }
+                    ui.label(RichText::new(text).monospace());
+                });
+            }
+        });
+    }
+
+    // Takes the results of the finished scan or revalidation
+    fn finish_worker(&mut self) {
+        if !self.worker.as_ref().is_some_and(JoinHandle::is_finished) {
+            return;
+        }
+        let Some(result) = self.worker.take().map(JoinHandle::join) else {
+            return;
+        };
+        match result {
+            Ok(Ok(results)) => {
+                println!("Found {} pointer paths", results.paths.len());
+                self.results = Some(results);
+            }
+            Ok(Err(err)) => self.error = Some(format!("Could not scan the pointers: {err}")),
+            Err(_) => self.error = Some("The pointer scan thread panicked".into()),
+        }
+    }
+
+    fn update_file_dialog(&mut self, ctx: &egui::Context) {
+        self.file_dialog.update(ctx);
+        let operation = self.file_dialog.operation_id().map(str::to_owned);
+        let Some(path) = self.file_dialog.take_picked() else {
+            return;
+        };
+        match operation.as_deref() {
+            Some(SAVE_OPERATION) => {
+                let path = with_extension(path);
+                if let Some(Err(err)) = self.results.as_ref().map(|r| r.save(&path)) {
+                    eprintln!("ERROR: Could not save the pointer scan: {err}");
+                }
+            }
+            Some(LOAD_OPERATION) => match PointerScanResults::load(&path) {
+                Ok(results) => {
+                    // The saved target is stale after a restart, revalidation keeps the
+                    // readable paths until a new target is given
+                    self.target_query.clear();
+                    self.results = Some(results);
+                }
+                Err(err) => eprintln!("ERROR: Could not load the pointer scan: {err}"),
+            },
+            _ => {}
+        }
+    }
+}
+
+fn with_extension(path: PathBuf) -> PathBuf {
+    match path.extension() {
+        Some(_) => path,
+        None => path.with_extension(EXTENSION),
+    }
+}
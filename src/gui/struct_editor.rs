@@ -0,0 +1,792 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use egui::{Color32, RichText};
+use rs_class::{
+    ops::{MemoryReader, ViewData, ViewId},
+    scan::{pointer::PointerPath, ScanValue},
+    snapshot::FieldHistory,
+    symbols::Symbolizer,
+    typing::{
+        DataType, DataTypeEnum, Edit, EditHistory, IntSize, IntegerDataType, StructChange,
+        StructDataType, StructEntry,
+    },
+};
+
+// Undefined bytes are shown by rows of this many bytes
+const GAP_ROW_SIZE: usize = 8;
+// Changed values are highlighted for this long
+const CHANGE_FADE: Duration = Duration::from_millis(1500);
+const CHANGE_COLOR: Color32 = Color32::from_rgb(200, 60, 60);
+const PLOT_SIZE: egui::Vec2 = egui::vec2(320.0, 60.0);
+// Pointer paths are followed with 64-bit pointers
+const POINTER_SIZE: usize = 8;
+
+#[derive(Debug, Default)]
+struct TabState {
+    address_query: String,
+    view: Option<ViewId>,
+    // Pointers read to resolve a pointer path address
+    links: Vec<ViewId>,
+    selected_entry: Option<usize>,
+    history: FieldHistory,
+    // Address the history was recorded at
+    history_address: Option<u64>,
+    // Offsets of the plotted fields
+    plotted: BTreeSet<usize>,
+    // Offset and text of the value being edited
+    editing: Option<(usize, String)>,
+    edit_error: Option<String>,
+}
+
+/// What the editor needs from the application after a frame.
+#[derive(Debug, Default)]
+pub struct EditorResponse {
+    // (tab, entry) whose type should be picked with the type selection dialog
+    pub retype: Option<(usize, usize)>,
+    // Tab whose root signature should be edited
+    pub edit_signature: Option<usize>,
+    // (old name, new name) of a renamed struct
+    pub renamed: Option<(String, String)>,
+}
+
+enum Row {
+    Entry(usize),
+    Gap { offset: usize, len: usize },
+}
+
+/// ReClass-style view of the struct tabs: one row per field with its value in the process.
+#[derive(Debug)]
+pub struct StructEditor {
+    selected_tab: usize,
+    tabs: Vec<TabState>,
+    bytes_to_add: usize,
+}
+
+impl Default for StructEditor {
+    fn default() -> Self {
+        Self {
+            selected_tab: 0,
+            tabs: Vec::new(),
+            bytes_to_add: 8,
+        }
+    }
+}
+
+impl StructEditor {
+    /// Forgets the watched ranges, which belong to the reader of the previous process.
+    pub fn reset_views(&mut self) {
+        for tab in &mut self.tabs {
+            tab.view = None;
+            tab.links.clear();
+            tab.history.clear();
+            tab.history_address = None;
+        }
+    }
+
+    /// Address expression of the struct of a tab.
+    pub fn address_query(&self, tab: usize) -> Option<&str> {
+        self.tabs
+            .get(tab)
+            .map(|t| t.address_query.as_str())
+            .filter(|q| !q.trim().is_empty())
+    }
+
+    /// Sets the address expression of the struct of a tab, e.g. once its signature is resolved.
+    pub fn set_address_query(&mut self, tab: usize, query: String) {
+        if self.tabs.len() <= tab {
+            self.tabs.resize_with(tab + 1, Default::default);
+        }
+        self.tabs[tab].address_query = query;
+    }
+
+    /// Forgets the state of the tabs, e.g. when other structs are loaded.
+    pub fn clear(&mut self, mut reader: Option<&mut MemoryReader>) {
+        for mut tab in self.tabs.drain(..) {
+            release_tab(reader.as_deref_mut(), &mut tab);
+        }
+        self.selected_tab = 0;
+    }
+
+    /// Inserts and removes tab states along with the structs of an undo or a redo.
+    pub fn follow_struct_changes(
+        &mut self,
+        changes: &[StructChange],
+        mut reader: Option<&mut MemoryReader>,
+    ) {
+        for change in changes {
+            match *change {
+                StructChange::Inserted(index) => {
+                    if self.tabs.len() < index {
+                        self.tabs.resize_with(index, Default::default);
+                    }
+                    self.tabs.insert(index, TabState::default());
+                    self.selected_tab = index;
+                }
+                StructChange::Removed(index) if index < self.tabs.len() => {
+                    let mut tab = self.tabs.remove(index);
+                    release_tab(reader.as_deref_mut(), &mut tab);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        structs: &mut Vec<StructDataType>,
+        edits: &mut EditHistory,
+        mut reader: Option<&mut MemoryReader>,
+        symbolizer: &Symbolizer,
+    ) -> EditorResponse {
+        let mut response = EditorResponse::default();
+        self.tabs.resize_with(structs.len(), Default::default);
+        self.selected_tab = self.selected_tab.min(structs.len().saturating_sub(1));
+
+        ui.horizontal_wrapped(|ui| {
+            for (i, s) in structs.iter().enumerate() {
+                ui.selectable_value(&mut self.selected_tab, i, s.get_name());
+            }
+            if ui.button("+ new struct").clicked() {
+                let name = format!("Struct{}", structs.len());
+                let mut s = StructDataType::new(name, Vec::new());
+                s.set_size(0x40);
+                edits.record(Edit::new("add struct").with_added_struct(structs.len(), s.clone()));
+                structs.push(s);
+                self.tabs.push(TabState::default());
+                self.selected_tab = structs.len() - 1;
+            }
+        });
+        ui.separator();
+
+        let tab_idx = self.selected_tab;
+        let (Some(s), Some(tab)) = (structs.get_mut(tab_idx), self.tabs.get_mut(tab_idx)) else {
+            ui.label("No struct, create one to start.");
+            return response;
+        };
+
+        let mut delete_struct = false;
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            let mut name = s.get_name();
+            let edit = ui.text_edit_singleline(&mut name);
+            if edit.changed() {
+                response.renamed = Some((s.get_name(), name.clone()));
+                edit_struct(edits, tab_idx, s, "rename struct", true, |s| {
+                    s.set_name(name)
+                });
+            }
+            if edit.lost_focus() {
+                edits.seal();
+            }
+            ui.label("Address:");
+            ui.add(
+                egui::TextEdit::singleline(&mut tab.address_query)
+                    .hint_text("0x1000, game.exe+0x10, [[game.exe+0x10]+0x8]"),
+            );
+            if ui
+                .button("signature")
+                .on_hover_text("Locate the struct with a code pattern on every attach")
+                .clicked()
+            {
+                response.edit_signature = Some(tab_idx);
+            }
+            ui.label(format!("Size: {:#X}", s.get_size()));
+            if ui.button("delete struct").clicked() {
+                delete_struct = true;
+            }
+        });
+        if delete_struct {
+            release_tab(reader.as_deref_mut(), tab);
+            let s = structs.remove(tab_idx);
+            edits.record(Edit::new("delete struct").with_removed_struct(tab_idx, s));
+            self.tabs.remove(tab_idx);
+            self.selected_tab = tab_idx.saturating_sub(1);
+            return response;
+        }
+
+        // Watch the struct range
+        let address = resolve_address(
+            &tab.address_query,
+            symbolizer,
+            reader.as_deref_mut(),
+            &mut tab.links,
+        );
+        let data = match (reader.as_deref_mut(), address) {
+            (Some(reader), Some(address)) => {
+                let view = match tab.view {
+                    Some(view) => {
+                        reader.set_range(view, address, s.get_size());
+                        view
+                    }
+                    None => *tab.view.insert(reader.watch(address, s.get_size())),
+                };
+                stale_label(ui, reader, view);
+                reader.get(view).cloned()
+            }
+            _ => None,
+        };
+
+        if let (Some(data), Some(address)) = (data.as_ref(), address) {
+            record_history(tab, s, data, address);
+        }
+
+        Self::toolbar(ui, s, tab_idx, tab, edits, &mut self.bytes_to_add);
+        if let Some(err) = &tab.edit_error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        ui.separator();
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui::Grid::new(("struct_grid", tab_idx))
+                .striped(true)
+                .num_columns(5)
+                .show(ui, |ui| {
+                    for header in ["Offset", "Address", "Name", "Type", "Value"] {
+                        ui.label(RichText::new(header).strong());
+                    }
+                    ui.end_row();
+
+                    for row in rows(s) {
+                        match row {
+                            Row::Entry(idx) => {
+                                let offset = s.get_entries()[idx].get_offset();
+                                let selected = tab.selected_entry == Some(idx);
+                                if ui
+                                    .selectable_label(selected, format!("{offset:04X}"))
+                                    .clicked()
+                                {
+                                    tab.selected_entry = Some(idx);
+                                }
+                                ui.monospace(address_text(address, offset));
+                                let mut name = s.get_entries()[idx].get_name().clone();
+                                let edit = ui.add(
+                                    egui::TextEdit::singleline(&mut name).desired_width(140.0),
+                                );
+                                if edit.changed() {
+                                    let label = format!("rename field {offset:#X}");
+                                    edit_struct(edits, tab_idx, s, &label, true, |s| {
+                                        if let Some(entry) = s.get_entry_mut(idx) {
+                                            entry.set_name(name);
+                                        }
+                                    });
+                                }
+                                if edit.lost_focus() {
+                                    edits.seal();
+                                }
+                                let entry = &s.get_entries()[idx];
+                                if ui.button(entry.get_datatype().get_name()).clicked() {
+                                    tab.selected_entry = Some(idx);
+                                    response.retype = Some((tab_idx, idx));
+                                }
+                                let bytes =
+                                    field_bytes(data.as_ref(), address, offset, entry.get_size());
+                                ui.horizontal(|ui| {
+                                    Self::value_cell(
+                                        ui,
+                                        tab,
+                                        entry,
+                                        address.map(|a| a + offset as u64),
+                                        bytes,
+                                        reader.as_deref_mut(),
+                                        symbolizer,
+                                    );
+                                });
+                            }
+                            Row::Gap { offset, len } => {
+                                ui.weak(format!("{offset:04X}"));
+                                ui.monospace(address_text(address, offset));
+                                ui.weak("");
+                                ui.weak(format!("undefined[{len}]"));
+                                let bytes = field_bytes(data.as_ref(), address, offset, len);
+                                ui.monospace(hex_bytes(bytes, len));
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            Self::plots(ui, s, tab);
+        });
+        response
+    }
+
+    // Value of a field, edited on click, with its freeze and plot toggles
+    fn value_cell(
+        ui: &mut egui::Ui,
+        tab: &mut TabState,
+        entry: &StructEntry,
+        field_address: Option<u64>,
+        bytes: Option<&[u8]>,
+        reader: Option<&mut MemoryReader>,
+        symbolizer: &Symbolizer,
+    ) {
+        let offset = entry.get_offset();
+        let numeric = bytes.is_some_and(|b| numeric_value(entry, b).is_some());
+        // Only primitive values of an attached process can be written
+        let mut target = reader.zip(field_address).zip(bytes).filter(|_| numeric);
+
+        match (&mut tab.editing, target.as_mut()) {
+            (Some((editing, text)), Some(((reader, field_address), bytes)))
+                if *editing == offset =>
+            {
+                let field_address = *field_address;
+                let edit = ui.add(egui::TextEdit::singleline(text).desired_width(120.0));
+                if !edit.has_focus() && !edit.lost_focus() {
+                    edit.request_focus();
+                }
+                if edit.lost_focus() {
+                    // Escape or a click elsewhere cancels the edit
+                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        match parse_value(entry, text, bytes) {
+                            Ok(new_bytes) => {
+                                let bitfield = entry.get_bitfield();
+                                if reader.get_frozen(field_address, bitfield).is_some() {
+                                    reader.freeze(field_address, bitfield, new_bytes.clone());
+                                }
+                                reader.write(field_address, new_bytes);
+                                tab.edit_error = None;
+                            }
+                            Err(err) => {
+                                tab.edit_error = Some(format!("{}: {err}", entry.get_name()))
+                            }
+                        }
+                    }
+                    tab.editing = None;
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        let mut text = RichText::new(entry_value(entry, bytes, symbolizer)).monospace();
+        if let Some(color) = change_color(ui, &tab.history, offset) {
+            text = text.background_color(color);
+        }
+        let label = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+        let Some(((reader, field_address), bytes)) = target else {
+            return;
+        };
+        if label.on_hover_text("Click to edit").clicked() {
+            tab.editing = Some((offset, edit_text(entry, bytes)));
+        }
+        let bitfield = entry.get_bitfield();
+        let mut frozen = reader.get_frozen(field_address, bitfield).is_some();
+        if ui
+            .toggle_value(&mut frozen, "❄")
+            .on_hover_text("Freeze the value")
+            .changed()
+        {
+            if frozen {
+                reader.freeze(field_address, bitfield, bytes.to_vec());
+            } else {
+                reader.unfreeze(field_address, bitfield);
+            }
+        }
+        let mut plotted = tab.plotted.contains(&offset);
+        if ui
+            .toggle_value(&mut plotted, "📈")
+            .on_hover_text("Plot the value over time")
+            .changed()
+        {
+            if plotted {
+                tab.plotted.insert(offset);
+            } else {
+                tab.plotted.remove(&offset);
+            }
+        }
+    }
+
+    // Graphs of the plotted fields, over their recorded history
+    fn plots(ui: &mut egui::Ui, s: &StructDataType, tab: &mut TabState) {
+        let entries = s.get_entries();
+        // Fields removed or moved since they were plotted
+        tab.plotted
+            .retain(|offset| entries.iter().any(|e| e.get_offset() == *offset));
+        if tab.plotted.is_empty() {
+            return;
+        }
+        ui.separator();
+        for &offset in &tab.plotted {
+            let Some(entry) = entries.iter().find(|e| e.get_offset() == offset) else {
+                continue;
+            };
+            let points: Vec<(f64, f64)> = {
+                let samples: Vec<_> = tab.history.samples(offset).collect();
+                let Some(&(start, _)) = samples.first() else {
+                    continue;
+                };
+                samples
+                    .into_iter()
+                    .filter_map(|(time, bytes)| {
+                        let value = numeric_value(entry, bytes)?;
+                        Some((time.duration_since(start).as_secs_f64(), value))
+                    })
+                    .collect()
+            };
+            let min = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+            let max = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(entry.get_name()).strong());
+                if let Some(last) = points.last() {
+                    ui.weak(format!("last {} min {min} max {max}", last.1));
+                }
+            });
+            plot(ui, &points, min, max);
+        }
+    }
+
+    // Row operations, recorded in the edit history
+    fn toolbar(
+        ui: &mut egui::Ui,
+        s: &mut StructDataType,
+        tab_idx: usize,
+        tab: &mut TabState,
+        edits: &mut EditHistory,
+        bytes_to_add: &mut usize,
+    ) {
+        ui.horizontal(|ui| {
+            if ui.button("add field").clicked() {
+                edit_struct(edits, tab_idx, s, "add field", false, |s| {
+                    s.insert_entry(s.get_entries().len(), new_entry(s.get_size()));
+                });
+                tab.selected_entry = Some(s.get_entries().len() - 1);
+            }
+            let count = s.get_entries().len();
+            let selected = tab.selected_entry.filter(|&i| i < count);
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new("insert field"))
+                .clicked()
+            {
+                if let Some(idx) = selected {
+                    edit_struct(edits, tab_idx, s, "insert field", false, |s| {
+                        let offset = s.get_entries()[idx].get_offset();
+                        s.insert_entry(idx, new_entry(offset));
+                    });
+                }
+            }
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new("delete field"))
+                .clicked()
+            {
+                if let Some(idx) = selected {
+                    edit_struct(edits, tab_idx, s, "delete field", false, |s| {
+                        s.remove_entry(idx);
+                    });
+                    tab.selected_entry = None;
+                }
+            }
+            let up = selected.filter(|&i| i > 0);
+            if ui
+                .add_enabled(up.is_some(), egui::Button::new("move up"))
+                .clicked()
+            {
+                if let Some(idx) = up {
+                    // Fields sharing bytes are not moved
+                    let mut moved = s.clone();
+                    if moved.swap_entries(idx - 1) {
+                        edit_struct(edits, tab_idx, s, "move field", false, |s| *s = moved);
+                        tab.selected_entry = Some(idx - 1);
+                    }
+                }
+            }
+            let down = selected.filter(|&i| i + 1 < count);
+            if ui
+                .add_enabled(down.is_some(), egui::Button::new("move down"))
+                .clicked()
+            {
+                if let Some(idx) = down {
+                    // Fields sharing bytes are not moved
+                    let mut moved = s.clone();
+                    if moved.swap_entries(idx) {
+                        edit_struct(edits, tab_idx, s, "move field", false, |s| *s = moved);
+                        tab.selected_entry = Some(idx + 1);
+                    }
+                }
+            }
+            ui.separator();
+            ui.add(egui::DragValue::new(bytes_to_add).range(1..=0x10000));
+            if ui.button("add bytes").clicked() {
+                edit_struct(edits, tab_idx, s, "add bytes", false, |s| {
+                    s.insert_bytes(s.get_size(), *bytes_to_add);
+                });
+            }
+        });
+    }
+}
+
+/// Changes the struct at `index` of the project, recording the change in the edit history.
+/// Merged edits are used for text fields, changed a keystroke at a time.
+pub fn edit_struct(
+    edits: &mut EditHistory,
+    index: usize,
+    s: &mut StructDataType,
+    label: &str,
+    merged: bool,
+    f: impl FnOnce(&mut StructDataType),
+) {
+    let before = s.clone();
+    f(s);
+    let edit = Edit::new(label).with_modified_struct(index, before, s.clone());
+    match merged {
+        true => edits.record_merged(edit),
+        false => edits.record(edit),
+    }
+}
+
+// Records the fields of a read, the history restarts when the struct moves
+fn record_history(tab: &mut TabState, s: &StructDataType, data: &ViewData, address: u64) {
+    if tab.history_address != Some(address) {
+        tab.history.clear();
+        tab.history_address = Some(address);
+    }
+    for entry in s.get_entries() {
+        let offset = entry.get_offset();
+        if let Some(bytes) = data.read.get(address + offset as u64, entry.get_size()) {
+            tab.history.record(offset, bytes, data.updated);
+        }
+    }
+}
+
+// Background of a recently changed value, fading out
+fn change_color(ui: &egui::Ui, history: &FieldHistory, offset: usize) -> Option<Color32> {
+    let elapsed = history.last_change(offset)?.elapsed();
+    if elapsed >= CHANGE_FADE {
+        return None;
+    }
+    ui.ctx().request_repaint();
+    let alpha = 1.0 - elapsed.as_secs_f32() / CHANGE_FADE.as_secs_f32();
+    Some(CHANGE_COLOR.gamma_multiply(alpha))
+}
+
+// Value of a primitive field, as plotted
+fn numeric_value(entry: &StructEntry, bytes: &[u8]) -> Option<f64> {
+    if let Some(bitfield) = entry.get_bitfield() {
+        return Some(bitfield.extract(bytes) as f64);
+    }
+    match ScanValue::decode(entry.get_datatype(), bytes).ok()? {
+        ScanValue::Int(value) => Some(value as f64),
+        ScanValue::Float(value) => Some(value).filter(|v| v.is_finite()),
+    }
+}
+
+// Text of a value when its edition starts, parsed back by `parse_value`
+fn edit_text(entry: &StructEntry, bytes: &[u8]) -> String {
+    match (entry.get_bitfield(), entry.get_datatype()) {
+        (Some(bitfield), _) => bitfield.extract(bytes).to_string(),
+        (None, DataTypeEnum::PointerDataType(p)) => p
+            .address(bytes)
+            .map_or_else(String::new, |a| format!("{a:#X}")),
+        (None, dt) => dt.bytes_to_string(bytes).unwrap_or_default(),
+    }
+}
+
+// Bytes to write for a value typed by the user, bitfields are written in `current`
+fn parse_value(entry: &StructEntry, text: &str, current: &[u8]) -> Result<Vec<u8>, String> {
+    let Some(bitfield) = entry.get_bitfield() else {
+        let dt = entry.get_datatype();
+        return ScanValue::parse(dt, text)
+            .and_then(|value| value.encode(dt))
+            .map_err(|err| err.to_string());
+    };
+    let unit = IntegerDataType::default()
+        .with_size(IntSize::Integer64)
+        .into();
+    let value = match ScanValue::parse(&unit, text).map_err(|err| err.to_string())? {
+        ScanValue::Int(value) if value >= 0 && value >> bitfield.bit_size == 0 => value as u64,
+        _ => return Err(format!("{text} does not fit in {} bits", bitfield.bit_size)),
+    };
+    let mut bytes = current.to_vec();
+    bitfield.insert(&mut bytes, value);
+    Ok(bytes)
+}
+
+// Line graph of (seconds, value) points
+fn plot(ui: &mut egui::Ui, points: &[(f64, f64)], min: f64, max: f64) {
+    let (response, painter) = ui.allocate_painter(PLOT_SIZE, egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let duration = points.last().map_or(0.0, |p| p.0);
+    // Constant values are drawn in the middle
+    let range = if max > min { max - min } else { 1.0 };
+    let offset = if max > min { 0.0 } else { 0.5 };
+    let line: Vec<egui::Pos2> = points
+        .iter()
+        .map(|&(time, value)| {
+            let x = if duration > 0.0 { time / duration } else { 1.0 };
+            let y = (value - min) / range + offset;
+            egui::pos2(
+                rect.left() + x as f32 * rect.width(),
+                rect.bottom() - y as f32 * rect.height(),
+            )
+        })
+        .collect();
+    let stroke = egui::Stroke::new(1.5, ui.visuals().selection.bg_fill);
+    match line.as_slice() {
+        [] => {}
+        [point] => {
+            painter.circle_filled(*point, 2.0, stroke.color);
+        }
+        _ => {
+            painter.add(egui::Shape::line(line, stroke));
+        }
+    }
+}
+
+fn new_entry(offset: usize) -> StructEntry {
+    let dt = IntegerDataType::default().with_hex(true);
+    StructEntry::new(format!("field_{offset:02X}"), dt.into())
+}
+
+// Entries and the undefined bytes between them, in offset order
+fn rows(s: &StructDataType) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut end = 0;
+    let push_gap = |rows: &mut Vec<Row>, from: usize, to: usize| {
+        let mut offset = from;
+        while offset < to {
+            // Rows are aligned, so that the following ones start on a multiple of their size
+            let len = (GAP_ROW_SIZE - offset % GAP_ROW_SIZE).min(to - offset);
+            rows.push(Row::Gap { offset, len });
+            offset += len;
+        }
+    };
+    for (i, e) in s.get_entries().iter().enumerate() {
+        if e.get_offset() > end {
+            push_gap(&mut rows, end, e.get_offset());
+        }
+        rows.push(Row::Entry(i));
+        end = end.max(e.get_offset() + e.get_size());
+    }
+    push_gap(&mut rows, end, s.get_size());
+    rows
+}
+
+/// Parses an hexadecimal address, or a module relative expression (`game.exe+0x10`).
+pub fn parse_address(query: &str, symbolizer: &Symbolizer) -> Option<u64> {
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+    u64::from_str_radix(query.trim_start_matches("0x"), 16)
+        .ok()
+        .or_else(|| symbolizer.resolve(query))
+}
+
+/// Resolves an address like [`parse_address`], or a pointer path (`[[game.exe+0x10]+0x8]`)
+/// whose pointers are read by `links`, one watched range per dereference.
+pub fn resolve_address(
+    query: &str,
+    symbolizer: &Symbolizer,
+    reader: Option<&mut MemoryReader>,
+    links: &mut Vec<ViewId>,
+) -> Option<u64> {
+    let path = match parse_address(query, symbolizer) {
+        Some(address) => {
+            release_links(reader, links);
+            return Some(address);
+        }
+        None => query.parse::<PointerPath>().ok(),
+    };
+    let Some(path) = path else {
+        release_links(reader, links);
+        return None;
+    };
+    let Some(reader) = reader else {
+        links.clear();
+        return None;
+    };
+    while links.len() > path.offsets.len() {
+        reader.unwatch(links.pop().expect("links is not empty"));
+    }
+    let mut depth = 0;
+    path.resolve_with(symbolizer.modules(), |address| {
+        let view = match links.get(depth) {
+            Some(&view) => {
+                reader.set_range(view, address, POINTER_SIZE);
+                view
+            }
+            None => {
+                links.push(reader.watch(address, POINTER_SIZE));
+                links[depth]
+            }
+        };
+        depth += 1;
+        let bytes = reader.get(view)?.read.get(address, POINTER_SIZE)?;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    })
+}
+
+/// Warns that the data of a view was not read recently, e.g. while the process is busy.
+pub fn stale_label(ui: &mut egui::Ui, reader: &MemoryReader, view: ViewId) {
+    if reader.is_stale(view) {
+        let staleness = reader.staleness(view).map_or("never read".into(), |d| {
+            format!("{:.1}s old", d.as_secs_f32())
+        });
+        ui.colored_label(ui.visuals().warn_fg_color, format!("Stale: {staleness}"));
+    }
+}
+
+// Stops watching the struct range and the pointers of a tab
+fn release_tab(reader: Option<&mut MemoryReader>, tab: &mut TabState) {
+    if let Some(reader) = reader {
+        tab.view
+            .take()
+            .into_iter()
+            .for_each(|view| reader.unwatch(view));
+        release_links(Some(reader), &mut tab.links);
+    } else {
+        tab.view = None;
+        tab.links.clear();
+    }
+}
+
+/// Stops watching the pointers of a pointer path address.
+pub fn release_links(reader: Option<&mut MemoryReader>, links: &mut Vec<ViewId>) {
+    match reader {
+        Some(reader) => links.drain(..).for_each(|view| reader.unwatch(view)),
+        None => links.clear(),
+    }
+}
+
+fn address_text(address: Option<u64>, offset: usize) -> String {
+    address.map_or_else(String::new, |a| format!("{:X}", a + offset as u64))
+}
+
+fn field_bytes(
+    data: Option<&ViewData>,
+    address: Option<u64>,
+    offset: usize,
+    len: usize,
+) -> Option<&[u8]> {
+    data?.read.get(address? + offset as u64, len)
+}
+
+pub fn hex_bytes(bytes: Option<&[u8]>, len: usize) -> String {
+    match bytes {
+        Some(bytes) => bytes
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => vec!["??"; len].join(" "),
+    }
+}
+
+fn entry_value(entry: &StructEntry, bytes: Option<&[u8]>, symbolizer: &Symbolizer) -> String {
+    let Some(bytes) = bytes else {
+        return "??".into();
+    };
+    if let Some(bitfield) = entry.get_bitfield() {
+        return bitfield.extract(bytes).to_string();
+    }
+    datatype_value(entry.get_datatype(), bytes, symbolizer)
+}
+
+/// Text of a value of `dt`, pointers are shown with the symbol they point to.
+pub fn datatype_value(dt: &DataTypeEnum, bytes: &[u8], symbolizer: &Symbolizer) -> String {
+    symbolizer
+        .format_pointer(dt, bytes)
+        .or_else(|| dt.bytes_to_string(bytes).ok())
+        .unwrap_or_else(|| "{...}".into())
+}
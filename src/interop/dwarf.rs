@@ -0,0 +1,671 @@
+/***
+ * DWARF debug information import
+ * Reads the type DIEs of an ELF file, or of its separate debug file, into the type library.
+ */
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use gimli::{
+    AttributeValue, DebuggingInformationEntry, DwAt, DwTag, EndianSlice, Operation, RunTimeEndian,
+    Unit, UnitOffset,
+};
+use object::{Object, ObjectSection, ReadCache};
+
+use super::{qualify_name, ImportError};
+use crate::typing::{
+    ArrayDataType, BooleanDataType, DataType, DataTypeEnum, EnumDataType, FloatDataType,
+    FloatPrecision, FunctionDataType, IntSize, IntegerDataType, PointerDataType, StructDataType,
+    StructEntry, TypeLibrary, UnionDataType,
+};
+
+type Reader<'a> = EndianSlice<'a, RunTimeEndian>;
+
+impl From<gimli::Error> for ImportError {
+    fn from(value: gimli::Error) -> Self {
+        ImportError::ParseError(value.to_string())
+    }
+}
+
+impl From<object::Error> for ImportError {
+    fn from(value: object::Error) -> Self {
+        ImportError::ParseError(value.to_string())
+    }
+}
+
+/// Returns whether DWARF types can be loaded for this file, either from itself or from its separate debug file.
+pub fn has_debug_info(path: &Path) -> bool {
+    debug_file(path).is_some()
+}
+
+/// Imports the structures, unions, enums and typedefs described by the DWARF information of an ELF file.
+/// The separate debug file is used when the file itself is stripped.
+pub fn import_file(path: &Path) -> Result<TypeLibrary, ImportError> {
+    let debug_path = debug_file(path).ok_or_else(|| {
+        ImportError::UnsupportedError(format!("{} has no debug information.", path.display()))
+    })?;
+    let data = std::fs::read(debug_path)?;
+    import_bytes(&data)
+}
+
+/// Imports the DWARF types of an in-memory object file.
+pub fn import_bytes(data: &[u8]) -> Result<TypeLibrary, ImportError> {
+    let file = object::File::parse(data)?;
+    let endian = if file.is_little_endian() {
+        RunTimeEndian::Little
+    } else {
+        RunTimeEndian::Big
+    };
+
+    let sections = gimli::DwarfSections::load(|id| -> Result<Cow<[u8]>, ImportError> {
+        Ok(match file.section_by_name(id.name()) {
+            Some(section) => section.uncompressed_data()?,
+            None => Cow::Borrowed(&[]),
+        })
+    })?;
+    let dwarf = sections.borrow(|section| EndianSlice::new(section, endian));
+
+    let mut units = Vec::new();
+    let mut iter = dwarf.units();
+    while let Some(header) = iter.next()? {
+        units.push(dwarf.unit(header)?);
+    }
+    let mut importer = Importer {
+        dwarf: &dwarf,
+        units: &units,
+        resolved: HashMap::new(),
+        in_progress: HashSet::new(),
+        names: HashMap::new(),
+    };
+
+    // Namespaces are only known while walking the tree, name every type DIE first
+    let mut declarations = Vec::new();
+    for (idx, unit) in units.iter().enumerate() {
+        let mut tree = unit.entries_tree(None)?;
+        let mut named = Vec::new();
+        collect_names(&dwarf, unit, tree.root()?, &mut Vec::new(), &mut named)?;
+        for (offset, name, is_declaration) in named {
+            if !is_declaration {
+                declarations.push((idx, offset, name.clone()));
+            }
+            importer.names.entry((idx, offset)).or_insert(name);
+        }
+    }
+
+    let mut library = TypeLibrary::new();
+    for (idx, offset, name) in declarations {
+        if library.contains_key(&name) {
+            continue;
+        }
+        let dt = importer.resolve(idx, offset)?;
+        let tag = units[idx].entry(offset)?.tag();
+        let description = match tag {
+            gimli::DW_TAG_typedef => format!("Typedef of {} (DWARF)", dt.get_name()),
+            gimli::DW_TAG_union_type => "Union (DWARF)".into(),
+            gimli::DW_TAG_enumeration_type => "Enum (DWARF)".into(),
+            gimli::DW_TAG_class_type => "Class (DWARF)".into(),
+            _ => "Structure (DWARF)".into(),
+        };
+        library.insert(name, (description, dt));
+    }
+    Ok(library)
+}
+
+/// Finds the file holding the DWARF information of `path`: the file itself,
+/// or its separate debug file found through its build id or its debug link.
+/// Only the headers and the sections looked up are read, not the whole files.
+fn debug_file(path: &Path) -> Option<PathBuf> {
+    debug_file_in(path, Path::new("/usr/lib/debug"))
+}
+
+/// [`debug_file`], with the global debug directory at `debug_root`.
+fn debug_file_in(path: &Path, debug_root: &Path) -> Option<PathBuf> {
+    let cache = ReadCache::new(std::fs::File::open(path).ok()?);
+    let file = object::File::parse(&cache).ok()?;
+    if file.section_by_name(".debug_info").is_some() {
+        return Some(path.to_owned());
+    }
+
+    let mut candidates = Vec::new();
+    if let Ok(Some(build_id)) = file.build_id() {
+        if build_id.len() > 1 {
+            let hex: String = build_id.iter().map(|b| format!("{b:02x}")).collect();
+            candidates.push(
+                debug_root
+                    .join(".build-id")
+                    .join(&hex[..2])
+                    .join(format!("{}.debug", &hex[2..])),
+            );
+        }
+    }
+    if let Ok(Some((name, _crc))) = file.gnu_debuglink() {
+        let name = Path::new(std::str::from_utf8(name).ok()?);
+        let dir = path.parent().unwrap_or(Path::new("/"));
+        candidates.push(dir.join(name));
+        candidates.push(dir.join(".debug").join(name));
+        let relative_dir = dir.strip_prefix("/").unwrap_or(dir);
+        candidates.push(debug_root.join(relative_dir).join(name));
+    }
+
+    candidates.into_iter().find(|p| {
+        p.as_path() != path
+            && std::fs::File::open(p).is_ok_and(|f| {
+                let cache = ReadCache::new(f);
+                object::File::parse(&cache)
+                    .is_ok_and(|f| f.section_by_name(".debug_info").is_some())
+            })
+    })
+}
+
+// (Unit index, offset in unit)
+type DieId = (usize, UnitOffset);
+
+/// Records the qualified name of every named type DIE below `node`.
+fn collect_names(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &Unit<Reader>,
+    node: gimli::EntriesTreeNode<Reader>,
+    namespace: &mut Vec<String>,
+    named: &mut Vec<(UnitOffset, String, bool)>,
+) -> Result<(), ImportError> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        let name = die_name(dwarf, unit, entry)?;
+        match entry.tag() {
+            gimli::DW_TAG_namespace
+            | gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_class_type
+            | gimli::DW_TAG_union_type
+            | gimli::DW_TAG_enumeration_type
+            | gimli::DW_TAG_typedef => {
+                let is_scope = entry.tag() != gimli::DW_TAG_typedef;
+                let is_declaration = entry.tag() == gimli::DW_TAG_namespace
+                    || matches!(
+                        entry.attr_value(gimli::DW_AT_declaration)?,
+                        Some(AttributeValue::Flag(true))
+                    );
+                if let Some(name) = &name {
+                    let qualified = qualify_name(namespace.iter().map(String::as_str), name);
+                    // `typedef struct { ... } Name;` names the anonymous structure as well
+                    if let Some(AttributeValue::UnitRef(target)) =
+                        entry.attr_value(gimli::DW_AT_type)?
+                    {
+                        let target_entry = unit.entry(target)?;
+                        if entry.tag() == gimli::DW_TAG_typedef
+                            && die_name(dwarf, unit, &target_entry)?.is_none()
+                        {
+                            named.push((target, qualified.clone(), true));
+                        }
+                    }
+                    named.push((entry.offset(), qualified, is_declaration));
+                }
+                if is_scope {
+                    // Anonymous namespaces and types do not add a level
+                    let pushed = name.is_some();
+                    if let Some(name) = name {
+                        namespace.push(name);
+                    }
+                    collect_names(dwarf, unit, child, namespace, named)?;
+                    if pushed {
+                        namespace.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn die_name(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &Unit<Reader>,
+    entry: &DebuggingInformationEntry<Reader>,
+) -> Result<Option<String>, ImportError> {
+    Ok(match entry.attr_value(gimli::DW_AT_name)? {
+        Some(value) => Some(
+            dwarf
+                .attr_string(unit, value)?
+                .to_string_lossy()
+                .into_owned(),
+        ),
+        None => None,
+    })
+}
+
+fn attr_udata(
+    entry: &DebuggingInformationEntry<Reader>,
+    at: DwAt,
+) -> Result<Option<u64>, ImportError> {
+    Ok(entry.attr(at)?.and_then(|a| a.udata_value()))
+}
+
+struct Importer<'a, 'd> {
+    dwarf: &'d gimli::Dwarf<Reader<'a>>,
+    units: &'d [Unit<Reader<'a>>],
+    resolved: HashMap<DieId, DataTypeEnum>,
+    in_progress: HashSet<DieId>,
+    names: HashMap<DieId, String>,
+}
+
+impl Importer<'_, '_> {
+    fn name_of(&self, id: DieId, tag: DwTag) -> String {
+        self.names.get(&id).cloned().unwrap_or_else(|| {
+            let kind = match tag {
+                gimli::DW_TAG_union_type => "union",
+                gimli::DW_TAG_enumeration_type => "enum",
+                gimli::DW_TAG_subroutine_type => "fn",
+                _ => "struct",
+            };
+            // Offsets are unique per unit only
+            format!("anon_{kind}_{}_{:#x}", id.0, id.1 .0)
+        })
+    }
+
+    /// Resolves the DIE referenced by the `DW_AT_type` attribute of `entry`, `void` if there is none.
+    fn resolve_type_attr(
+        &mut self,
+        idx: usize,
+        entry: &DebuggingInformationEntry<Reader>,
+        behind_pointer: bool,
+    ) -> Result<DataTypeEnum, ImportError> {
+        let target = match entry.attr_value(gimli::DW_AT_type)? {
+            Some(AttributeValue::UnitRef(offset)) => Some((idx, offset)),
+            Some(AttributeValue::DebugInfoRef(offset)) => {
+                let target_idx = self
+                    .units
+                    .partition_point(|u| {
+                        u.header
+                            .offset()
+                            .as_debug_info_offset()
+                            .is_some_and(|o| o <= offset)
+                    })
+                    .saturating_sub(1);
+                offset
+                    .to_unit_offset(&self.units[target_idx].header)
+                    .map(|o| (target_idx, o))
+            }
+            _ => None,
+        };
+        match target {
+            None => Ok(void()),
+            Some((target_idx, offset)) if behind_pointer => {
+                // Only keep the name of pointed composites, the definition lives in the library
+                let tag = self.units[target_idx].entry(offset)?.tag();
+                match tag {
+                    gimli::DW_TAG_structure_type
+                    | gimli::DW_TAG_class_type
+                    | gimli::DW_TAG_union_type => Ok(StructDataType::new(
+                        self.name_of((target_idx, offset), tag),
+                        Vec::new(),
+                    )
+                    .into()),
+                    _ => self.resolve(target_idx, offset),
+                }
+            }
+            Some((target_idx, offset)) => self.resolve(target_idx, offset),
+        }
+    }
+
+    fn resolve(&mut self, idx: usize, offset: UnitOffset) -> Result<DataTypeEnum, ImportError> {
+        let id = (idx, offset);
+        if let Some(dt) = self.resolved.get(&id) {
+            return Ok(dt.clone());
+        }
+        let units = self.units;
+        let entry = units[idx].entry(offset)?;
+        if self.in_progress.contains(&id) {
+            return Ok(StructDataType::new(self.name_of(id, entry.tag()), Vec::new()).into());
+        }
+        self.in_progress.insert(id);
+
+        let byte_size = attr_udata(&entry, gimli::DW_AT_byte_size)?.map(|s| s as usize);
+        let dt: DataTypeEnum = match entry.tag() {
+            gimli::DW_TAG_base_type => base_type(&entry, byte_size.unwrap_or(0))?,
+            gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type
+            | gimli::DW_TAG_ptr_to_member_type => {
+                let target = self.resolve_type_attr(idx, &entry, true)?;
+                let size = byte_size.unwrap_or(self.units[idx].header.address_size() as usize);
+                PointerDataType::new(target).with_size(size).into()
+            }
+            gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_atomic_type
+            | gimli::DW_TAG_typedef => self.resolve_type_attr(idx, &entry, false)?,
+            gimli::DW_TAG_unspecified_type => void(),
+            gimli::DW_TAG_array_type => {
+                let element = self.resolve_type_attr(idx, &entry, false)?;
+                let mut dims = Vec::new();
+                let mut tree = self.units[idx].entries_tree(Some(offset))?;
+                let mut children = tree.root()?.children();
+                while let Some(child) = children.next()? {
+                    let sub = child.entry();
+                    if sub.tag() != gimli::DW_TAG_subrange_type {
+                        continue;
+                    }
+                    let count = match attr_udata(sub, gimli::DW_AT_count)? {
+                        Some(c) => c,
+                        None => {
+                            let lower = attr_udata(sub, gimli::DW_AT_lower_bound)?.unwrap_or(0);
+                            // Flexible array members have no bound
+                            attr_udata(sub, gimli::DW_AT_upper_bound)?
+                                .map_or(0, |upper| (upper + 1).saturating_sub(lower))
+                        }
+                    };
+                    dims.push(count as usize);
+                }
+                if dims.is_empty() {
+                    dims.push(0);
+                }
+                dims.into_iter()
+                    .rev()
+                    .fold(element, |acc, d| ArrayDataType::new(acc, d).into())
+            }
+            gimli::DW_TAG_enumeration_type => {
+                let size = byte_size
+                    .and_then(|s| IntSize::try_from(s).ok())
+                    .unwrap_or_default();
+                let mut e = EnumDataType::new(self.name_of(id, entry.tag()), size);
+                let mut tree = self.units[idx].entries_tree(Some(offset))?;
+                let mut children = tree.root()?.children();
+                while let Some(child) = children.next()? {
+                    let variant = child.entry();
+                    if variant.tag() != gimli::DW_TAG_enumerator {
+                        continue;
+                    }
+                    let value = match variant.attr_value(gimli::DW_AT_const_value)? {
+                        Some(AttributeValue::Sdata(v)) => v,
+                        Some(AttributeValue::Udata(v)) => v as i64,
+                        Some(v) => v.sdata_value().unwrap_or_default(),
+                        None => 0,
+                    };
+                    let name = die_name(self.dwarf, &self.units[idx], variant)?.unwrap_or_default();
+                    e.push_variant(name, value);
+                }
+                e.into()
+            }
+            gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type => {
+                let mut s = StructDataType::new(self.name_of(id, entry.tag()), Vec::new());
+                if let Some(size) = byte_size {
+                    s.set_size(size);
+                }
+                for (member_offset, member) in self.members(idx, offset)? {
+                    s.add_entry_at(member_offset, member);
+                }
+                s.into()
+            }
+            gimli::DW_TAG_union_type => {
+                let mut u = UnionDataType::new(self.name_of(id, entry.tag()), Vec::new());
+                for (_, member) in self.members(idx, offset)? {
+                    u.push_entry(member);
+                }
+                u.into()
+            }
+            gimli::DW_TAG_subroutine_type => {
+                let return_type = self.resolve_type_attr(idx, &entry, true)?.get_name();
+                let mut f = FunctionDataType::new(self.name_of(id, entry.tag()), return_type);
+                let mut params = Vec::new();
+                let mut variadic = false;
+                let mut tree = self.units[idx].entries_tree(Some(offset))?;
+                let mut children = tree.root()?.children();
+                while let Some(child) = children.next()? {
+                    let param = child.entry();
+                    match param.tag() {
+                        gimli::DW_TAG_formal_parameter => params.push(param.clone()),
+                        gimli::DW_TAG_unspecified_parameters => variadic = true,
+                        _ => {}
+                    }
+                }
+                for (i, param) in params.iter().enumerate() {
+                    let type_name = self.resolve_type_attr(idx, param, true)?.get_name();
+                    f.push_parameter(format!("param_{}", i + 1), type_name);
+                }
+                f.with_variadic(variadic).into()
+            }
+            // Unknown type kinds (e.g. language specific ones) are kept as raw bytes
+            _ => raw_bytes(byte_size.unwrap_or(0)),
+        };
+
+        self.in_progress.remove(&id);
+        self.resolved.insert(id, dt.clone());
+        Ok(dt)
+    }
+
+    /// Members of a structure or union, with their byte offset.
+    fn members(
+        &mut self,
+        idx: usize,
+        offset: UnitOffset,
+    ) -> Result<Vec<(usize, StructEntry)>, ImportError> {
+        let units = self.units;
+        let mut dies = Vec::new();
+        let mut tree = units[idx].entries_tree(Some(offset))?;
+        let mut children = tree.root()?.children();
+        while let Some(child) = children.next()? {
+            let entry = child.entry();
+            let is_static = matches!(
+                entry.attr_value(gimli::DW_AT_external)?,
+                Some(AttributeValue::Flag(true))
+            ) || entry.attr_value(gimli::DW_AT_declaration)?.is_some();
+            if matches!(
+                entry.tag(),
+                gimli::DW_TAG_member | gimli::DW_TAG_inheritance
+            ) && !is_static
+            {
+                dies.push(entry.clone());
+            }
+        }
+
+        let mut members = Vec::with_capacity(dies.len());
+        for entry in dies {
+            let dt = self.resolve_type_attr(idx, &entry, false)?;
+            let name = match die_name(self.dwarf, &self.units[idx], &entry)? {
+                Some(name) => name,
+                None if entry.tag() == gimli::DW_TAG_inheritance => {
+                    format!("base_{}", dt.get_name())
+                }
+                None => format!("field_{}", members.len()),
+            };
+
+            let byte_offset = match entry.attr_value(gimli::DW_AT_data_member_location)? {
+                Some(AttributeValue::Exprloc(expr)) => {
+                    let mut ops = expr.operations(self.units[idx].encoding());
+                    match ops.next()? {
+                        Some(Operation::PlusConstant { value }) => Some(value),
+                        _ => None,
+                    }
+                }
+                Some(value) => value.udata_value(),
+                None => None,
+            };
+
+            let storage_size = dt.get_size();
+            let bit_size = attr_udata(&entry, gimli::DW_AT_bit_size)?;
+            let mut entry_offset = byte_offset.unwrap_or(0) as usize;
+            let mut member = StructEntry::new(name, dt);
+            if let Some(bit_size) = bit_size {
+                // Absolute position of the first bit, from the start of the structure
+                let data_bit_offset = match attr_udata(&entry, gimli::DW_AT_data_bit_offset)? {
+                    Some(bits) => bits,
+                    None => {
+                        // DWARF 2/3 count from the most significant bit of the storage unit
+                        let unit_size = attr_udata(&entry, gimli::DW_AT_byte_size)?
+                            .unwrap_or(storage_size as u64);
+                        let msb_offset = attr_udata(&entry, gimli::DW_AT_bit_offset)?.unwrap_or(0);
+                        (byte_offset.unwrap_or(0) * 8 + unit_size * 8)
+                            .saturating_sub(msb_offset + bit_size)
+                    }
+                };
+                let unit_bits = (storage_size.max(1) * 8) as u64;
+                let mut unit_start = data_bit_offset / unit_bits * unit_bits;
+                if data_bit_offset + bit_size > unit_start + unit_bits {
+                    // Straddling bitfield (packed structures), start from its first byte instead
+                    unit_start = data_bit_offset / 8 * 8;
+                }
+                entry_offset = (unit_start / 8) as usize;
+                member = member.with_bitfield((data_bit_offset - unit_start) as u8, bit_size as u8);
+            }
+            members.push((entry_offset, member));
+        }
+        Ok(members)
+    }
+}
+
+fn void() -> DataTypeEnum {
+    StructDataType::new("void".into(), Vec::new()).into()
+}
+
+fn base_type(
+    entry: &DebuggingInformationEntry<Reader>,
+    size: usize,
+) -> Result<DataTypeEnum, ImportError> {
+    let encoding = match entry.attr_value(gimli::DW_AT_encoding)? {
+        Some(AttributeValue::Encoding(e)) => e,
+        _ => gimli::DW_ATE_unsigned,
+    };
+    let int_size = IntSize::try_from(size);
+    let dt = match (encoding, int_size) {
+        (gimli::DW_ATE_boolean, _) => BooleanDataType::default().with_size(size).into(),
+        (gimli::DW_ATE_float, _) if size == 4 => FloatDataType::default().into(),
+        (gimli::DW_ATE_float, _) if size == 8 => FloatDataType::default()
+            .with_precision(FloatPrecision::Double)
+            .into(),
+        (gimli::DW_ATE_signed | gimli::DW_ATE_signed_char, Ok(int_size)) => {
+            IntegerDataType::default()
+                .with_size(int_size)
+                .with_signed(true)
+                .into()
+        }
+        (
+            gimli::DW_ATE_unsigned | gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF,
+            Ok(int_size),
+        ) => IntegerDataType::default().with_size(int_size).into(),
+        (gimli::DW_ATE_address, Ok(int_size)) => IntegerDataType::default()
+            .with_size(int_size)
+            .with_hex(true)
+            .into(),
+        // long double, 128-bit integers, ... are kept as raw bytes
+        _ => raw_bytes(size),
+    };
+    Ok(dt)
+}
+
+fn raw_bytes(size: usize) -> DataTypeEnum {
+    let byte = IntegerDataType::default()
+        .with_size(IntSize::Integer8)
+        .with_hex(true);
+    ArrayDataType::new(byte.into(), size).into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Built from sample_dwarf.c with `gcc -g -gdwarf-N -O0 -shared -nostdlib -fPIC -Wl,--build-id`,
+    // the DWARF 5 one then split with `objcopy --only-keep-debug` and `--strip-debug --add-gnu-debuglink`
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn check_sample(lib: &TypeLibrary) {
+        let DataTypeEnum::StructDataType(player) = &lib["Player"].1 else {
+            panic!("Player should be a struct");
+        };
+        assert_eq!(player.get_size(), 32);
+        let entries = player.get_entries();
+        assert_eq!(entries.len(), 7);
+        assert_eq!(entries[0].get_datatype().get_name(), "Pointer to Player");
+
+        // `team:3` and `flags:5` share an unsigned int
+        let team = entries[1]
+            .get_bitfield()
+            .expect("team should be a bitfield");
+        let flags = entries[2]
+            .get_bitfield()
+            .expect("flags should be a bitfield");
+        assert_eq!((entries[1].get_offset(), entries[2].get_offset()), (8, 8));
+        assert_eq!((team.bit_offset, team.bit_size), (0, 3));
+        assert_eq!((flags.bit_offset, flags.bit_size), (3, 5));
+
+        assert_eq!(entries[3].get_datatype().get_name(), "State");
+        assert_eq!(entries[4].get_offset(), 16);
+        assert_eq!(entries[5].get_datatype().get_name(), "Value");
+        assert_eq!(entries[6].get_offset(), 24);
+        assert_eq!(entries[6].get_datatype().get_name(), "Point");
+
+        let DataTypeEnum::UnionDataType(value) = &lib["Value"].1 else {
+            panic!("Value should be a union");
+        };
+        assert_eq!(value.get_entries().len(), 2);
+        assert_eq!(value.get_size(), 4);
+
+        let DataTypeEnum::EnumDataType(state) = &lib["State"].1 else {
+            panic!("State should be an enum");
+        };
+        assert_eq!(state.get_size(), 4);
+        assert_eq!(state.get_variants()[1], ("Dead".into(), 1));
+
+        let (desc, health) = &lib["Health"];
+        assert!(desc.starts_with("Typedef"));
+        assert_eq!(health.get_size(), 4);
+
+        // The anonymous struct takes the name of its typedef
+        let DataTypeEnum::StructDataType(point) = &lib["Point"].1 else {
+            panic!("Point should be a struct");
+        };
+        assert_eq!(point.get_name(), "Point");
+        assert_eq!(point.get_entries()[1].get_offset(), 2);
+    }
+
+    #[test]
+    fn test_dwarf_import_bit_offset() {
+        // DWARF 4 counts the bits of `DW_AT_bit_offset` from the most significant one
+        let lib = import_file(&fixture("sample_dwarf4.so")).expect("Should import");
+        check_sample(&lib);
+    }
+
+    #[test]
+    fn test_dwarf_import_debuglink() {
+        // DWARF 5 gives `DW_AT_data_bit_offset`, the types are in the debug link
+        let stripped = fixture("sample_dwarf5.so");
+        assert_eq!(
+            debug_file(&stripped),
+            Some(fixture("sample_dwarf5.so.debug"))
+        );
+        let lib = import_file(&stripped).expect("Should import");
+        check_sample(&lib);
+    }
+
+    #[test]
+    fn test_dwarf_build_id() {
+        let dir = std::env::temp_dir().join(format!("rs_class_build_id_{}", std::process::id()));
+        let data = std::fs::read(fixture("sample_dwarf5.so")).unwrap();
+        let build_id = object::File::parse(&*data)
+            .unwrap()
+            .build_id()
+            .unwrap()
+            .expect("The fixture should have a build id");
+        let hex: String = build_id.iter().map(|b| format!("{b:02x}")).collect();
+
+        // Away from its debug link, the debug file is only found through the build id
+        let stripped = dir.join("bin/sample_dwarf5.so");
+        let debug = dir.join(format!("debug/.build-id/{}/{}.debug", &hex[..2], &hex[2..]));
+        std::fs::create_dir_all(stripped.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(debug.parent().unwrap()).unwrap();
+        std::fs::write(&stripped, &data).unwrap();
+        std::fs::copy(fixture("sample_dwarf5.so.debug"), &debug).unwrap();
+
+        let found = debug_file_in(&stripped, &dir.join("debug"));
+        let no_debug = debug_file_in(&stripped, &dir.join("missing"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(found, Some(debug));
+        assert_eq!(no_debug, None);
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::interop::NAMESPACE_SEPARATOR;
+use crate::typing::{
+    DataType, DataTypeEnum, EnumDataType, FunctionDataType, StrEncoding, StructDataType,
+    StructEntry, TypeLibrary, UnionDataType,
+};
+
+/// Writes the library and the project structures as a Ghidra XML file,
+/// which can be applied to a program with Ghidra's "Add To Program" XML import.
+pub fn export_file(
+    path: &Path,
+    library: &TypeLibrary,
+    structs: &[StructDataType],
+) -> std::io::Result<()> {
+    let program_name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("rsclass");
+    std::fs::write(path, export_xml(program_name, library, structs))
+}
+
+/// Builds a Ghidra XML document whose `<DATATYPES>` section holds every structure,
+/// union, enum and function definition reachable from the library and the structures,
+/// and a typedef for each library entry named differently from its type.
+/// Namespaces become category paths, `Game::Player` is exported as `/Game/Player`.
+pub fn export_xml(program_name: &str, library: &TypeLibrary, structs: &[StructDataType]) -> String {
+    let mut definitions = BTreeMap::new();
+    let mut typedefs = BTreeMap::new();
+    for (name, (_, dt)) in library {
+        collect_definitions(dt, &mut definitions);
+        if *name != dt.get_name() {
+            typedefs.insert(name.clone(), dt);
+        }
+    }
+    for s in structs {
+        collect_definitions(&s.clone().into(), &mut definitions);
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" standalone=\"yes\"?>\n");
+    xml.push_str("<?program_dtd version=\"1\"?>\n");
+    let _ = writeln!(xml, "<PROGRAM NAME=\"{}\">", escape(program_name));
+    xml.push_str("  <DATATYPES>\n");
+    for dt in definitions.values() {
+        match dt {
+            DataTypeEnum::StructDataType(s) => write_struct(&mut xml, s),
+            DataTypeEnum::UnionDataType(u) => write_union(&mut xml, u),
+            DataTypeEnum::EnumDataType(e) => write_enum(&mut xml, e),
+            DataTypeEnum::FunctionDataType(f) => write_function(&mut xml, f),
+            _ => {}
+        }
+    }
+    for (name, dt) in typedefs {
+        let (category, name) = split_name(&name);
+        let (ref_category, ref_name) = type_ref(dt);
+        let _ = writeln!(
+            xml,
+            "    <TYPE_DEF NAME=\"{}\" NAMESPACE=\"{}\" DATATYPE=\"{}\" DATATYPE_NAMESPACE=\"{}\" />",
+            escape(name),
+            escape(&category),
+            escape(&ref_name),
+            escape(&ref_category),
+        );
+    }
+    xml.push_str("  </DATATYPES>\n");
+    xml.push_str("</PROGRAM>\n");
+    xml
+}
+
+/// Gathers every named composite type used by `dt`, keyed by name.
+fn collect_definitions(dt: &DataTypeEnum, definitions: &mut BTreeMap<String, DataTypeEnum>) {
+    match dt {
+        DataTypeEnum::StructDataType(s) => {
+            if is_stub(s) {
+                return;
+            }
+            for e in s.get_entries() {
+                collect_definitions(e.get_datatype(), definitions);
+            }
+        }
+        DataTypeEnum::UnionDataType(u) => {
+            for e in u.get_entries() {
+                collect_definitions(e.get_datatype(), definitions);
+            }
+        }
+        DataTypeEnum::EnumDataType(_) | DataTypeEnum::FunctionDataType(_) => {}
+        DataTypeEnum::PointerDataType(p) => {
+            return collect_definitions(p.get_pointed_datatype(), definitions)
+        }
+        DataTypeEnum::ArrayDataType(a) => {
+            return collect_definitions(a.get_element_datatype(), definitions)
+        }
+        _ => return,
+    }
+    definitions.insert(dt.get_name(), dt.clone());
+}
+
+// Named struct without a layout, as kept behind pointers
+fn is_stub(s: &StructDataType) -> bool {
+    s.get_entries().is_empty() && s.get_size() == 0
+}
+
+/// Splits a namespaced name into a Ghidra category path and a plain name.
+fn split_name(name: &str) -> (String, &str) {
+    // Namespaces inside template arguments are part of the name
+    let mut depth = 0i32;
+    let mut split = None;
+    for (i, c) in name.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if depth == 0 && name[i..].starts_with(NAMESPACE_SEPARATOR) => split = Some(i),
+            _ => {}
+        }
+    }
+    match split {
+        Some(i) => {
+            let category = name[..i]
+                .split(NAMESPACE_SEPARATOR)
+                .collect::<Vec<_>>()
+                .join("/");
+            (
+                format!("/{category}"),
+                &name[i + NAMESPACE_SEPARATOR.len()..],
+            )
+        }
+        None => ("/".into(), name),
+    }
+}
+
+/// Returns the (category, name) Ghidra uses to reference `dt`, e.g. `("/Game", "Player *")`.
+fn type_ref(dt: &DataTypeEnum) -> (String, String) {
+    let sized_int = |size: usize, names: [&str; 4]| match size {
+        1 => names[0].to_owned(),
+        2 => names[1].to_owned(),
+        4 => names[2].to_owned(),
+        _ => names[3].to_owned(),
+    };
+    let root = |name: String| ("/".to_owned(), name);
+    match dt {
+        DataTypeEnum::IntegerDataType(i) => root(match (i.is_hex(), i.is_signed()) {
+            (true, _) => sized_int(i.get_size(), ["byte", "word", "dword", "qword"]),
+            (false, true) => sized_int(i.get_size(), ["char", "short", "int", "longlong"]),
+            (false, false) => sized_int(i.get_size(), ["uchar", "ushort", "uint", "ulonglong"]),
+        }),
+        DataTypeEnum::BooleanDataType(b) if b.get_size() == 1 => root("bool".into()),
+        DataTypeEnum::BooleanDataType(b) => root(format!("undefined{}", b.get_size())),
+        DataTypeEnum::FloatDataType(f) if f.get_size() == 4 => root("float".into()),
+        DataTypeEnum::FloatDataType(_) => root("double".into()),
+        DataTypeEnum::StrDataType(s) => root(match s.get_encoding() {
+            StrEncoding::Utf8 => format!("char[{}]", s.get_size()),
+            StrEncoding::Utf16 => format!("wchar16[{}]", s.get_size() / 2),
+        }),
+        DataTypeEnum::PointerDataType(p) => {
+            let (category, name) = type_ref(p.get_pointed_datatype());
+            (category, format!("{name} *"))
+        }
+        DataTypeEnum::ArrayDataType(a) => {
+            // Ghidra names nested arrays C-style, outermost dimension first
+            let (category, name) = type_ref(a.get_element_datatype());
+            match name.find('[') {
+                Some(i) if !name.ends_with('*') => (
+                    category,
+                    format!("{}[{}]{}", &name[..i], a.get_length(), &name[i..]),
+                ),
+                _ => (category, format!("{name}[{}]", a.get_length())),
+            }
+        }
+        DataTypeEnum::StructDataType(s) if is_stub(s) && s.get_name() == "void" => {
+            root("void".into())
+        }
+        _ => {
+            let name = dt.get_name();
+            let (category, name) = split_name(&name);
+            (category, name.to_owned())
+        }
+    }
+}
+
+fn write_struct(xml: &mut String, s: &StructDataType) {
+    let name = s.get_name();
+    let (category, name) = split_name(&name);
+    let _ = writeln!(
+        xml,
+        "    <STRUCTURE NAME=\"{}\" NAMESPACE=\"{}\" SIZE=\"{:#x}\">",
+        escape(name),
+        escape(&category),
+        s.get_size()
+    );
+    write_comment(xml, s.get_comment(), 6);
+    for e in s.get_entries() {
+        write_member(xml, e, e.get_offset());
+    }
+    xml.push_str("    </STRUCTURE>\n");
+}
+
+fn write_union(xml: &mut String, u: &UnionDataType) {
+    let name = u.get_name();
+    let (category, name) = split_name(&name);
+    let _ = writeln!(
+        xml,
+        "    <UNION NAME=\"{}\" NAMESPACE=\"{}\" SIZE=\"{:#x}\">",
+        escape(name),
+        escape(&category),
+        u.get_size()
+    );
+    write_comment(xml, u.get_comment(), 6);
+    for e in u.get_entries() {
+        write_member(xml, e, 0);
+    }
+    xml.push_str("    </UNION>\n");
+}
+
+fn write_member(xml: &mut String, e: &StructEntry, offset: usize) {
+    let (category, mut type_name) = type_ref(e.get_datatype());
+    // Bitfields share their storage unit, Ghidra writes them as `int:3`
+    let bitfield = e.get_bitfield();
+    if let Some(b) = bitfield {
+        let _ = write!(type_name, ":{}", b.bit_size);
+    }
+    let _ = write!(
+        xml,
+        "      <MEMBER OFFSET=\"{offset:#x}\" DATATYPE=\"{}\" DATATYPE_NAMESPACE=\"{}\" NAME=\"{}\" SIZE=\"{:#x}\"",
+        escape(&type_name),
+        escape(&category),
+        escape(e.get_name()),
+        e.get_size()
+    );
+    if let Some(b) = bitfield {
+        let _ = write!(xml, " BIT_OFFSET=\"{}\"", b.bit_offset);
+    }
+    if e.get_comment().is_empty() {
+        xml.push_str(" />\n");
+    } else {
+        xml.push_str(">\n");
+        write_comment(xml, e.get_comment(), 8);
+        xml.push_str("      </MEMBER>\n");
+    }
+}
+
+fn write_enum(xml: &mut String, e: &EnumDataType) {
+    let name = e.get_name();
+    let (category, name) = split_name(&name);
+    let _ = writeln!(
+        xml,
+        "    <ENUM NAME=\"{}\" NAMESPACE=\"{}\" SIZE=\"{:#x}\">",
+        escape(name),
+        escape(&category),
+        e.get_size()
+    );
+    for (name, value) in e.get_variants() {
+        let value = if *value < 0 {
+            format!("-{:#x}", value.unsigned_abs())
+        } else {
+            format!("{value:#x}")
+        };
+        let _ = writeln!(
+            xml,
+            "      <ENUM_ENTRY NAME=\"{}\" VALUE=\"{value}\" />",
+            escape(name)
+        );
+    }
+    xml.push_str("    </ENUM>\n");
+}
+
+fn write_function(xml: &mut String, f: &FunctionDataType) {
+    let name = f.get_name();
+    let (category, name) = split_name(&name);
+    let varargs = if f.is_variadic() {
+        " VARARGS=\"y\""
+    } else {
+        ""
+    };
+    let _ = writeln!(
+        xml,
+        "    <FUNCTION_DEF NAME=\"{}\" NAMESPACE=\"{}\"{varargs}>",
+        escape(name),
+        escape(&category)
+    );
+    let _ = writeln!(
+        xml,
+        "      <RETURN_TYPE DATATYPE=\"{}\" DATATYPE_NAMESPACE=\"/\" />",
+        escape(f.get_return_type())
+    );
+    for (i, (name, type_name)) in f.get_parameters().iter().enumerate() {
+        let _ = writeln!(
+            xml,
+            "      <PARAMETER ORDINAL=\"{i:#x}\" DATATYPE=\"{}\" DATATYPE_NAMESPACE=\"/\" NAME=\"{}\" />",
+            escape(type_name),
+            escape(name)
+        );
+    }
+    xml.push_str("    </FUNCTION_DEF>\n");
+}
+
+fn write_comment(xml: &mut String, comment: &str, indent: usize) {
+    if !comment.is_empty() {
+        let _ = writeln!(
+            xml,
+            "{:indent$}<REGULAR_CMT>{}</REGULAR_CMT>",
+            "",
+            escape(comment)
+        );
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::interop::ghidra::import_xml;
+    use crate::typing::{EnumDataType, IntSize, IntegerDataType, PointerDataType};
+
+    #[test]
+    fn test_ghidra_export_round_trip() {
+        let mut player = StructDataType::new("Game::Player".into(), Vec::new())
+            .with_size(0x18)
+            .with_comment("A <player>".into());
+        player.add_entry_at(
+            0,
+            StructEntry::new(
+                "health".into(),
+                IntegerDataType::default().with_signed(true).into(),
+            )
+            .with_comment("HP & shields".into()),
+        );
+        player.add_entry_at(
+            8,
+            StructEntry::new(
+                "next".into(),
+                PointerDataType::new(StructDataType::new("Game::Player".into(), Vec::new()).into())
+                    .with_size(8)
+                    .into(),
+            ),
+        );
+        let state = EnumDataType::new("Game::State".into(), IntSize::Integer8)
+            .with_variant("Alive".into(), 0)
+            .with_variant("Invalid".into(), -1);
+        player.add_entry_at(0x10, StructEntry::new("state".into(), state.into()));
+        let flags = IntegerDataType::default().with_size(IntSize::Integer32);
+        player.add_entry_at(
+            0x14,
+            StructEntry::new("alive".into(), flags.clone().into()).with_bitfield(0, 1),
+        );
+        player.add_entry_at(
+            0x14,
+            StructEntry::new("team".into(), flags.into()).with_bitfield(4, 3),
+        );
+
+        let mut library = TypeLibrary::new();
+        library.insert(
+            "DWORD".into(),
+            (
+                String::new(),
+                IntegerDataType::default().with_hex(true).into(),
+            ),
+        );
+
+        let xml = export_xml("game", &library, &[player]);
+        let imported = import_xml(&xml).expect("Export should be importable");
+        assert_eq!(imported.len(), 3);
+
+        let DataTypeEnum::StructDataType(s) = &imported["Game::Player"].1 else {
+            panic!("Player should be a struct");
+        };
+        assert_eq!(s.get_size(), 0x18);
+        assert_eq!(s.get_comment(), "A <player>");
+        let entries = s.get_entries();
+        assert_eq!(entries[0].get_comment(), "HP & shields");
+        assert_eq!(entries[1].get_offset(), 8);
+        assert_eq!(entries[1].get_size(), 8);
+        assert_eq!(
+            entries[1].get_datatype().get_name(),
+            "Pointer to Game::Player"
+        );
+        assert_eq!(entries[2].get_offset(), 0x10);
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[4].get_offset(), 0x14);
+        assert_eq!(entries[4].get_size(), 4);
+        let team = entries[4]
+            .get_bitfield()
+            .expect("team should be a bitfield");
+        assert_eq!((team.bit_offset, team.bit_size), (4, 3));
+        assert_eq!(
+            imported["Game::State"].1.bytes_to_string(&[0xFF]).unwrap(),
+            "Invalid"
+        );
+        assert_eq!(imported["DWORD"].1.get_size(), 4);
+    }
+
+    #[test]
+    fn test_ghidra_type_ref() {
+        let int: DataTypeEnum = IntegerDataType::default().with_signed(true).into();
+        let inner = crate::typing::ArrayDataType::new(int, 2);
+        let outer = crate::typing::ArrayDataType::new(inner.into(), 4);
+        assert_eq!(type_ref(&outer.into()), ("/".into(), "int[4][2]".into()));
+        assert_eq!(split_name("A::B<C::D>"), ("/A".into(), "B<C::D>"));
+    }
+}
@@ -0,0 +1,542 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use super::{builtin, category_to_namespace, int, parse_number};
+use crate::interop::{qualify_name, ImportError};
+use crate::typing::{
+    ArrayDataType, Bitfield, DataType, DataTypeEnum, EnumDataType, FunctionDataType, IntSize,
+    PointerDataType, StrDataType, StrEncoding, StructDataType, StructEntry, TypeLibrary,
+    UnionDataType,
+};
+
+/// Imports the data types of a Ghidra XML export.
+///
+/// `.gdt` archives are Ghidra packed databases and cannot be read outside of Ghidra,
+/// they have to be exported to XML first.
+pub fn import_file(path: &Path) -> Result<TypeLibrary, ImportError> {
+    let data = std::fs::read(path)?;
+    let is_gdt = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gdt"));
+    let text = std::str::from_utf8(&data).ok().filter(|s| {
+        s.trim_start_matches('\u{feff}')
+            .trim_start()
+            .starts_with('<')
+    });
+    match text {
+        Some(text) if !is_gdt => import_xml(text),
+        _ => Err(ImportError::UnsupportedError(
+            "Ghidra data type archives (.gdt) are packed databases, export the types to XML from Ghidra first.".into(),
+        )),
+    }
+}
+
+/// Imports every type of the `<DATATYPES>` section of a Ghidra XML document.
+/// Category paths become namespaces, `/Game/Player` is imported as `Game::Player`.
+pub fn import_xml(xml: &str) -> Result<TypeLibrary, ImportError> {
+    let doc = Document::parse(xml).map_err(|e| ImportError::ParseError(e.to_string()))?;
+    let datatypes = doc
+        .descendants()
+        .find(|n| n.has_tag_name("DATATYPES"))
+        .ok_or_else(|| ImportError::ParseError("No DATATYPES section found.".into()))?;
+
+    let mut resolver = Resolver::default();
+    for node in datatypes.children().filter(Node::is_element) {
+        if DeclKind::from_tag(node.tag_name().name()).is_none() {
+            continue;
+        }
+        let name = attr(&node, "NAME")?;
+        let key = qualify_name(
+            category_to_namespace(node.attribute("NAMESPACE").unwrap_or("/")),
+            name,
+        );
+        resolver.decls.insert(key, node);
+    }
+
+    let mut library = TypeLibrary::new();
+    let keys: Vec<String> = resolver.decls.keys().cloned().collect();
+    for key in keys {
+        let node = resolver.decls[&key];
+        let dt = resolver.resolve_decl(&key)?;
+        library.insert(key, (describe(&node), dt));
+    }
+    Ok(library)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeclKind {
+    Structure,
+    Union,
+    Enum,
+    TypeDef,
+    FunctionDef,
+}
+impl DeclKind {
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "STRUCTURE" => Some(DeclKind::Structure),
+            "UNION" => Some(DeclKind::Union),
+            "ENUM" => Some(DeclKind::Enum),
+            "TYPE_DEF" => Some(DeclKind::TypeDef),
+            "FUNCTION_DEF" => Some(DeclKind::FunctionDef),
+            _ => None,
+        }
+    }
+}
+
+enum Modifier {
+    // Pointer size in bytes, if explicit
+    Pointer(Option<usize>),
+    // Array dimensions, outermost first
+    Array(Vec<usize>),
+}
+
+#[derive(Default)]
+struct Resolver<'a, 'input> {
+    decls: HashMap<String, Node<'a, 'input>>,
+    resolved: HashMap<String, DataTypeEnum>,
+    in_progress: HashSet<String>,
+}
+
+impl Resolver<'_, '_> {
+    fn resolve_decl(&mut self, key: &str) -> Result<DataTypeEnum, ImportError> {
+        if let Some(dt) = self.resolved.get(key) {
+            return Ok(dt.clone());
+        }
+        let node = self.decls[key];
+        let kind =
+            DeclKind::from_tag(node.tag_name().name()).expect("only declarations are indexed");
+        self.in_progress.insert(key.to_owned());
+
+        let dt: DataTypeEnum = match kind {
+            DeclKind::Structure => {
+                let mut s =
+                    StructDataType::new(key.to_owned(), Vec::new()).with_comment(comment(&node));
+                if let Some(size) = node.attribute("SIZE").and_then(parse_number) {
+                    s.set_size(size as usize);
+                }
+                // Bitfields without a BIT_OFFSET are packed after the previous one of their unit
+                let mut next_bit: Option<(usize, i64)> = None;
+                for member in node.children().filter(|n| n.has_tag_name("MEMBER")) {
+                    let (offset, mut entry) = self.member(&member)?;
+                    if let Some(mut bitfield) = entry.get_bitfield() {
+                        if member.attribute("BIT_OFFSET").is_none() {
+                            let bit_offset = match next_bit {
+                                Some((o, bit)) if o == offset => bit,
+                                _ => 0,
+                            };
+                            bitfield =
+                                checked_bitfield(&entry, bit_offset, bitfield.bit_size.into())?;
+                            entry.set_bitfield(Some(bitfield));
+                        }
+                        let end = bitfield.bit_offset as i64 + bitfield.bit_size as i64;
+                        next_bit = Some((offset, end));
+                    } else {
+                        next_bit = None;
+                    }
+                    s.add_entry_at(offset, entry);
+                }
+                s.into()
+            }
+            DeclKind::Union => {
+                let mut u =
+                    UnionDataType::new(key.to_owned(), Vec::new()).with_comment(comment(&node));
+                for member in node.children().filter(|n| n.has_tag_name("MEMBER")) {
+                    let (_, entry) = self.member(&member)?;
+                    u.push_entry(entry);
+                }
+                u.into()
+            }
+            DeclKind::Enum => {
+                let size = node
+                    .attribute("SIZE")
+                    .and_then(parse_number)
+                    .and_then(|s| IntSize::try_from(s as usize).ok())
+                    .unwrap_or_default();
+                let mut e = EnumDataType::new(key.to_owned(), size);
+                for entry in node.children().filter(|n| n.has_tag_name("ENUM_ENTRY")) {
+                    let value =
+                        entry
+                            .attribute("VALUE")
+                            .and_then(parse_number)
+                            .ok_or_else(|| {
+                                ImportError::ParseError(format!(
+                                    "Invalid value for an entry of enum {key}."
+                                ))
+                            })?;
+                    e.push_variant(attr(&entry, "NAME")?.to_owned(), value);
+                }
+                e.into()
+            }
+            DeclKind::TypeDef => {
+                let namespace = node.attribute("DATATYPE_NAMESPACE").unwrap_or("/");
+                self.resolve_ref(attr(&node, "DATATYPE")?, namespace, None)?
+            }
+            DeclKind::FunctionDef => {
+                let return_type = node
+                    .children()
+                    .find(|n| n.has_tag_name("RETURN_TYPE"))
+                    .and_then(|n| n.attribute("DATATYPE"))
+                    .unwrap_or("void");
+                let mut f = FunctionDataType::new(key.to_owned(), return_type.to_owned());
+                let mut params: Vec<_> = node
+                    .children()
+                    .filter(|n| n.has_tag_name("PARAMETER"))
+                    .collect();
+                params.sort_by_key(|n| n.attribute("ORDINAL").and_then(parse_number));
+                for (i, p) in params.iter().enumerate() {
+                    let name = p
+                        .attribute("NAME")
+                        .map_or_else(|| format!("param_{}", i + 1), ToOwned::to_owned);
+                    f.push_parameter(name, attr(p, "DATATYPE")?.to_owned());
+                }
+                f.set_variadic(node.attribute("VARARGS").is_some_and(|v| v == "y"));
+                f.into()
+            }
+        };
+
+        self.in_progress.remove(key);
+        self.resolved.insert(key.to_owned(), dt.clone());
+        Ok(dt)
+    }
+
+    fn member(&mut self, member: &Node) -> Result<(usize, StructEntry), ImportError> {
+        let offset = member
+            .attribute("OFFSET")
+            .and_then(parse_number)
+            .unwrap_or(0) as usize;
+        let size = member
+            .attribute("SIZE")
+            .and_then(parse_number)
+            .map(|s| s as usize);
+        let name = member
+            .attribute("NAME")
+            .map_or_else(|| format!("field_{offset:#x}"), ToOwned::to_owned);
+        let namespace = member.attribute("DATATYPE_NAMESPACE").unwrap_or("/");
+        let type_str = attr(member, "DATATYPE")?;
+        let bit_size = type_str
+            .rsplit_once(':')
+            .and_then(|(_, bits)| bits.trim().parse::<u64>().ok())
+            .filter(|&bits| bits > 0);
+        // Ghidra records the bytes spanned by a bitfield, not the size of its storage unit
+        let size = size.filter(|_| bit_size.is_none());
+        let dt = self.resolve_ref(type_str, namespace, size)?;
+        let mut entry = StructEntry::new(name, dt).with_comment(comment(member));
+        if let Some(bit_size) = bit_size {
+            let bit_offset = member
+                .attribute("BIT_OFFSET")
+                .and_then(parse_number)
+                .unwrap_or(0);
+            let bitfield = checked_bitfield(&entry, bit_offset, bit_size)?;
+            entry.set_bitfield(Some(bitfield));
+        }
+        Ok((offset, entry))
+    }
+
+    /// Resolves a type reference such as `Player *`, `char[16]` or `int[4][2]`.
+    /// `size` is the size Ghidra recorded for the reference, if any.
+    fn resolve_ref(
+        &mut self,
+        type_str: &str,
+        namespace: &str,
+        size: Option<usize>,
+    ) -> Result<DataTypeEnum, ImportError> {
+        let (base, modifiers) = split_modifiers(type_str)?;
+        let behind_pointer = matches!(modifiers.first(), Some(Modifier::Pointer(_)));
+        let base_size = if modifiers.is_empty() { size } else { None };
+
+        let key = qualify_name(category_to_namespace(namespace), base);
+        let mut dt = if self.decls.contains_key(&key) {
+            let kind = DeclKind::from_tag(self.decls[&key].tag_name().name());
+            let is_composite = matches!(kind, Some(DeclKind::Structure | DeclKind::Union));
+            if self.in_progress.contains(&key) || (behind_pointer && is_composite) {
+                // Only keep the name of pointed composites, the definition lives in the library
+                StructDataType::new(key, Vec::new()).into()
+            } else {
+                self.resolve_decl(&key)?
+            }
+        } else if let Some(dt) = builtin(base, base_size) {
+            dt
+        } else {
+            match (base, base_size) {
+                ("pointer" | "pointer32" | "pointer64", _) => {
+                    let void = builtin("void", None).expect("void is a builtin");
+                    let mut p = PointerDataType::new(void);
+                    if let Some(s) = base_size {
+                        p.set_size(s);
+                    }
+                    p.into()
+                }
+                ("string" | "TerminatedCString", Some(s)) => {
+                    StrDataType::default().with_size(s).into()
+                }
+                ("unicode" | "TerminatedUnicode", Some(s)) => StrDataType::default()
+                    .with_size(s)
+                    .with_encoding(StrEncoding::Utf16)
+                    .into(),
+                // Unknown types are kept as raw bytes when their size is known
+                (_, Some(s)) => ArrayDataType::new(int(IntSize::Integer8, false, true), s).into(),
+                (_, None) => {
+                    return Err(ImportError::ParseError(format!(
+                        "Unknown data type {type_str}."
+                    )))
+                }
+            }
+        };
+
+        let last = modifiers.len().saturating_sub(1);
+        for (i, m) in modifiers.into_iter().enumerate() {
+            dt = match m {
+                Modifier::Pointer(psize) => {
+                    let mut p = PointerDataType::new(dt);
+                    if let Some(s) = psize.or(size.filter(|_| i == last)) {
+                        p.set_size(s);
+                    }
+                    p.into()
+                }
+                Modifier::Array(dims) => dims
+                    .into_iter()
+                    .rev()
+                    .fold(dt, |acc, d| ArrayDataType::new(acc, d).into()),
+            };
+        }
+        Ok(dt)
+    }
+}
+
+/// Splits a Ghidra type reference into its base name and its pointer/array modifiers.
+/// Bitfield widths (`int:3`) are read by the members, only the base type is kept here.
+fn split_modifiers(type_str: &str) -> Result<(&str, Vec<Modifier>), ImportError> {
+    let invalid = || ImportError::ParseError(format!("Invalid type reference {type_str}."));
+    let type_str = type_str.split(':').next().unwrap_or_default();
+
+    // Skip template arguments, which may contain modifiers of their own
+    let mut depth = 0i32;
+    let start = type_str
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+            depth == 0 && (c == '*' || c == '[')
+        })
+        .map_or(type_str.len(), |(i, _)| i);
+
+    let base = type_str[..start].trim();
+    if base.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut modifiers = Vec::new();
+    let mut rest = type_str[start..].trim_start();
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('*') {
+            let digits = r.len() - r.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let bits = r[..digits].parse::<usize>().ok();
+            modifiers.push(Modifier::Pointer(bits.map(|b| b / 8)));
+            rest = r[digits..].trim_start();
+        } else if rest.starts_with('[') {
+            let mut dims = Vec::new();
+            while let Some(r) = rest.strip_prefix('[') {
+                let end = r.find(']').ok_or_else(invalid)?;
+                dims.push(parse_number(&r[..end]).ok_or_else(invalid)? as usize);
+                rest = r[end + 1..].trim_start();
+            }
+            modifiers.push(Modifier::Array(dims));
+        } else {
+            return Err(invalid());
+        }
+    }
+    Ok((base, modifiers))
+}
+
+fn attr<'a>(node: &Node<'a, '_>, name: &str) -> Result<&'a str, ImportError> {
+    node.attribute(name).ok_or_else(|| {
+        ImportError::ParseError(format!(
+            "Missing attribute {name} on {}.",
+            node.tag_name().name()
+        ))
+    })
+}
+
+// The bitfield of an entry, if it fits in the storage unit of the entry
+fn checked_bitfield(
+    entry: &StructEntry,
+    bit_offset: i64,
+    bit_size: u64,
+) -> Result<Bitfield, ImportError> {
+    let unit_bits = (entry.get_datatype().get_size() as u64 * 8).min(64);
+    match u64::try_from(bit_offset) {
+        Ok(offset) if bit_size <= unit_bits && offset <= unit_bits - bit_size => Ok(Bitfield {
+            bit_offset: offset as u8,
+            bit_size: bit_size as u8,
+        }),
+        _ => Err(ImportError::ParseError(format!(
+            "Bitfield {} of {bit_size} bits at bit {bit_offset} does not fit in its {unit_bits} bit unit.",
+            entry.get_name()
+        ))),
+    }
+}
+
+fn comment(node: &Node) -> String {
+    node.children()
+        .find(|n| n.has_tag_name("REGULAR_CMT"))
+        .and_then(|n| n.text())
+        .or_else(|| node.attribute("COMMENT"))
+        .unwrap_or_default()
+        .to_owned()
+}
+
+fn describe(node: &Node) -> String {
+    let cmt = comment(node);
+    if !cmt.is_empty() {
+        return cmt;
+    }
+    let category = node.attribute("NAMESPACE").unwrap_or("/");
+    match DeclKind::from_tag(node.tag_name().name()) {
+        Some(DeclKind::TypeDef) => format!(
+            "Typedef of {} (Ghidra: {category})",
+            node.attribute("DATATYPE").unwrap_or_default()
+        ),
+        Some(DeclKind::Structure) => format!("Structure (Ghidra: {category})"),
+        Some(DeclKind::Union) => format!("Union (Ghidra: {category})"),
+        Some(DeclKind::Enum) => format!("Enum (Ghidra: {category})"),
+        Some(DeclKind::FunctionDef) => format!("Function definition (Ghidra: {category})"),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0" standalone="yes"?>
+<PROGRAM NAME="game.exe">
+  <DATATYPES>
+    <STRUCTURE NAME="Player" NAMESPACE="/Game/Entities" SIZE="0x20">
+      <REGULAR_CMT>The local player</REGULAR_CMT>
+      <MEMBER OFFSET="0x0" DATATYPE="Player *" DATATYPE_NAMESPACE="/Game/Entities" NAME="next" SIZE="0x8" />
+      <MEMBER OFFSET="0x8" DATATYPE="int" DATATYPE_NAMESPACE="/" NAME="health" SIZE="0x4">
+        <REGULAR_CMT>Max is 100</REGULAR_CMT>
+      </MEMBER>
+      <MEMBER OFFSET="0xc" DATATYPE="State" DATATYPE_NAMESPACE="/Game" NAME="state" SIZE="0x4" />
+      <MEMBER OFFSET="0x10" DATATYPE="char[4][2]" DATATYPE_NAMESPACE="/" NAME="tag" SIZE="0x8" />
+      <MEMBER OFFSET="0x18" DATATYPE="uchar:3" DATATYPE_NAMESPACE="/" NAME="team" SIZE="0x1" />
+      <MEMBER OFFSET="0x18" DATATYPE="uchar:5" DATATYPE_NAMESPACE="/" NAME="flags" SIZE="0x1" />
+    </STRUCTURE>
+    <ENUM NAME="State" NAMESPACE="/Game" SIZE="0x4">
+      <ENUM_ENTRY NAME="Alive" VALUE="0x0" />
+      <ENUM_ENTRY NAME="Dead" VALUE="0x1" />
+    </ENUM>
+    <UNION NAME="Value" NAMESPACE="/" SIZE="0x8">
+      <MEMBER OFFSET="0x0" DATATYPE="int" DATATYPE_NAMESPACE="/" NAME="i" SIZE="0x4" />
+      <MEMBER OFFSET="0x0" DATATYPE="double" DATATYPE_NAMESPACE="/" NAME="d" SIZE="0x8" />
+    </UNION>
+    <TYPE_DEF NAME="PPlayer" NAMESPACE="/Game" DATATYPE="Player *" DATATYPE_NAMESPACE="/Game/Entities" />
+    <FUNCTION_DEF NAME="on_hit" NAMESPACE="/Game">
+      <RETURN_TYPE DATATYPE="void" DATATYPE_NAMESPACE="/" SIZE="0x0" />
+      <PARAMETER ORDINAL="0x1" DATATYPE="int" DATATYPE_NAMESPACE="/" NAME="damage" SIZE="0x4" />
+      <PARAMETER ORDINAL="0x0" DATATYPE="Player *" DATATYPE_NAMESPACE="/Game/Entities" NAME="self" SIZE="0x8" />
+    </FUNCTION_DEF>
+  </DATATYPES>
+</PROGRAM>"#;
+
+    #[test]
+    fn test_ghidra_import_struct() {
+        let lib = import_xml(XML).expect("Should import");
+        assert_eq!(lib.len(), 5);
+
+        let (desc, dt) = &lib["Game::Entities::Player"];
+        assert_eq!(desc, "The local player");
+        let DataTypeEnum::StructDataType(s) = dt else {
+            panic!("Player should be a struct");
+        };
+        assert_eq!(s.get_size(), 0x20);
+        let entries = s.get_entries();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[0].get_size(), 8);
+        assert_eq!(
+            entries[0].get_datatype().get_name(),
+            "Pointer to Game::Entities::Player"
+        );
+        assert_eq!(entries[1].get_offset(), 8);
+        assert_eq!(entries[1].get_comment(), "Max is 100");
+        assert_eq!(entries[2].get_datatype().get_name(), "Game::State");
+        assert_eq!(entries[3].get_size(), 8);
+
+        // Bitfields share their unit, each one starts after the previous
+        assert_eq!(entries[5].get_offset(), 0x18);
+        assert_eq!(entries[5].get_size(), 1);
+        let flags = entries[5]
+            .get_bitfield()
+            .expect("flags should be a bitfield");
+        assert_eq!((flags.bit_offset, flags.bit_size), (3, 5));
+    }
+
+    #[test]
+    fn test_ghidra_import_other_kinds() {
+        let lib = import_xml(XML).expect("Should import");
+
+        let state = &lib["Game::State"].1;
+        assert_eq!(state.bytes_to_string(&[1, 0, 0, 0]).unwrap(), "Dead");
+        assert_eq!(lib["Value"].1.get_size(), 8);
+        assert_eq!(
+            lib["Game::PPlayer"].1.get_name(),
+            "Pointer to Game::Entities::Player"
+        );
+
+        let DataTypeEnum::FunctionDataType(f) = &lib["Game::on_hit"].1 else {
+            panic!("on_hit should be a function");
+        };
+        assert_eq!(
+            f.prototype(),
+            "void Game::on_hit(Player * self, int damage)"
+        );
+    }
+
+    #[test]
+    fn test_ghidra_bitfield_outside_unit() {
+        let bitfield = |member: &str| {
+            format!(
+                r#"<PROGRAM NAME="game.exe"><DATATYPES><STRUCTURE NAME="Flags" NAMESPACE="/" SIZE="0x4">
+{member}
+</STRUCTURE></DATATYPES></PROGRAM>"#
+            )
+        };
+        // Past the end of the unit, or too wide for a byte
+        for member in [
+            r#"<MEMBER OFFSET="0x0" DATATYPE="uchar:4" DATATYPE_NAMESPACE="/" NAME="a" BIT_OFFSET="0x106" />"#,
+            r#"<MEMBER OFFSET="0x0" DATATYPE="uchar:300" DATATYPE_NAMESPACE="/" NAME="a" />"#,
+            r#"<MEMBER OFFSET="0x0" DATATYPE="uchar:5" DATATYPE_NAMESPACE="/" NAME="a" />
+<MEMBER OFFSET="0x0" DATATYPE="uchar:5" DATATYPE_NAMESPACE="/" NAME="b" />"#,
+        ] {
+            assert!(matches!(
+                import_xml(&bitfield(member)),
+                Err(ImportError::ParseError(_))
+            ));
+        }
+        let lib = import_xml(&bitfield(
+            r#"<MEMBER OFFSET="0x0" DATATYPE="uint:4" DATATYPE_NAMESPACE="/" NAME="a" BIT_OFFSET="0x1c" />"#,
+        ))
+        .expect("Should import");
+        let DataTypeEnum::StructDataType(s) = &lib["Flags"].1 else {
+            panic!("Flags should be a struct");
+        };
+        let a = s.get_entries()[0]
+            .get_bitfield()
+            .expect("a should be a bitfield");
+        assert_eq!((a.bit_offset, a.bit_size), (28, 4));
+    }
+
+    #[test]
+    fn test_ghidra_split_modifiers() {
+        let (base, mods) = split_modifiers("vector<int *> * *[3]").unwrap();
+        assert_eq!(base, "vector<int *>");
+        assert_eq!(mods.len(), 3);
+        assert!(split_modifiers("*").is_err());
+    }
+}
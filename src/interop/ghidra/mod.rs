@@ -0,0 +1,76 @@
+/***
+ * Ghidra data type interoperability
+ * Works on the XML format produced by Ghidra's "XML" exporter (`<DATATYPES>` section).
+ */
+
+mod export;
+pub use export::{export_file, export_xml};
+mod import;
+pub use import::{import_file, import_xml};
+
+use crate::typing::{
+    BooleanDataType, DataTypeEnum, FloatDataType, FloatPrecision, IntSize, IntegerDataType,
+    StructDataType,
+};
+
+/// Converts a Ghidra category path (`/cat/sub`) to namespace components.
+fn category_to_namespace(category: &str) -> Vec<&str> {
+    category.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+fn parse_number(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let val = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()? as i64,
+        None => s.parse::<i64>().ok()?,
+    };
+    Some(if neg { val.wrapping_neg() } else { val })
+}
+
+fn int(size: IntSize, signed: bool, hex: bool) -> DataTypeEnum {
+    IntegerDataType::default()
+        .with_size(size)
+        .with_signed(signed)
+        .with_hex(hex)
+        .into()
+}
+
+/// Maps a Ghidra built-in type name to a datatype.
+/// `size` is the size Ghidra recorded for it, used for data-organisation dependent types (e.g. `long`).
+fn builtin(name: &str, size: Option<usize>) -> Option<DataTypeEnum> {
+    use IntSize::{Integer16, Integer32, Integer64, Integer8};
+    let sized = |default: IntSize| {
+        size.and_then(|s| IntSize::try_from(s).ok())
+            .unwrap_or(default)
+    };
+    let dt = match name {
+        "undefined" | "undefined1" | "byte" => int(Integer8, false, true),
+        "undefined2" | "word" => int(Integer16, false, true),
+        "undefined4" | "dword" => int(Integer32, false, true),
+        "undefined8" | "qword" => int(Integer64, false, true),
+        "sbyte" | "char" | "schar" | "int8_t" => int(Integer8, true, false),
+        "uchar" | "uint8_t" => int(Integer8, false, false),
+        "sword" | "short" | "int16_t" => int(Integer16, true, false),
+        "ushort" | "wchar16" | "uint16_t" => int(Integer16, false, false),
+        "sdword" | "int" | "int32_t" => int(Integer32, true, false),
+        "uint" | "wchar32" | "uint32_t" => int(Integer32, false, false),
+        "sqword" | "longlong" | "int64_t" => int(Integer64, true, false),
+        "ulonglong" | "uint64_t" => int(Integer64, false, false),
+        "long" => int(sized(Integer32), true, false),
+        "ulong" => int(sized(Integer32), false, false),
+        "wchar_t" => int(sized(Integer16), false, false),
+        "bool" => BooleanDataType::default().into(),
+        "float" | "float4" => FloatDataType::default().into(),
+        "double" | "float8" => FloatDataType::default()
+            .with_precision(FloatPrecision::Double)
+            .into(),
+        // Opaque, only meaningful behind a pointer or as a return type
+        "void" => StructDataType::new("void".into(), Vec::new()).into(),
+        _ => return None,
+    };
+    Some(dt)
+}
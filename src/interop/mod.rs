@@ -0,0 +1,40 @@
+pub mod dwarf;
+pub mod ghidra;
+pub mod pdb;
+
+use std::fmt::Display;
+
+/// Separator used between the namespace components of imported type names
+pub const NAMESPACE_SEPARATOR: &str = "::";
+
+#[derive(Debug, Clone)]
+pub enum ImportError {
+    IoError(String),
+    ParseError(String),
+    UnsupportedError(String),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::IoError(e) => write!(f, "Could not read the file: {e}"),
+            ImportError::ParseError(e) => write!(f, "Could not parse the file: {e}"),
+            ImportError::UnsupportedError(e) => write!(f, "Unsupported input: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+    fn from(value: std::io::Error) -> Self {
+        ImportError::IoError(value.to_string())
+    }
+}
+
+/// Joins namespace components and a type name into a library key, e.g. `Game::Player`.
+pub fn qualify_name<'a>(namespace: impl IntoIterator<Item = &'a str>, name: &str) -> String {
+    let mut parts: Vec<&str> = namespace.into_iter().filter(|s| !s.is_empty()).collect();
+    parts.push(name);
+    parts.join(NAMESPACE_SEPARATOR)
+}
@@ -0,0 +1,518 @@
+/***
+ * PDB import
+ * Reads the TPI stream types and the public symbols of a program database, on any OS.
+ */
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use pdb::{FallibleIterator, PrimitiveKind, TypeData, TypeFinder, TypeIndex, PDB};
+
+use super::ImportError;
+use crate::symbols::{Symbol, SymbolTable};
+use crate::typing::{
+    ArrayDataType, BooleanDataType, DataType, DataTypeEnum, EnumDataType, FloatDataType,
+    FloatPrecision, FunctionDataType, IntSize, IntegerDataType, PointerDataType, StructDataType,
+    StructEntry, TypeLibrary, UnionDataType,
+};
+
+impl From<pdb::Error> for ImportError {
+    fn from(value: pdb::Error) -> Self {
+        ImportError::ParseError(value.to_string())
+    }
+}
+
+/// Imports the classes, structures, unions and enums of a PDB, and its typedefs (`S_UDT` symbols).
+/// Classes with virtual methods get a `__vfptr` entry pointing to a `Class::vftable` structure
+/// holding the slots they introduce.
+pub fn import_file(path: &Path) -> Result<TypeLibrary, ImportError> {
+    let mut pdb = PDB::open(std::fs::File::open(path)?)?;
+    let pointer_size = match pdb.debug_information()?.machine_type()? {
+        pdb::MachineType::Amd64 | pdb::MachineType::Arm64 | pdb::MachineType::Ia64 => 8,
+        _ => 4,
+    };
+
+    let type_information = pdb.type_information()?;
+    let mut finder = type_information.finder();
+    let mut definitions = Vec::new();
+    let mut by_name = HashMap::new();
+    let mut iter = type_information.iter();
+    while let Some(item) = iter.next()? {
+        finder.update(&iter);
+        // Unknown records (e.g. LF_VTSHAPE) are not needed
+        let Ok(data) = item.parse() else {
+            continue;
+        };
+        let (name, forward_reference) = match &data {
+            TypeData::Class(c) => (c.name, c.properties.forward_reference()),
+            TypeData::Union(u) => (u.name, u.properties.forward_reference()),
+            TypeData::Enumeration(e) => (e.name, e.properties.forward_reference()),
+            _ => continue,
+        };
+        if !forward_reference {
+            let name = name.to_string().into_owned();
+            if !by_name.contains_key(&name) {
+                by_name.insert(name.clone(), item.index());
+                definitions.push((name, item.index()));
+            }
+        }
+    }
+
+    let mut importer = Importer {
+        finder: &finder,
+        by_name,
+        pointer_size,
+        resolved: HashMap::new(),
+        in_progress: HashSet::new(),
+        vftables: Vec::new(),
+    };
+
+    let mut library = TypeLibrary::new();
+    for (name, index) in definitions {
+        let dt = importer.resolve(index, false)?;
+        let description = match &dt {
+            DataTypeEnum::UnionDataType(_) => "Union (PDB)",
+            DataTypeEnum::EnumDataType(_) => "Enum (PDB)",
+            _ => "Structure (PDB)",
+        };
+        library.insert(name, (description.into(), dt));
+    }
+    for vftable in std::mem::take(&mut importer.vftables) {
+        library.insert(
+            vftable.get_name(),
+            ("Virtual function table (PDB)".into(), vftable.into()),
+        );
+    }
+
+    let symbol_table = pdb.global_symbols()?;
+    let mut symbols = symbol_table.iter();
+    while let Some(symbol) = symbols.next()? {
+        if let Ok(pdb::SymbolData::UserDefinedType(udt)) = symbol.parse() {
+            let name = udt.name.to_string().into_owned();
+            if library.contains_key(&name) {
+                continue;
+            }
+            let dt = importer.resolve(udt.type_index, false)?;
+            library.insert(name, (format!("Typedef of {} (PDB)", dt.get_name()), dt));
+        }
+    }
+    Ok(library)
+}
+
+/// Loads the public and global data symbols of a PDB, relative to the module base.
+pub fn load_symbols(path: &Path) -> Result<SymbolTable, ImportError> {
+    let mut pdb = PDB::open(std::fs::File::open(path)?)?;
+    let address_map = pdb.address_map()?;
+    let symbol_table = pdb.global_symbols()?;
+
+    let mut result = Vec::new();
+    let mut symbols = symbol_table.iter();
+    while let Some(symbol) = symbols.next()? {
+        let (name, offset) = match symbol.parse() {
+            Ok(pdb::SymbolData::Public(data)) => (data.name, data.offset),
+            Ok(pdb::SymbolData::Data(data)) => (data.name, data.offset),
+            _ => continue,
+        };
+        if let Some(rva) = offset.to_rva(&address_map) {
+            result.push(Symbol {
+                name: name.to_string().into_owned(),
+                rva: u64::from(rva.0),
+                size: 0,
+            });
+        }
+    }
+    Ok(SymbolTable::new(result))
+}
+
+struct Importer<'a, 's> {
+    finder: &'a TypeFinder<'s>,
+    // Definitions of the forward referenced types
+    by_name: HashMap<String, TypeIndex>,
+    pointer_size: usize,
+    resolved: HashMap<TypeIndex, DataTypeEnum>,
+    in_progress: HashSet<TypeIndex>,
+    vftables: Vec<StructDataType>,
+}
+
+impl<'s> Importer<'_, 's> {
+    fn parse(&self, index: TypeIndex) -> Result<TypeData<'s>, ImportError> {
+        Ok(self.finder.find(index)?.parse()?)
+    }
+
+    /// Follows forward references (`struct Foo;`) to the definition of the type.
+    fn definition(&self, index: TypeIndex) -> Result<TypeIndex, ImportError> {
+        let name = match self.parse(index)? {
+            TypeData::Class(c) if c.properties.forward_reference() => c.name,
+            TypeData::Union(u) if u.properties.forward_reference() => u.name,
+            TypeData::Enumeration(e) if e.properties.forward_reference() => e.name,
+            _ => return Ok(index),
+        };
+        Ok(*self.by_name.get(&*name.to_string()).unwrap_or(&index))
+    }
+
+    fn resolve(
+        &mut self,
+        index: TypeIndex,
+        behind_pointer: bool,
+    ) -> Result<DataTypeEnum, ImportError> {
+        let index = self.definition(index)?;
+        let data = self.parse(index)?;
+        if let TypeData::Class(c) = &data {
+            if behind_pointer || self.in_progress.contains(&index) {
+                // Only keep the name of pointed composites, the definition lives in the library
+                return Ok(StructDataType::new(c.name.to_string().into_owned(), Vec::new()).into());
+            }
+        }
+        if let TypeData::Union(u) = &data {
+            if behind_pointer || self.in_progress.contains(&index) {
+                return Ok(StructDataType::new(u.name.to_string().into_owned(), Vec::new()).into());
+            }
+        }
+        if let Some(dt) = self.resolved.get(&index) {
+            return Ok(dt.clone());
+        }
+        self.in_progress.insert(index);
+
+        let dt: DataTypeEnum = match data {
+            TypeData::Primitive(p) => {
+                let dt = primitive(p.kind);
+                match p.indirection {
+                    Some(indirection) => {
+                        let size = match indirection {
+                            pdb::Indirection::Near16 => 2,
+                            pdb::Indirection::Near32
+                            | pdb::Indirection::Far16
+                            | pdb::Indirection::Huge16 => 4,
+                            pdb::Indirection::Far32 => 6,
+                            pdb::Indirection::Near64 => 8,
+                            pdb::Indirection::Near128 => 16,
+                        };
+                        PointerDataType::new(dt).with_size(size).into()
+                    }
+                    None => dt,
+                }
+            }
+            TypeData::Pointer(p) => {
+                let target = self.resolve(p.underlying_type, true)?;
+                PointerDataType::new(target)
+                    .with_size(usize::from(p.attributes.size()))
+                    .into()
+            }
+            TypeData::Modifier(m) => self.resolve(m.underlying_type, behind_pointer)?,
+            TypeData::Bitfield(b) => self.resolve(b.underlying_type, false)?,
+            TypeData::Array(a) => {
+                let element = self.resolve(a.element_type, false)?;
+                // Dimensions are cumulated byte sizes, innermost first
+                let mut dt = element;
+                let mut inner_size = dt.get_size().max(1);
+                for dim in a.dimensions {
+                    let count = dim as usize / inner_size;
+                    dt = ArrayDataType::new(dt, count).into();
+                    inner_size = (dim as usize).max(1);
+                }
+                dt
+            }
+            TypeData::Enumeration(e) => {
+                let size = self.resolve(e.underlying_type, false)?.get_size();
+                let mut dt = EnumDataType::new(
+                    e.name.to_string().into_owned(),
+                    IntSize::try_from(size).unwrap_or_default(),
+                );
+                for field in self.fields(e.fields)? {
+                    if let TypeData::Enumerate(variant) = field {
+                        let value = match variant.value {
+                            pdb::Variant::U8(v) => i64::from(v),
+                            pdb::Variant::U16(v) => i64::from(v),
+                            pdb::Variant::U32(v) => i64::from(v),
+                            pdb::Variant::U64(v) => v as i64,
+                            pdb::Variant::I8(v) => i64::from(v),
+                            pdb::Variant::I16(v) => i64::from(v),
+                            pdb::Variant::I32(v) => i64::from(v),
+                            pdb::Variant::I64(v) => v,
+                        };
+                        dt.push_variant(variant.name.to_string().into_owned(), value);
+                    }
+                }
+                dt.into()
+            }
+            TypeData::Class(c) => {
+                let name = c.name.to_string().into_owned();
+                let mut s =
+                    StructDataType::new(name.clone(), Vec::new()).with_size(c.size as usize);
+                if let Some(fields) = c.fields {
+                    for (offset, entry) in self.members(&name, fields)? {
+                        s.add_entry_at(offset, entry);
+                    }
+                }
+                s.into()
+            }
+            TypeData::Union(u) => {
+                let mut dt = UnionDataType::new(u.name.to_string().into_owned(), Vec::new());
+                for (_, entry) in self.members(&u.name.to_string(), u.fields)? {
+                    dt.push_entry(entry);
+                }
+                dt.into()
+            }
+            TypeData::Procedure(p) => {
+                let return_type = match p.return_type {
+                    Some(t) => self.resolve(t, true)?.get_name(),
+                    None => "void".into(),
+                };
+                self.function(index, return_type, p.argument_list)?
+            }
+            TypeData::MemberFunction(m) => {
+                let return_type = self.resolve(m.return_type, true)?.get_name();
+                self.function(index, return_type, m.argument_list)?
+            }
+            // Not a data type (e.g. a field or method record), keep it opaque
+            _ => StructDataType::new(format!("unknown_{}", index.0), Vec::new()).into(),
+        };
+
+        self.in_progress.remove(&index);
+        self.resolved.insert(index, dt.clone());
+        Ok(dt)
+    }
+
+    fn function(
+        &mut self,
+        index: TypeIndex,
+        return_type: String,
+        argument_list: TypeIndex,
+    ) -> Result<DataTypeEnum, ImportError> {
+        let mut f = FunctionDataType::new(format!("fn_{:#x}", index.0), return_type);
+        if let TypeData::ArgumentList(args) = self.parse(argument_list)? {
+            for (i, arg) in args.arguments.into_iter().enumerate() {
+                // A trailing `NoType` argument marks variadic functions
+                if let TypeData::Primitive(pdb::PrimitiveType {
+                    kind: PrimitiveKind::NoType,
+                    indirection: None,
+                }) = self.parse(arg)?
+                {
+                    f.set_variadic(true);
+                    continue;
+                }
+                let type_name = self.resolve(arg, true)?.get_name();
+                f.push_parameter(format!("param_{}", i + 1), type_name);
+            }
+        }
+        Ok(f.into())
+    }
+
+    /// Records of a field list, following its continuations.
+    fn fields(&self, index: TypeIndex) -> Result<Vec<TypeData<'s>>, ImportError> {
+        let mut fields = Vec::new();
+        let mut next = Some(index);
+        while let Some(index) = next {
+            match self.parse(index)? {
+                TypeData::FieldList(list) => {
+                    fields.extend(list.fields);
+                    next = list.continuation;
+                }
+                _ => next = None,
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Data members of a class or union, with their offset.
+    fn members(
+        &mut self,
+        class_name: &str,
+        fields: TypeIndex,
+    ) -> Result<Vec<(usize, StructEntry)>, ImportError> {
+        let mut members = Vec::new();
+        let mut vftable = StructDataType::new(format!("{class_name}::vftable"), Vec::new());
+        let mut has_vfptr = false;
+
+        // Fields borrow the finder, resolve them once the records are collected
+        let records: Vec<_> = self
+            .fields(fields)?
+            .into_iter()
+            .filter_map(|field| match field {
+                TypeData::Member(m) => Some((
+                    Some(m.name.to_string().into_owned()),
+                    m.field_type,
+                    m.offset as usize,
+                    None,
+                )),
+                TypeData::BaseClass(b) => Some((None, b.base_class, b.offset as usize, None)),
+                TypeData::VirtualFunctionTablePointer(_) => {
+                    has_vfptr = true;
+                    None
+                }
+                TypeData::Method(m) => m.vtable_offset.map(|vt_offset| {
+                    (
+                        Some(m.name.to_string().into_owned()),
+                        m.method_type,
+                        vt_offset as usize,
+                        Some(()),
+                    )
+                }),
+                _ => None,
+            })
+            .collect();
+        let overloads: Vec<_> = self
+            .fields(fields)?
+            .into_iter()
+            .filter_map(|field| match field {
+                TypeData::OverloadedMethod(m) => {
+                    Some((m.name.to_string().into_owned(), m.method_list))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut virtual_methods = Vec::new();
+        for (name, method_list) in overloads {
+            if let TypeData::MethodList(list) = self.parse(method_list)? {
+                for method in list.methods {
+                    if let Some(vt_offset) = method.vtable_offset {
+                        virtual_methods.push((
+                            name.clone(),
+                            method.method_type,
+                            vt_offset as usize,
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (name, type_index, offset, is_virtual) in records {
+            if is_virtual.is_some() {
+                virtual_methods.push((name.unwrap_or_default(), type_index, offset));
+                continue;
+            }
+            let bitfield = match self.parse(type_index)? {
+                TypeData::Bitfield(b) => Some((b.position, b.length)),
+                _ => None,
+            };
+            let dt = self.resolve(type_index, false)?;
+            let name = name.unwrap_or_else(|| format!("base_{}", dt.get_name()));
+            let mut entry = StructEntry::new(name, dt);
+            if let Some((position, length)) = bitfield {
+                entry = entry.with_bitfield(position, length);
+            }
+            members.push((offset, entry));
+        }
+
+        for (name, method_type, vt_offset) in virtual_methods {
+            let f = self.resolve(method_type, true)?;
+            let slot = PointerDataType::new(f).with_size(self.pointer_size);
+            vftable.add_entry_at(vt_offset, StructEntry::new(name, slot.into()));
+        }
+        if has_vfptr {
+            let pointed = StructDataType::new(vftable.get_name(), Vec::new());
+            let vfptr = PointerDataType::new(pointed.into()).with_size(self.pointer_size);
+            members.insert(0, (0, StructEntry::new("__vfptr".into(), vfptr.into())));
+        }
+        if !vftable.get_entries().is_empty() {
+            self.vftables.push(vftable);
+        }
+        Ok(members)
+    }
+}
+
+fn primitive(kind: PrimitiveKind) -> DataTypeEnum {
+    use IntSize::{Integer16, Integer32, Integer64, Integer8};
+    let int = |size: IntSize, signed: bool| -> DataTypeEnum {
+        IntegerDataType::default()
+            .with_size(size)
+            .with_signed(signed)
+            .into()
+    };
+    let raw = |size: usize| -> DataTypeEnum {
+        let byte = IntegerDataType::default()
+            .with_size(Integer8)
+            .with_hex(true);
+        ArrayDataType::new(byte.into(), size).into()
+    };
+    match kind {
+        PrimitiveKind::NoType | PrimitiveKind::Void => {
+            StructDataType::new("void".into(), Vec::new()).into()
+        }
+        PrimitiveKind::Char | PrimitiveKind::RChar | PrimitiveKind::I8 => int(Integer8, true),
+        PrimitiveKind::UChar | PrimitiveKind::U8 => int(Integer8, false),
+        PrimitiveKind::WChar
+        | PrimitiveKind::RChar16
+        | PrimitiveKind::U16
+        | PrimitiveKind::UShort => int(Integer16, false),
+        PrimitiveKind::Short | PrimitiveKind::I16 => int(Integer16, true),
+        PrimitiveKind::Long | PrimitiveKind::I32 => int(Integer32, true),
+        PrimitiveKind::ULong | PrimitiveKind::U32 | PrimitiveKind::RChar32 => int(Integer32, false),
+        PrimitiveKind::HRESULT => IntegerDataType::default().with_hex(true).into(),
+        PrimitiveKind::Quad | PrimitiveKind::I64 => int(Integer64, true),
+        PrimitiveKind::UQuad | PrimitiveKind::U64 => int(Integer64, false),
+        PrimitiveKind::F32 | PrimitiveKind::F32PP => FloatDataType::default().into(),
+        PrimitiveKind::F64 => FloatDataType::default()
+            .with_precision(FloatPrecision::Double)
+            .into(),
+        PrimitiveKind::Bool8 => BooleanDataType::default().into(),
+        PrimitiveKind::Bool16 => BooleanDataType::default().with_size(2).into(),
+        PrimitiveKind::Bool32 => BooleanDataType::default().with_size(4).into(),
+        PrimitiveKind::Bool64 => BooleanDataType::default().with_size(8).into(),
+        PrimitiveKind::F16 => raw(2),
+        PrimitiveKind::F48 => raw(6),
+        PrimitiveKind::F80 | PrimitiveKind::Complex80 => raw(10),
+        PrimitiveKind::Complex32 => raw(8),
+        PrimitiveKind::Octa | PrimitiveKind::UOcta | PrimitiveKind::I128 | PrimitiveKind::U128 => {
+            raw(16)
+        }
+        PrimitiveKind::F128 | PrimitiveKind::Complex64 => raw(16),
+        PrimitiveKind::Complex128 => raw(32),
+        _ => raw(0),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Built by hand: Game::Player has a `team:3` and a `flags:5` bitfield sharing an uint,
+    // Game::Value is an int/float union, Game::State an enum, Game::Health an int typedef,
+    // and player_update a public symbol at .text+0x10, .text being at 0x1000
+    fn fixture() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sample.pdb")
+    }
+
+    #[test]
+    fn test_pdb_import() {
+        let lib = import_file(&fixture()).expect("Should import the fixture");
+
+        let DataTypeEnum::StructDataType(player) = &lib["Game::Player"].1 else {
+            panic!("Player should be a struct");
+        };
+        assert_eq!(player.get_size(), 12);
+        let entries = player.get_entries();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[1].get_offset(), 4);
+        assert_eq!(entries[2].get_offset(), 4);
+        let flags = entries[2]
+            .get_bitfield()
+            .expect("flags should be a bitfield");
+        assert_eq!((flags.bit_offset, flags.bit_size), (3, 5));
+        assert_eq!(entries[3].get_datatype().get_name(), "Game::State");
+
+        let DataTypeEnum::UnionDataType(value) = &lib["Game::Value"].1 else {
+            panic!("Value should be a union");
+        };
+        assert_eq!(value.get_entries().len(), 2);
+        assert_eq!(value.get_size(), 4);
+
+        let DataTypeEnum::EnumDataType(state) = &lib["Game::State"].1 else {
+            panic!("State should be an enum");
+        };
+        assert_eq!(state.get_size(), 4);
+        assert_eq!(state.get_variants()[1], ("Dead".into(), 1));
+
+        assert_eq!(lib["Game::Health"].1.get_size(), 4);
+    }
+
+    #[test]
+    fn test_pdb_symbols() {
+        let table = load_symbols(&fixture()).expect("Should load the fixture symbols");
+        let symbol = table
+            .find_by_name("player_update")
+            .expect("The public symbol should be loaded");
+        assert_eq!(symbol.rva, 0x1010);
+    }
+}
@@ -1,2 +1,6 @@
+pub mod interop;
 pub mod ops;
+pub mod scan;
+pub mod snapshot;
+pub mod symbols;
 pub mod typing;
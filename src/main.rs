@@ -5,23 +5,47 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::rc::Rc;
-use sysinfo::{ProcessRefreshKind, RefreshKind, System};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use sysinfo::{
+    Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, RefreshKind, System, UpdateKind,
+};
 
 use rs_class::{
-    ops::{Process, SystemProcess},
+    interop::{dwarf, pdb, ImportError},
+    ops::{
+        DumpProcess, LaunchOptions, LaunchedProcess, MemoryReader, Module, Process, ProcessError,
+        SystemProcess, Target, ViewId,
+    },
+    scan::{signature::Signature, ScanError},
+    symbols::{self, SymbolTable, Symbolizer},
     typing::{
-        BooleanDataType, DataTypeEnum, FloatDataType, FloatPrecision, IntSize, IntegerDataType,
-        StrDataType, StructDataType,
+        ArrayDataType, BooleanDataType, DataType, DataTypeEnum, Edit, EditHistory, FloatDataType,
+        FloatPrecision, IntSize, IntegerDataType, StrDataType, StructChange, StructDataType,
+        StructEntry, TypeLibrary, Typedef,
     },
 };
 
 mod gui;
+mod settings;
+use gui::attach_dialog::AttachDialog;
+use gui::debug_info_dialog::DebugInfoDialog;
+use gui::dump_dialog::DumpDialog;
+use gui::export_dialog::ExportDialog;
+use gui::hex_view::{FieldConversion, HexView};
+use gui::import_dialog::ImportDialog;
+use gui::launch_dialog::LaunchDialog;
 use gui::load_dialog::LoadDialog;
+use gui::pointer_scan::PointerScanWindow;
 use gui::process_dialog::ProcessDialog;
 use gui::prompt_save_dialog::Choice;
 use gui::save_dialog::SaveDialog;
+use gui::signature_dialog::SignatureDialog;
+use gui::struct_editor::{edit_struct, StructEditor};
 use gui::type_selection_dialog::TypeSelectionDialog;
+use gui::typedef_dialog::TypedefDialog;
 use gui::{Dialog, DialogState};
+use settings::Settings;
 
 fn main() {
     let native_options = eframe::NativeOptions::default();
@@ -34,31 +58,75 @@ fn main() {
     .expect("eframe should run");
 }
 
-// (Description: String, dt: DataTypeEnum)
-type Typedef = (String, DataTypeEnum);
-
 #[derive(Debug, Serialize, Deserialize)]
 struct SaveData<'a> {
     typedefs: Cow<'a, HashMap<String, Typedef>>,
-    structs: Cow<'a, Vec<StructDataType>>,
+    structs: Cow<'a, [StructDataType]>,
+    // Signatures locating the root of the structs, by struct name
+    #[serde(default)]
+    signatures: Cow<'a, HashMap<String, Signature>>,
+    // Executable of the process the project was made on
+    #[serde(default)]
+    target: Option<Cow<'a, Target>>,
 }
 
 #[derive(Default)]
 struct MyEguiApp {
     struct_tabs: Vec<StructDataType>,
+    struct_editor: StructEditor,
+    hex_view: HexView,
+    pointer_scan: PointerScanWindow,
+    signatures: HashMap<String, Signature>,
+    // Signatures being resolved on worker threads, by struct name
+    signature_resolves: Vec<(String, JoinHandle<Result<u64, ScanError>>)>,
     system: System,
 
     // type system
     typedefs: Rc<RefCell<HashMap<String, Typedef>>>,
     selected_type: Option<String>,
+    // Types picked in the type selection dialog, most recent first
+    recent_types: Vec<String>,
 
-    selected_process: Option<Process>,
+    // Reads the memory of the selected process
+    reader: Option<MemoryReader>,
+    // Dump read by the reader instead of a running process
+    dump: Option<PathBuf>,
+    // Executable of the attached process, found again when it restarts
+    target: Option<Target>,
+    last_reattach_check: Option<Instant>,
+    // Process started by RsClass, and how it was last started
+    launched: Option<LaunchedProcess>,
+    launch_options: LaunchOptions,
+    // Debug types being loaded on worker threads, by file
+    debug_imports: Vec<(PathBuf, JoinHandle<Result<TypeLibrary, ImportError>>)>,
+    symbolizer: Symbolizer,
+    // Module symbols being loaded on a worker thread since the last attach
+    symbol_loader: Option<JoinHandle<Vec<(String, SymbolTable)>>>,
+    address_query: String,
+    lookup_view: Option<ViewId>,
     state: AppState,
 
+    // Kept between sessions
+    settings: Settings,
+
     // file saving
     save_file_location: Option<PathBuf>,
-    is_dirty: bool,
+    // Undo and redo of the struct and typedef edits, dirty since the last save
+    edits: EditHistory,
 }
+
+// How often the target is looked for once its process exited
+const REATTACH_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_RECENT_TYPES: usize = 10;
+const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO_SHORTCUTS: [egui::KeyboardShortcut; 2] = [
+    egui::KeyboardShortcut::new(
+        egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+        egui::Key::Z,
+    ),
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y),
+];
 #[derive(Debug, Clone, Copy)]
 enum SaveType {
     Normal,
@@ -66,16 +134,38 @@ enum SaveType {
     Quit,
 }
 
+// What the type picked in the type selection dialog is used for
+#[derive(Debug, Clone, Copy)]
+enum TypeTarget {
+    Entry { tab: usize, entry: usize },
+    NewField(FieldConversion),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ImportKind {
+    Ghidra,
+    Pdb,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Default)]
 enum AppState {
     #[default]
     Normal,
     ProcessSelection(gui::process_dialog::ProcessDialog),
+    Launch(LaunchDialog),
+    Attach(AttachDialog),
+    OpenDump(DumpDialog),
     PromptForSave(gui::prompt_save_dialog::PromptSaveDialog, SaveType),
     Load(gui::load_dialog::LoadDialog),
+    Import(gui::import_dialog::ImportDialog, ImportKind),
+    Export(gui::export_dialog::ExportDialog),
+    DebugInfo(gui::debug_info_dialog::DebugInfoDialog),
     Save(gui::save_dialog::SaveDialog, SaveType),
-    TypeSelection(TypeSelectionDialog),
+    TypeSelection(TypeSelectionDialog, Option<TypeTarget>),
+    TypedefEditor(TypedefDialog),
+    // Signature of the named struct
+    Signature(SignatureDialog, String),
     Quit,
 }
 
@@ -178,6 +268,11 @@ impl MyEguiApp {
         );
         drop(typedefs);
 
+        s.settings = Settings::load().unwrap_or_else(|err_s| {
+            eprintln!("ERROR: Could not load the settings: {err_s}");
+            Settings::default()
+        });
+
         let system = System::new_with_specifics(
             RefreshKind::nothing().with_processes(ProcessRefreshKind::everything()),
         );
@@ -201,6 +296,8 @@ impl MyEguiApp {
         let data_to_save = SaveData {
             typedefs: Cow::Borrowed(&td),
             structs: Cow::Borrowed(&self.struct_tabs),
+            signatures: Cow::Borrowed(&self.signatures),
+            target: self.target.as_ref().map(Cow::Borrowed),
         };
 
         ron::ser::to_writer_pretty(file, &data_to_save, ron::ser::PrettyConfig::default())
@@ -215,10 +312,350 @@ impl MyEguiApp {
         )
         .map_err(|e| e.to_string())?;
         let loaded_data: SaveData = ron::de::from_reader(file).map_err(|e| e.to_string())?;
+        self.struct_editor.clear(self.reader.as_mut());
         self.struct_tabs = loaded_data.structs.into_owned();
+        self.signatures = loaded_data.signatures.into_owned();
+        self.target = loaded_data.target.map(Cow::into_owned);
         self.typedefs = Rc::new(RefCell::new(loaded_data.typedefs.into_owned()));
         Ok(())
     }
+
+    fn import_types(&mut self, path: &std::path::Path, kind: ImportKind) -> Result<usize, String> {
+        let library = match kind {
+            ImportKind::Ghidra => rs_class::interop::ghidra::import_file(path),
+            ImportKind::Pdb => pdb::import_file(path),
+        }
+        .map_err(|e| e.to_string())?;
+        let count = library.len();
+        self.add_typedefs("import types", library);
+
+        if let ImportKind::Pdb = kind {
+            // Public symbols are keyed by the PDB name, which matches the name of its module
+            let symbols = pdb::load_symbols(path).map_err(|e| e.to_string())?;
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            println!("Loaded {} symbols for {file_name}", symbols.len());
+            self.symbolizer.add_symbols(&file_name, symbols);
+        }
+        Ok(count)
+    }
+
+    fn apply_type(&mut self, target: TypeTarget, datatype: DataTypeEnum) {
+        match target {
+            TypeTarget::Entry { tab, entry } => {
+                if let Some(s) = self.struct_tabs.get_mut(tab) {
+                    edit_struct(&mut self.edits, tab, s, "retype field", false, |s| {
+                        s.set_entry_datatype(entry, datatype)
+                    });
+                }
+            }
+            TypeTarget::NewField(FieldConversion { tab, offset, len }) => {
+                let Some(s) = self.struct_tabs.get_mut(tab) else {
+                    return;
+                };
+                // Selections of several elements become arrays
+                let size = datatype.get_size();
+                let datatype = if size > 0 && len > size && len % size == 0 {
+                    ArrayDataType::new(datatype, len / size).into()
+                } else {
+                    datatype
+                };
+                let e = StructEntry::new(format!("field_{offset:02X}"), datatype);
+                edit_struct(&mut self.edits, tab, s, "convert to field", false, |s| {
+                    s.replace_at(offset, e)
+                });
+            }
+        }
+    }
+
+    // Adds types to the project as a single edit, replacing the types of the same name
+    fn add_typedefs(&mut self, label: &str, library: TypeLibrary) {
+        let mut typedefs = self.typedefs.borrow_mut();
+        let mut edit = Edit::new(label);
+        for (name, typedef) in library {
+            let before = typedefs.get(&name).cloned();
+            edit.set_typedef(name, before, Some(typedef));
+        }
+        edit.apply(&mut self.struct_tabs, &mut typedefs);
+        drop(typedefs);
+        self.edits.record(edit);
+    }
+
+    fn undo(&mut self) {
+        let changes = self.edits.undo_struct_changes();
+        let mut typedefs = self.typedefs.borrow_mut();
+        self.edits.undo(&mut self.struct_tabs, &mut typedefs);
+        drop(typedefs);
+        self.follow_struct_changes(&changes);
+    }
+
+    fn redo(&mut self) {
+        let changes = self.edits.redo_struct_changes();
+        let mut typedefs = self.typedefs.borrow_mut();
+        self.edits.redo(&mut self.struct_tabs, &mut typedefs);
+        drop(typedefs);
+        self.follow_struct_changes(&changes);
+    }
+
+    // Keeps the state kept by struct index or name in step with the undone or redone changes
+    fn follow_struct_changes(&mut self, changes: &[StructChange]) {
+        self.struct_editor
+            .follow_struct_changes(changes, self.reader.as_mut());
+        for change in changes {
+            if let StructChange::Renamed { from, to } = change {
+                self.rename_signature(from, to);
+            }
+        }
+    }
+
+    // Moves the signature of a renamed struct, and its resolution, to the new name
+    fn rename_signature(&mut self, from: &str, to: &str) {
+        if let Some(signature) = self.signatures.remove(from) {
+            self.signatures.insert(to.to_owned(), signature);
+        }
+        for (name, _) in &mut self.signature_resolves {
+            if name == from {
+                *name = to.to_owned();
+            }
+        }
+    }
+
+    // Opens a process and reads it from now on, returns its modules with debug information
+    fn attach(&mut self, pid: Pid) -> Result<Vec<Module>, ProcessError> {
+        let mut process = Process::new(pid);
+        process.open()?;
+        let debug_modules = Self::modules_with_debug_info(&process);
+        // Module relative roots resolve against the new modules
+        match process.modules() {
+            Ok(modules) => {
+                self.symbolizer.set_modules(modules.clone());
+                // The worker reads the modules through its own handle
+                self.symbol_loader = Some(std::thread::spawn(move || {
+                    let mut process = Process::new(pid);
+                    match process.open() {
+                        Ok(()) => symbols::load_module_symbols(&modules, &mut process),
+                        Err(err) => {
+                            eprintln!("ERROR: Could not open the process for its symbols: {err}");
+                            Vec::new()
+                        }
+                    }
+                }));
+            }
+            Err(err_s) => eprintln!("ERROR: Could not list the modules: {err_s}"),
+        }
+        self.lookup_view = None;
+        self.struct_editor.reset_views();
+        self.hex_view.reset_view();
+        self.reader = Some(MemoryReader::spawn(process));
+        self.dump = None;
+        let names: Vec<_> = self.signatures.keys().cloned().collect();
+        self.resolve_signatures(names);
+
+        self.refresh_processes(ProcessesToUpdate::Some(&[pid]));
+        if let Some(process) = self.system.process(pid) {
+            self.target = Some(Target::of(process));
+        }
+        Ok(debug_modules)
+    }
+
+    // Reads a memory dump instead of a process, its modules resolve module relative roots
+    fn open_dump(&mut self, path: PathBuf, raw_base: u64) -> Result<(), String> {
+        let mut dump = DumpProcess::from_file(&path, raw_base)?;
+        dump.open().map_err(|err| err.to_string())?;
+        self.symbolizer
+            .set_modules(dump.modules().map_err(|err| err.to_string())?);
+        self.symbol_loader = None;
+        self.lookup_view = None;
+        self.struct_editor.reset_views();
+        self.hex_view.reset_view();
+        self.reader = Some(MemoryReader::spawn(dump));
+        // Not a process to find again when it restarts
+        self.target = None;
+        self.dump = Some(path);
+        Ok(())
+    }
+
+    // Attaches to a process, then offers to load the types of its modules with debug information
+    fn open_process(&mut self, pid: Pid) -> AppState {
+        match self.attach(pid) {
+            Err(err) => {
+                eprintln!("ERROR: Could not open the process: {err}");
+                AppState::Normal
+            }
+            Ok(debug_modules) if debug_modules.is_empty() => AppState::Normal,
+            Ok(debug_modules) => AppState::DebugInfo(DebugInfoDialog::new(debug_modules)),
+        }
+    }
+
+    // Whether the attached process is still running, as of the last process refresh
+    fn is_attached(&self) -> bool {
+        self.reader.as_ref().is_some_and(|r| {
+            !r.is_stopped()
+                && self.system.process(r.pid()).is_some_and(|p| {
+                    !matches!(p.status(), ProcessStatus::Zombie | ProcessStatus::Dead)
+                })
+        })
+    }
+
+    // Refreshes what finding the target needs, the executables are only read once per process
+    fn refresh_processes(&mut self, processes: ProcessesToUpdate) {
+        let kind = ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet);
+        self.system
+            .refresh_processes_specifics(processes, true, kind);
+    }
+
+    // Running process of the project target, unless the attached one runs it
+    fn find_target(&mut self) -> Option<Pid> {
+        let target = self.target.clone()?;
+        if let Some(pid) = self.reader.as_ref().map(MemoryReader::pid) {
+            self.refresh_processes(ProcessesToUpdate::Some(&[pid]));
+            let running = self.system.process(pid).is_some_and(|p| target.matches(p));
+            if running && self.is_attached() {
+                return None;
+            }
+        }
+        self.refresh_processes(ProcessesToUpdate::All);
+        target.find(&self.system)
+    }
+
+    // Attaches again once the target exited and restarted
+    fn reattach(&mut self) {
+        if !self.settings.auto_reattach || self.reader.is_none() || self.target.is_none() {
+            return;
+        }
+        if self
+            .last_reattach_check
+            .is_some_and(|t| t.elapsed() < REATTACH_INTERVAL)
+        {
+            return;
+        }
+        self.last_reattach_check = Some(Instant::now());
+        // Processes are only listed once the attached one is gone
+        if let Some(reader) = self.reader.as_ref().filter(|r| !r.is_stopped()) {
+            let pid = reader.pid();
+            self.refresh_processes(ProcessesToUpdate::Some(&[pid]));
+            if self.is_attached() {
+                return;
+            }
+        }
+        self.refresh_processes(ProcessesToUpdate::All);
+        let pid = self.target.as_ref().and_then(|t| t.find(&self.system));
+        if let Some(pid) = pid {
+            match self.attach(pid) {
+                Ok(_) => println!("Reattached to process {pid}"),
+                Err(err) => eprintln!("ERROR: Could not reattach to process {pid}: {err}"),
+            }
+        }
+    }
+
+    // Looks for the roots of the named structs with their signatures, in the attached process
+    fn resolve_signatures(&mut self, names: Vec<String>) {
+        // The workers open the process by pid, which a dump does not have
+        let Some(pid) = self
+            .reader
+            .as_ref()
+            .filter(|_| self.dump.is_none())
+            .map(MemoryReader::pid)
+        else {
+            return;
+        };
+        for name in names {
+            let Some(signature) = self.signatures.get(&name).cloned() else {
+                continue;
+            };
+            let handle = std::thread::spawn(move || {
+                let mut process = Process::new(pid);
+                process.open()?;
+                signature.resolve(&mut process)
+            });
+            self.signature_resolves.push((name, handle));
+        }
+    }
+
+    // Moves the structs whose signature is resolved to their new root
+    fn finish_signature_resolves(&mut self) {
+        let (finished, pending) = std::mem::take(&mut self.signature_resolves)
+            .into_iter()
+            .partition(|(_, handle)| handle.is_finished());
+        self.signature_resolves = pending;
+        for (name, handle) in finished {
+            match handle.join() {
+                Ok(Ok(address)) => {
+                    let tab = self.struct_tabs.iter().position(|s| s.get_name() == name);
+                    if let Some(tab) = tab {
+                        println!("Signature of {name} resolved to {address:#X}");
+                        self.struct_editor
+                            .set_address_query(tab, format!("{address:#X}"));
+                    }
+                }
+                Ok(Err(err)) => {
+                    eprintln!("ERROR: Could not resolve the signature of {name}: {err}")
+                }
+                Err(_) => eprintln!("ERROR: The signature thread of {name} panicked"),
+            }
+        }
+    }
+
+    // Publishes the module symbols once they are loaded
+    fn finish_symbol_loading(&mut self) {
+        if !self
+            .symbol_loader
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            return;
+        }
+        let Some(Ok(tables)) = self.symbol_loader.take().map(JoinHandle::join) else {
+            eprintln!("ERROR: The symbol loading thread panicked");
+            return;
+        };
+        let mut count = 0;
+        for (module, table) in tables {
+            count += table.len();
+            self.symbolizer.add_symbols(&module, table);
+        }
+        println!("Loaded {count} module symbols");
+    }
+
+    // Adds the types of the finished debug info imports
+    fn finish_debug_imports(&mut self) {
+        let (finished, pending) = std::mem::take(&mut self.debug_imports)
+            .into_iter()
+            .partition(|(_, handle)| handle.is_finished());
+        self.debug_imports = pending;
+        for (path, handle) in finished {
+            let result = handle.join().unwrap_or_else(|_| {
+                Err(ImportError::ParseError("the import thread panicked".into()))
+            });
+            match result {
+                Ok(library) => {
+                    println!("Loaded {} types from {}", library.len(), path.display());
+                    self.add_typedefs("load debug types", library);
+                }
+                Err(err_s) => eprintln!(
+                    "ERROR: Could not load the types of {}: {err_s}",
+                    path.display()
+                ),
+            }
+        }
+    }
+
+    fn modules_with_debug_info(process: &Process) -> Vec<Module> {
+        match process.modules() {
+            Ok(modules) => modules
+                .into_iter()
+                .filter(|m| dwarf::has_debug_info(&m.path))
+                .collect(),
+            Err(err_s) => {
+                eprintln!("ERROR: Could not list the modules of the process: {err_s}");
+                Vec::new()
+            }
+        }
+    }
+
+    fn export_types(&self, path: &std::path::Path) -> Result<(), String> {
+        rs_class::interop::ghidra::export_file(path, &self.typedefs.borrow(), &self.struct_tabs)
+            .map_err(|e| e.to_string())
+    }
 }
 
 impl eframe::App for MyEguiApp {
@@ -227,23 +664,49 @@ impl eframe::App for MyEguiApp {
         let close_requested = ctx.input(|i| i.viewport().close_requested());
         if close_requested {
             match self.state {
-                AppState::Save(_, _) | AppState::PromptForSave(_, _) | AppState::Load(_) => {
+                AppState::Save(_, _)
+                | AppState::PromptForSave(_, _)
+                | AppState::Load(_)
+                | AppState::Import(_, _)
+                | AppState::Export(_) => {
                     ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
                 }
                 AppState::Quit => {}
                 _ => {
-                    if self.is_dirty {
+                    if self.edits.is_dirty() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
                     }
                 }
             }
         }
 
+        if let Some(reader) = &mut self.reader {
+            reader.poll();
+            ctx.request_repaint_after(reader.get_refresh_rate());
+        }
+        self.finish_debug_imports();
+        self.finish_symbol_loading();
+        self.finish_signature_resolves();
+        if !self.debug_imports.is_empty()
+            || self.symbol_loader.is_some()
+            || !self.signature_resolves.is_empty()
+            || self.pointer_scan.is_busy()
+        {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        // Reaps the launched process once it exited
+        if self.launched.as_mut().is_some_and(|l| l.has_exited()) {
+            self.launched = None;
+        }
+        if let AppState::Normal = self.state {
+            self.reattach();
+        }
+
         /* DIALOGS AND STATE TRANSITIONS */
         let nstate = match &mut self.state {
             AppState::Normal => None,
             AppState::Quit => {
-                if self.is_dirty {
+                if self.edits.is_dirty() {
                     Some(AppState::PromptForSave(Default::default(), SaveType::Quit))
                 } else {
                     ctx.send_viewport_cmd(egui::ViewportCommand::Close);
@@ -251,7 +714,7 @@ impl eframe::App for MyEguiApp {
                 }
             }
             AppState::Load(ld) => {
-                if self.is_dirty {
+                if self.edits.is_dirty() {
                     Some(AppState::PromptForSave(Default::default(), SaveType::Load))
                 } else {
                     ld.show(ctx);
@@ -265,17 +728,59 @@ impl eframe::App for MyEguiApp {
                             match load_success {
                                 Ok(()) => {
                                     println!("Data sucessfully loaded!");
-                                    self.is_dirty = false;
+                                    self.edits.clear();
+                                    // Offer to attach to the target of the project
+                                    let target = self.target.clone();
+                                    target.zip(self.find_target()).map_or(
+                                        Some(AppState::Normal),
+                                        |(target, pid)| {
+                                            Some(AppState::Attach(AttachDialog::new(target, pid)))
+                                        },
+                                    )
                                 }
                                 Err(err_s) => {
                                     eprintln!("ERROR: Could not load from file: {err_s}");
+                                    Some(AppState::Normal)
                                 }
                             }
-                            Some(AppState::Normal)
                         }
                     }
                 }
             }
+            AppState::Import(dialog, kind) => {
+                dialog.show(ctx);
+                match dialog.state() {
+                    DialogState::Open => None,
+                    DialogState::Cancelled => Some(AppState::Normal),
+                    DialogState::Selected(p) => {
+                        let (p, kind) = (p.clone(), *kind);
+                        match self.import_types(&p, kind) {
+                            Ok(count) => {
+                                println!("Imported {count} types from {}", p.display());
+                            }
+                            Err(err_s) => {
+                                eprintln!("ERROR: Could not import types: {err_s}");
+                            }
+                        }
+                        Some(AppState::Normal)
+                    }
+                }
+            }
+            AppState::Export(dialog) => {
+                dialog.show(ctx);
+                match dialog.state() {
+                    DialogState::Open => None,
+                    DialogState::Cancelled => Some(AppState::Normal),
+                    DialogState::Selected(p) => {
+                        let p = p.clone();
+                        match self.export_types(&p) {
+                            Ok(()) => println!("Types exported to {}", p.display()),
+                            Err(err_s) => eprintln!("ERROR: Could not export types: {err_s}"),
+                        }
+                        Some(AppState::Normal)
+                    }
+                }
+            }
             AppState::Save(dialog, save_type) => {
                 let st = *save_type;
                 if self.save_file_location.is_some() {
@@ -283,7 +788,7 @@ impl eframe::App for MyEguiApp {
                     match save_success {
                         Ok(()) => {
                             println!("File successfully saved!");
-                            self.is_dirty = false;
+                            self.edits.mark_saved();
                             match st {
                                 SaveType::Quit => Some(AppState::Quit),
                                 SaveType::Load => Some(AppState::Load(LoadDialog::new(self))),
@@ -324,73 +829,409 @@ impl eframe::App for MyEguiApp {
                 }
             }
             AppState::ProcessSelection(dialog) => {
+                dialog.show(ctx);
+                if dialog.state() != &DialogState::Open
+                    && dialog.get_ignored() != self.settings.ignored_processes
+                {
+                    self.settings.ignored_processes = dialog.get_ignored().to_vec();
+                    if let Err(err_s) = self.settings.save() {
+                        eprintln!("ERROR: Could not save the settings: {err_s}");
+                    }
+                }
+                match dialog.state() {
+                    DialogState::Open => None,
+                    DialogState::Selected(pid) => {
+                        let pid = *pid;
+                        Some(self.open_process(pid))
+                    }
+                    DialogState::Cancelled => Some(AppState::Normal),
+                }
+            }
+            AppState::Launch(dialog) => {
                 dialog.show(ctx);
                 match dialog.state() {
                     DialogState::Open => None,
+                    DialogState::Cancelled => Some(AppState::Normal),
+                    DialogState::Selected(options) => {
+                        self.launch_options = options.clone();
+                        match rs_class::ops::launch(&self.launch_options) {
+                            Err(err) => {
+                                eprintln!("ERROR: Could not launch the executable: {err}");
+                                Some(AppState::Normal)
+                            }
+                            Ok(launched) => {
+                                let pid = launched.pid();
+                                self.launched = Some(launched);
+                                Some(self.open_process(pid))
+                            }
+                        }
+                    }
+                }
+            }
+            AppState::Attach(dialog) => {
+                dialog.show(ctx);
+                match dialog.state() {
+                    DialogState::Open => None,
+                    DialogState::Cancelled => Some(AppState::Normal),
                     DialogState::Selected(pid) => {
-                        self.selected_process = Some(Process::new(*pid));
+                        let pid = *pid;
+                        Some(self.open_process(pid))
+                    }
+                }
+            }
+            AppState::OpenDump(dialog) => {
+                dialog.show(ctx);
+                match dialog.state() {
+                    DialogState::Open => None,
+                    DialogState::Cancelled => Some(AppState::Normal),
+                    DialogState::Selected((path, raw_base)) => {
+                        let (path, raw_base) = (path.clone(), *raw_base);
+                        match self.open_dump(path.clone(), raw_base) {
+                            Ok(()) => println!("Opened the dump {}", path.display()),
+                            Err(err_s) => eprintln!("ERROR: Could not open the dump: {err_s}"),
+                        }
                         Some(AppState::Normal)
                     }
+                }
+            }
+            AppState::DebugInfo(dialog) => {
+                dialog.show(ctx);
+                match dialog.state() {
+                    DialogState::Open => None,
                     DialogState::Cancelled => Some(AppState::Normal),
+                    DialogState::Selected(paths) => {
+                        for path in paths.clone() {
+                            let worker_path = path.clone();
+                            let handle =
+                                std::thread::spawn(move || dwarf::import_file(&worker_path));
+                            self.debug_imports.push((path, handle));
+                        }
+                        Some(AppState::Normal)
+                    }
                 }
             }
-            AppState::TypeSelection(dialog) => {
+            AppState::Signature(dialog, name) => {
+                dialog.show(ctx);
+                match dialog.state() {
+                    DialogState::Open => None,
+                    DialogState::Cancelled => Some(AppState::Normal),
+                    DialogState::Selected(signature) => {
+                        let name = name.clone();
+                        match signature.clone() {
+                            Some(signature) => {
+                                self.signatures.insert(name.clone(), signature);
+                                self.resolve_signatures(vec![name]);
+                            }
+                            None => {
+                                self.signatures.remove(&name);
+                            }
+                        }
+                        Some(AppState::Normal)
+                    }
+                }
+            }
+            AppState::TypeSelection(dialog, target) => {
                 dialog.show(ctx);
                 match dialog.state() {
                     DialogState::Open => None,
                     DialogState::Selected(data) => {
                         self.selected_type = Some(data.to_owned());
+                        self.recent_types.retain(|t| t != data);
+                        self.recent_types.insert(0, data.to_owned());
+                        self.recent_types.truncate(MAX_RECENT_TYPES);
+                        let datatype = self.typedefs.borrow().get(data).map(|(_, dt)| dt.clone());
+                        if let (Some(target), Some(datatype)) = (*target, datatype) {
+                            self.apply_type(target, datatype);
+                        }
                         Some(AppState::Normal)
                     }
                     DialogState::Cancelled => Some(AppState::Normal),
                 }
             }
+            AppState::TypedefEditor(dialog) => {
+                dialog.show(ctx, self.reader.as_mut(), &self.symbolizer);
+                for edit in dialog.take_edits() {
+                    edit.apply(&mut self.struct_tabs, &mut self.typedefs.borrow_mut());
+                    self.edits.record(edit);
+                }
+                match dialog.state() {
+                    DialogState::Open => None,
+                    _ => Some(AppState::Normal),
+                }
+            }
         };
 
         if let Some(s) = nstate {
             self.state = s;
         }
 
+        // Edits are only undone outside of the dialogs
+        if let AppState::Normal = self.state {
+            let (undo, redo) = ctx.input_mut(|i| {
+                // Checked first, as Ctrl+Shift+Z also matches Ctrl+Z
+                let redo = REDO_SHORTCUTS.iter().any(|s| i.consume_shortcut(s));
+                (i.consume_shortcut(&UNDO_SHORTCUT), redo)
+            });
+            if redo {
+                self.redo();
+            } else if undo {
+                self.undo();
+            }
+        }
+
         /* GUI INTERFACE */
         egui::TopBottomPanel::top("menu").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("open process").clicked() {
-                    let dialog = ProcessDialog::default();
+                    let dialog = ProcessDialog::new(&self.settings.ignored_processes);
                     self.state = AppState::ProcessSelection(dialog);
                 };
+                if ui.button("launch executable").clicked() {
+                    let dialog = LaunchDialog::new(&self.launch_options);
+                    self.state = AppState::Launch(dialog);
+                };
+                if ui.button("open dump").clicked() {
+                    self.state = AppState::OpenDump(DumpDialog::new());
+                };
                 if ui.button("load").clicked() {
                     self.state = AppState::Load(LoadDialog::new(self));
                 };
+                if ui.button("import").clicked() {
+                    let dialog = ImportDialog::new("Ghidra data types", &["xml", "gdt"]);
+                    self.state = AppState::Import(dialog, ImportKind::Ghidra);
+                };
+                if ui.button("load pdb").clicked() {
+                    let dialog = ImportDialog::new("Program database", &["pdb"]);
+                    self.state = AppState::Import(dialog, ImportKind::Pdb);
+                };
+                if ui.button("export").clicked() {
+                    self.state = AppState::Export(ExportDialog::new());
+                };
                 let save_button = egui::Button::new("save");
-                if ui.add_enabled(self.is_dirty, save_button).clicked() {
+                if ui.add_enabled(self.edits.is_dirty(), save_button).clicked() {
                     self.state = AppState::Save(SaveDialog::new(self), SaveType::Normal);
                 };
+                let undo_label = self
+                    .edits
+                    .undo_label()
+                    .map(|l| format!("Undo {l} (Ctrl+Z)"));
+                let undo_button = ui.add_enabled(undo_label.is_some(), egui::Button::new("undo"));
+                if undo_button
+                    .on_hover_text(undo_label.unwrap_or_default())
+                    .clicked()
+                {
+                    self.undo();
+                }
+                let redo_label = self
+                    .edits
+                    .redo_label()
+                    .map(|l| format!("Redo {l} (Ctrl+Y)"));
+                let redo_button = ui.add_enabled(redo_label.is_some(), egui::Button::new("redo"));
+                if redo_button
+                    .on_hover_text(redo_label.unwrap_or_default())
+                    .clicked()
+                {
+                    self.redo();
+                }
                 if ui.button("type").clicked() {
-                    let dialog = TypeSelectionDialog::new(self.typedefs.clone());
-                    self.state = AppState::TypeSelection(dialog);
+                    let dialog =
+                        TypeSelectionDialog::new(self.typedefs.clone(), &self.recent_types);
+                    self.state = AppState::TypeSelection(dialog, None);
+                }
+                if ui.button("typedefs").clicked() {
+                    let dialog = TypedefDialog::new(self.typedefs.clone());
+                    self.state = AppState::TypedefEditor(dialog);
+                }
+                if ui.button("pointer scan").clicked() {
+                    self.pointer_scan.open();
                 }
             });
         });
+        self.pointer_scan.show(
+            ctx,
+            self.reader
+                .as_ref()
+                .filter(|_| self.dump.is_none())
+                .map(MemoryReader::pid),
+            &self.struct_tabs,
+            &mut self.struct_editor,
+            &self.symbolizer,
+        );
+        egui::TopBottomPanel::bottom("hex_view")
+            .resizable(true)
+            .default_height(250.0)
+            .show(ctx, |ui| {
+                let conversion = self.hex_view.show(
+                    ui,
+                    &self.struct_tabs,
+                    &self.struct_editor,
+                    self.reader.as_mut(),
+                    &self.symbolizer,
+                );
+                if let Some(conversion) = conversion {
+                    let dialog =
+                        TypeSelectionDialog::new(self.typedefs.clone(), &self.recent_types);
+                    let target = TypeTarget::NewField(conversion);
+                    self.state = AppState::TypeSelection(dialog, Some(target));
+                }
+            });
         egui::CentralPanel::default().show(ctx, |ui| {
+            let response = self.struct_editor.show(
+                ui,
+                &mut self.struct_tabs,
+                &mut self.edits,
+                self.reader.as_mut(),
+                &self.symbolizer,
+            );
+            if let Some(name) = response
+                .edit_signature
+                .and_then(|tab| self.struct_tabs.get(tab))
+                .map(StructDataType::get_name)
+            {
+                let dialog = SignatureDialog::new(name.clone(), self.signatures.get(&name));
+                self.state = AppState::Signature(dialog, name);
+            }
+            if let Some((from, to)) = &response.renamed {
+                self.rename_signature(from, to);
+            }
+            if let Some(target) = response.retype {
+                let dialog = TypeSelectionDialog::new(self.typedefs.clone(), &self.recent_types);
+                let (tab, entry) = target;
+                let target = TypeTarget::Entry { tab, entry };
+                self.state = AppState::TypeSelection(dialog, Some(target));
+            }
+        });
+        egui::SidePanel::right("info").show(ctx, |ui| {
             ui.heading("General Data");
             ui.label(format!("App state: {:?}", self.state));
-            ui.label(format!(
-                "Selected process: {}",
-                self.selected_process
-                    .as_ref()
-                    .and_then(|p| self.system.process(p.pid()))
-                    .and_then(|p| p.name().to_str())
-                    .unwrap_or("None")
-            ));
+            match &self.dump {
+                Some(path) => ui.label(format!("Dump: {}", path.display())),
+                None => ui.label(format!(
+                    "Selected process: {}",
+                    self.reader
+                        .as_ref()
+                        .map_or("None".into(), |r| r.pid().to_string())
+                )),
+            };
+            if let Some(launched) = self.launched.as_mut().filter(|l| l.is_suspended()) {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Process {} is suspended", launched.pid()));
+                    if ui.button("Resume").clicked() {
+                        if let Err(err) = launched.resume() {
+                            eprintln!("ERROR: Could not resume the process: {err}");
+                        }
+                    }
+                });
+            }
+            if self.symbol_loader.is_some() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Loading module symbols");
+                });
+            }
+            for (name, _) in &self.signature_resolves {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Resolving the signature of {name}"));
+                });
+            }
+            for (path, _) in &self.debug_imports {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Loading debug types")
+                        .on_hover_text(path.display().to_string());
+                });
+            }
+            if let Some(target) = &self.target {
+                ui.label(format!("Target: {}", target.name)).on_hover_text(
+                    target
+                        .path
+                        .as_ref()
+                        .map_or(String::new(), |p| p.display().to_string()),
+                );
+                let reattach = ui
+                    .checkbox(&mut self.settings.auto_reattach, "Reattach on restart")
+                    .on_hover_text("Attach again when the target exits and restarts");
+                if reattach.changed() {
+                    if let Err(err_s) = self.settings.save() {
+                        eprintln!("ERROR: Could not save the settings: {err_s}");
+                    }
+                }
+            }
+            if let Some(reader) = &mut self.reader {
+                let mut refresh_ms = reader.get_refresh_rate().as_millis() as u64;
+                ui.horizontal(|ui| {
+                    ui.label("Refresh rate (ms):");
+                    let drag = egui::DragValue::new(&mut refresh_ms).range(10..=5000);
+                    if ui.add(drag).changed() {
+                        reader.set_refresh_rate(std::time::Duration::from_millis(refresh_ms));
+                    }
+                });
+                let mut freeze_ms = reader.get_freeze_interval().as_millis() as u64;
+                ui.horizontal(|ui| {
+                    ui.label("Freeze interval (ms):");
+                    let drag = egui::DragValue::new(&mut freeze_ms).range(1..=5000);
+                    if ui.add(drag).changed() {
+                        reader.set_freeze_interval(std::time::Duration::from_millis(freeze_ms));
+                    }
+                });
+                if let Some(err) = reader.last_error() {
+                    let text = match reader.is_stopped() {
+                        true => format!("Detached: {err}"),
+                        false => format!("Last error: {err}"),
+                    };
+                    ui.colored_label(ui.visuals().error_fg_color, text);
+                }
+            }
             ui.add_space(10.0);
 
             ui.heading("File saving");
-            ui.label(format!("Dirty? {}", self.is_dirty));
+            ui.label(format!("Dirty? {}", self.edits.is_dirty()));
             ui.label(format!("File location: {:?}", self.save_file_location));
             ui.add_space(10.0);
 
             ui.heading("Type Selection Dialog");
             ui.label(format!("Selected type : {:?}", self.selected_type));
+            ui.add_space(10.0);
+
+            ui.heading("Address lookup");
+            ui.text_edit_singleline(&mut self.address_query);
+            let query = self.address_query.trim();
+            let (lookup, address) = match u64::from_str_radix(query.trim_start_matches("0x"), 16) {
+                Ok(addr) => (self.symbolizer.symbolize(addr), Some(addr)),
+                Err(_) => {
+                    let addr = self.symbolizer.resolve(query);
+                    (addr.map(|a| format!("{a:#X}")), addr)
+                }
+            };
+            ui.label(lookup.unwrap_or_else(|| "-".into()));
+            if let (Some(reader), Some(address)) = (&mut self.reader, address) {
+                const LOOKUP_LEN: usize = 16;
+                let view = match self.lookup_view {
+                    Some(view) => {
+                        reader.set_range(view, address, LOOKUP_LEN);
+                        view
+                    }
+                    None => *self.lookup_view.insert(reader.watch(address, LOOKUP_LEN)),
+                };
+                let bytes = match reader.get(view) {
+                    Some(data) => (0..LOOKUP_LEN as u64)
+                        .map(|i| match data.read.get(address + i, 1) {
+                            Some(b) => format!("{:02X}", b[0]),
+                            None => "??".into(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    None => "reading...".into(),
+                };
+                ui.monospace(bytes);
+                if reader.is_stale(view) {
+                    let staleness = reader.staleness(view).map_or("never read".into(), |s| {
+                        format!("{:.1}s old", s.as_secs_f32())
+                    });
+                    ui.colored_label(ui.visuals().warn_fg_color, format!("Stale: {staleness}"));
+                }
+            }
         });
     }
 }
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use sysinfo::Pid;
+
+use super::{pages_of, MemoryRegion, Module, ProcessError, RangeRead, SystemProcess, PAGE_SIZE};
+use crate::typing::DataType;
+
+// One frame at 60 Hz
+const DEFAULT_TTL: Duration = Duration::from_millis(16);
+// Expired pages are dropped past this many cached pages (16 MiB)
+const MAX_PAGES: usize = 0x1000;
+
+#[derive(Debug, Clone)]
+struct CachedPage {
+    // None when the page is not entirely readable
+    data: Option<Vec<u8>>,
+    fetched: Instant,
+}
+
+// A range over pages which are not entirely readable, read on its own
+#[derive(Debug, Clone)]
+struct CachedRange {
+    read: RangeRead,
+    fetched: Instant,
+}
+
+/// Wraps a process to cache its memory by pages for a short time, so that the panels
+/// reading the same memory within a frame only cost one read. Writes go through to the process.
+#[derive(Debug)]
+pub struct CachedProcess<P: SystemProcess> {
+    inner: P,
+    ttl: Duration,
+    pages: HashMap<u64, CachedPage>,
+    // By (address, len), unreadable ranges are not read again before they expire either
+    ranges: HashMap<(u64, usize), CachedRange>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<P: SystemProcess> CachedProcess<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            ttl: DEFAULT_TTL,
+            pages: HashMap::new(),
+            ranges: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.set_ttl(ttl);
+        self
+    }
+
+    pub fn get_inner(&self) -> &P {
+        &self.inner
+    }
+    /// The wrapped process, writes made through it are not seen by the cache until invalidated.
+    pub fn get_inner_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    /// Number of pages read from the cache and from the process.
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits, self.misses)
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.pages.clear();
+        self.ranges.clear();
+    }
+
+    /// Forgets the pages touched by a range.
+    pub fn invalidate(&mut self, address: u64, len: usize) {
+        for page in page_addresses(address, len) {
+            self.pages.remove(&page);
+        }
+        self.invalidate_ranges(address, len);
+    }
+
+    // Forgets the ranges read on their own which overlap a range
+    fn invalidate_ranges(&mut self, address: u64, len: usize) {
+        let end = address + len as u64;
+        self.ranges
+            .retain(|_, cached| cached.read.end() <= address || end <= cached.read.address());
+    }
+
+    // Caches the missing and expired pages of `ranges`, with a single read of the process
+    fn fetch(&mut self, ranges: &[(u64, usize)]) -> Result<(), ProcessError> {
+        let now = Instant::now();
+        let mut missing: Vec<u64> = Vec::new();
+        for &(address, len) in ranges {
+            for page in page_addresses(address, len) {
+                match self.pages.get(&page) {
+                    Some(cached) if now.duration_since(cached.fetched) < self.ttl => {
+                        self.hits += 1;
+                    }
+                    _ => missing.push(page),
+                }
+            }
+        }
+        if missing.is_empty() {
+            return Ok(());
+        }
+        missing.sort_unstable();
+        missing.dedup();
+        self.misses += missing.len();
+
+        // Adjacent pages are read as one range
+        let mut merged: Vec<(u64, usize)> = Vec::new();
+        for page in missing {
+            match merged.last_mut() {
+                Some((start, len)) if *start + *len as u64 == page => *len += PAGE_SIZE as usize,
+                _ => merged.push((page, PAGE_SIZE as usize)),
+            }
+        }
+        if self.pages.len() + self.ranges.len() > MAX_PAGES {
+            let ttl = self.ttl;
+            self.pages
+                .retain(|_, cached| now.duration_since(cached.fetched) < ttl);
+            self.ranges
+                .retain(|_, cached| now.duration_since(cached.fetched) < ttl);
+        }
+        for read in self.inner.read_scatter(&merged)? {
+            for (i, (page, _)) in pages_of(read.address(), read.data().len()).enumerate() {
+                let offset = (page - read.address()) as usize;
+                let data = read.pages()[i]
+                    .then(|| read.data()[offset..offset + PAGE_SIZE as usize].to_vec());
+                self.pages.insert(page, CachedPage { data, fetched: now });
+            }
+        }
+        Ok(())
+    }
+
+    // Bytes of a range from the cache, if all its pages are cached and readable
+    fn cached(&self, address: u64, len: usize) -> Option<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(len);
+        for (chunk, chunk_len) in pages_of(address, len) {
+            let page = chunk / PAGE_SIZE * PAGE_SIZE;
+            let data = self.pages.get(&page)?.data.as_ref()?;
+            let offset = (chunk - page) as usize;
+            bytes.extend_from_slice(&data[offset..offset + chunk_len]);
+        }
+        Some(bytes)
+    }
+
+    // Reads a range whose pages are not all readable, e.g. within the small regions of dumps
+    fn read_uncached(&mut self, address: u64, len: usize) -> Result<RangeRead, ProcessError> {
+        let now = Instant::now();
+        match self.ranges.get(&(address, len)) {
+            Some(cached) if now.duration_since(cached.fetched) < self.ttl => {
+                Ok(cached.read.clone())
+            }
+            _ => {
+                let read = self.inner.read_range(address, len)?;
+                let cached = CachedRange {
+                    read: read.clone(),
+                    fetched: now,
+                };
+                self.ranges.insert((address, len), cached);
+                Ok(read)
+            }
+        }
+    }
+}
+
+// Error of a read which stopped at its first unreadable page
+fn read_error(read: &RangeRead) -> ProcessError {
+    let readable = read
+        .valid_runs()
+        .first()
+        .filter(|(address, _)| *address == read.address())
+        .map_or(0, |(_, bytes)| bytes.len());
+    match readable {
+        0 => ProcessError::Unmapped(read.address()),
+        _ => ProcessError::PartialRead {
+            address: read.address(),
+            read: readable,
+            requested: read.data().len(),
+        },
+    }
+}
+
+// Start of the pages touched by a range
+fn page_addresses(address: u64, len: usize) -> impl Iterator<Item = u64> {
+    pages_of(address, len).map(|(chunk, _)| chunk / PAGE_SIZE * PAGE_SIZE)
+}
+
+impl<P: SystemProcess> SystemProcess for CachedProcess<P> {
+    fn pid(&self) -> Pid {
+        self.inner.pid()
+    }
+
+    fn open(&mut self) -> Result<(), ProcessError> {
+        self.inner.open()
+    }
+
+    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, ProcessError> {
+        let size = dt.get_size();
+        self.fetch(&[(location, size)])?;
+        if let Some(bytes) = self.cached(location, size) {
+            return Ok(bytes);
+        }
+        let read = self.read_uncached(location, size)?;
+        match read.get(location, size) {
+            Some(bytes) => Ok(bytes.to_vec()),
+            None => Err(read_error(&read)),
+        }
+    }
+
+    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), ProcessError> {
+        let result = self.inner.write_memory(location, what.clone());
+        self.invalidate_ranges(location, what.len());
+        if result.is_err() {
+            self.invalidate(location, what.len());
+            return result;
+        }
+        let mut written = 0;
+        for (chunk, chunk_len) in pages_of(location, what.len()) {
+            let page = chunk / PAGE_SIZE * PAGE_SIZE;
+            if let Some(data) = self.pages.get_mut(&page).and_then(|c| c.data.as_mut()) {
+                let offset = (chunk - page) as usize;
+                data[offset..offset + chunk_len]
+                    .copy_from_slice(&what[written..written + chunk_len]);
+            }
+            written += chunk_len;
+        }
+        Ok(())
+    }
+
+    fn modules(&self) -> Result<Vec<Module>, ProcessError> {
+        self.inner.modules()
+    }
+
+    fn regions(&self) -> Result<Vec<MemoryRegion>, ProcessError> {
+        self.inner.regions()
+    }
+
+    fn close(&mut self) {
+        self.invalidate_all();
+        self.inner.close();
+    }
+
+    fn read_scatter(&mut self, ranges: &[(u64, usize)]) -> Result<Vec<RangeRead>, ProcessError> {
+        self.fetch(ranges)?;
+        let mut reads = Vec::with_capacity(ranges.len());
+        for &(address, len) in ranges {
+            let mut read = RangeRead::new(address, len);
+            for (chunk, chunk_len) in pages_of(address, len) {
+                let page = chunk / PAGE_SIZE * PAGE_SIZE;
+                if let Some(data) = self.pages.get(&page).and_then(|c| c.data.as_ref()) {
+                    let offset = (chunk - page) as usize;
+                    read.fill(chunk, &data[offset..offset + chunk_len]);
+                }
+            }
+            if !read.is_complete() {
+                // Partly readable pages are read again, on their own
+                read = self.read_uncached(address, len)?;
+            }
+            reads.push(read);
+        }
+        Ok(reads)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::MockProcess;
+    use crate::typing::IntegerDataType;
+
+    #[test]
+    fn test_cache() {
+        let u32_dt = IntegerDataType::default();
+        let mock = MockProcess::new(1)
+            .with_region(0x1000, vec![1; 0x2000], true, false)
+            .with_region(0x3000, vec![2; 0x10], true, false);
+        let mut process = CachedProcess::new(mock).with_ttl(Duration::from_secs(60));
+        process.open().unwrap();
+
+        assert_eq!(process.read_memory(0x1FFE, &u32_dt).unwrap(), vec![1; 4]);
+        assert_eq!(process.read_memory(0x1000, &u32_dt).unwrap(), vec![1; 4]);
+        let reads = process.read_scatter(&[(0x1100, 8), (0x2100, 8)]).unwrap();
+        assert!(reads.iter().all(RangeRead::is_complete));
+        assert_eq!(process.get_inner().read_count(), 1);
+        assert_eq!(process.stats(), (3, 2));
+
+        // Write-through
+        process.write_memory(0x1FFF, vec![7, 7]).unwrap();
+        assert_eq!(process.get_inner().memory(0x1FFF, 2).unwrap(), &[7, 7]);
+        assert_eq!(
+            process.read_memory(0x1FFE, &u32_dt).unwrap(),
+            vec![1, 7, 7, 1]
+        );
+        assert_eq!(process.get_inner().read_count(), 1);
+
+        // Changes made behind the cache are seen once invalidated
+        process
+            .get_inner_mut()
+            .write_memory(0x1000, vec![9])
+            .unwrap();
+        assert_eq!(process.read_memory(0x1000, &u32_dt).unwrap(), vec![1; 4]);
+        process.invalidate(0x1000, 1);
+        assert_eq!(
+            process.read_memory(0x1000, &u32_dt).unwrap(),
+            vec![9, 1, 1, 1]
+        );
+
+        // Partial pages are read on their own, unreadable ones are not read again
+        assert_eq!(process.read_memory(0x3000, &u32_dt).unwrap(), vec![2; 4]);
+        assert!(process.read_memory(0x300E, &u32_dt).is_err());
+        assert_eq!(
+            process.read_memory(0x5000, &u32_dt),
+            Err(ProcessError::Unmapped(0x5000))
+        );
+        let count = process.get_inner().read_count();
+        assert!(process.read_memory(0x300E, &u32_dt).is_err());
+        assert!(process.read_memory(0x5000, &u32_dt).is_err());
+        let reads = process.read_scatter(&[(0x5000, 4)]).unwrap();
+        assert!(!reads[0].is_complete());
+        assert_eq!(process.get_inner().read_count(), count);
+
+        process.set_ttl(Duration::ZERO);
+        let count = process.get_inner().read_count();
+        process.read_memory(0x1000, &u32_dt).unwrap();
+        assert!(process.get_inner().read_count() > count);
+    }
+}
@@ -0,0 +1,587 @@
+use std::path::{Path, PathBuf};
+use sysinfo::Pid;
+
+use object::read::elf::{ElfFile, FileHeader, ProgramHeader};
+use object::{elf, Endian, Endianness as ElfEndianness};
+
+use super::{MemoryRegion, Module, ProcessError};
+use crate::typing::DataType;
+
+#[derive(Debug, Default, PartialEq)]
+enum State {
+    #[default]
+    Created,
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    region: MemoryRegion,
+    data: Vec<u8>,
+}
+
+/// Memory captured from a process: a raw memory dump, an ELF core dump or a Windows minidump.
+/// Writes only change the loaded copy.
+#[derive(Debug)]
+pub struct DumpProcess {
+    pid: Pid,
+    state: State,
+    // Sorted by address
+    segments: Vec<Segment>,
+    modules: Vec<Module>,
+}
+
+const MINIDUMP_SIGNATURE: &[u8] = b"MDMP";
+
+impl DumpProcess {
+    fn new(pid: u32, mut segments: Vec<Segment>, modules: Vec<Module>) -> Self {
+        segments.sort_by_key(|s| s.region.start);
+        Self {
+            pid: Pid::from_u32(pid),
+            state: State::Created,
+            segments,
+            modules,
+        }
+    }
+
+    /// Loads a dump, guessing its format from its header. Files which are neither an ELF
+    /// core dump nor a minidump are loaded as raw memory at `raw_base`.
+    pub fn from_file(path: &Path, raw_base: u64) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|err| err.to_string())?;
+        if data.starts_with(MINIDUMP_SIGNATURE) {
+            Self::from_minidump(&data)
+        } else if data.starts_with(&elf::ELFMAG) {
+            Self::from_core_dump(&data)
+        } else {
+            Ok(Self::from_raw(data, raw_base))
+        }
+    }
+
+    /// Raw memory starting at `base`, e.g. a region saved by a debugger.
+    pub fn from_raw(data: Vec<u8>, base: u64) -> Self {
+        let region = MemoryRegion {
+            start: base,
+            size: data.len() as u64,
+            readable: true,
+            writable: true,
+            executable: false,
+            path: None,
+        };
+        Self::new(0, vec![Segment { region, data }], Vec::new())
+    }
+
+    /// An ELF core dump, its PT_LOAD segments are the memory regions.
+    pub fn from_core_dump(data: &[u8]) -> Result<Self, String> {
+        // e_ident[EI_CLASS]
+        match data.get(4) {
+            Some(&elf::ELFCLASS64) => parse_core::<elf::FileHeader64<ElfEndianness>>(data),
+            Some(&elf::ELFCLASS32) => parse_core::<elf::FileHeader32<ElfEndianness>>(data),
+            _ => Err("Invalid ELF class.".into()),
+        }
+    }
+
+    /// A Windows minidump, parsed on any OS.
+    pub fn from_minidump(data: &[u8]) -> Result<Self, String> {
+        parse_minidump(data)
+    }
+
+    fn segment_at(&self, address: u64) -> Option<usize> {
+        let idx = self
+            .segments
+            .partition_point(|s| s.region.start <= address)
+            .checked_sub(1)?;
+        (address < self.segments[idx].region.end()).then_some(idx)
+    }
+
+    fn check_open(&self) -> Result<(), ProcessError> {
+        match self.state {
+            State::Open => Ok(()),
+            _ => Err(ProcessError::NotOpened),
+        }
+    }
+}
+
+impl super::SystemProcess for DumpProcess {
+    fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    fn open(&mut self) -> Result<(), ProcessError> {
+        match self.state {
+            State::Created => {
+                self.state = State::Open;
+                Ok(())
+            }
+            _ => Err(ProcessError::AlreadyOpened),
+        }
+    }
+
+    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, ProcessError> {
+        self.check_open()?;
+        let size = dt.get_size();
+        let mut read_buffer = Vec::with_capacity(size);
+        let mut address = location;
+        // A read can span adjacent segments
+        while read_buffer.len() < size {
+            let Some(idx) = self.segment_at(address) else {
+                return Err(match read_buffer.len() {
+                    0 => ProcessError::Unmapped(address),
+                    read => ProcessError::PartialRead {
+                        address: location,
+                        read,
+                        requested: size,
+                    },
+                });
+            };
+            let segment = &self.segments[idx];
+            let offset = (address - segment.region.start) as usize;
+            let len = (size - read_buffer.len()).min(segment.data.len() - offset);
+            read_buffer.extend_from_slice(&segment.data[offset..offset + len]);
+            address += len as u64;
+        }
+        Ok(read_buffer)
+    }
+
+    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), ProcessError> {
+        self.check_open()?;
+        let mut written = 0;
+        while written < what.len() {
+            let address = location + written as u64;
+            let idx = self
+                .segment_at(address)
+                .ok_or(ProcessError::Unmapped(address))?;
+            let segment = &mut self.segments[idx];
+            let offset = (address - segment.region.start) as usize;
+            let len = (what.len() - written).min(segment.data.len() - offset);
+            segment.data[offset..offset + len].copy_from_slice(&what[written..written + len]);
+            written += len;
+        }
+        Ok(())
+    }
+
+    fn modules(&self) -> Result<Vec<Module>, ProcessError> {
+        Ok(self.modules.clone())
+    }
+
+    fn regions(&self) -> Result<Vec<MemoryRegion>, ProcessError> {
+        Ok(self.segments.iter().map(|s| s.region.clone()).collect())
+    }
+
+    fn close(&mut self) {
+        self.state = State::Closed;
+    }
+}
+
+fn module_name(path: &str) -> String {
+    // Minidump paths use backslashes whatever the OS parsing them
+    path.rsplit(['/', '\\']).next().unwrap_or(path).into()
+}
+
+// Merges file mappings by path, as the modules of a live process
+fn merge_modules(mappings: impl IntoIterator<Item = (String, u64, u64)>) -> Vec<Module> {
+    let mut modules: Vec<Module> = Vec::new();
+    for (path, start, end) in mappings {
+        match modules
+            .iter_mut()
+            .find(|m| m.path.as_os_str() == path.as_str())
+        {
+            Some(m) => {
+                let module_end = (m.base + m.size).max(end);
+                m.base = m.base.min(start);
+                m.size = module_end - m.base;
+            }
+            None => modules.push(Module {
+                name: module_name(&path),
+                path: PathBuf::from(path),
+                base: start,
+                size: end - start,
+            }),
+        }
+    }
+    modules
+}
+
+fn parse_core<Elf: FileHeader<Endian = ElfEndianness>>(data: &[u8]) -> Result<DumpProcess, String> {
+    let file = ElfFile::<Elf>::parse(data).map_err(|err| err.to_string())?;
+    if file.elf_header().e_type(file.endian()) != elf::ET_CORE {
+        return Err("This ELF file is not a core dump.".into());
+    }
+    let endian = file.endian();
+    let is_64 = file.elf_header().is_class_64();
+    let word = |bytes: &[u8], idx: usize| -> Option<u64> {
+        if is_64 {
+            let b = bytes.get(8 * idx..8 * idx + 8)?;
+            Some(endian.read_u64_bytes(b.try_into().ok()?))
+        } else {
+            let b = bytes.get(4 * idx..4 * idx + 4)?;
+            Some(u64::from(endian.read_u32_bytes(b.try_into().ok()?)))
+        }
+    };
+
+    let mut pid = 0;
+    let mut mappings = Vec::new();
+    let mut segments = Vec::new();
+    for header in file.elf_program_headers() {
+        match header.p_type(endian) {
+            elf::PT_LOAD => {
+                let flags = header.p_flags(endian);
+                let file_data = header
+                    .data(endian, data)
+                    .map_err(|()| "Invalid segment in the core dump.".to_string())?;
+                // Segments which were not dumped (e.g. unmodified file mappings) are not readable
+                if file_data.is_empty() {
+                    continue;
+                }
+                let mut segment_data = file_data.to_vec();
+                segment_data.resize(header.p_memsz(endian).into() as usize, 0);
+                segments.push(Segment {
+                    region: MemoryRegion {
+                        start: header.p_vaddr(endian).into(),
+                        size: segment_data.len() as u64,
+                        readable: flags & elf::PF_R != 0,
+                        writable: flags & elf::PF_W != 0,
+                        executable: flags & elf::PF_X != 0,
+                        path: None,
+                    },
+                    data: segment_data,
+                });
+            }
+            elf::PT_NOTE => {
+                let Ok(Some(mut notes)) = header.notes(endian, data) else {
+                    continue;
+                };
+                while let Ok(Some(note)) = notes.next() {
+                    if note.name() != elf::ELF_NOTE_CORE && note.name() != b"LINUX" {
+                        continue;
+                    }
+                    let desc = note.desc();
+                    match note.n_type(endian) {
+                        elf::NT_PRSTATUS if pid == 0 => {
+                            // pr_pid follows the signal info and the pending and held signal sets
+                            let offset = if is_64 { 32 } else { 24 };
+                            if let Some(b) = desc.get(offset..offset + 4) {
+                                pid = endian.read_u32_bytes(b.try_into().expect("4 bytes"));
+                            }
+                        }
+                        elf::NT_FILE => {
+                            // count, page size, (start, end, offset) * count, then the paths
+                            let count = word(desc, 0).unwrap_or(0) as usize;
+                            let word_size = if is_64 { 8 } else { 4 };
+                            let names_start = word_size * (2 + 3 * count);
+                            let names = desc
+                                .get(names_start..)
+                                .unwrap_or_default()
+                                .split(|&b| b == 0);
+                            for (i, name) in names.take(count).enumerate() {
+                                let (Some(start), Some(end)) =
+                                    (word(desc, 2 + 3 * i), word(desc, 3 + 3 * i))
+                                else {
+                                    break;
+                                };
+                                let path = String::from_utf8_lossy(name).into_owned();
+                                mappings.push((path, start, end));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for segment in &mut segments {
+        let region = &mut segment.region;
+        region.path = mappings
+            .iter()
+            .find(|(_, start, end)| *start <= region.start && region.start < *end)
+            .map(|(path, _, _)| PathBuf::from(path));
+    }
+    Ok(DumpProcess::new(pid, segments, merge_modules(mappings)))
+}
+
+const MODULE_LIST_STREAM: u32 = 4;
+const MEMORY_LIST_STREAM: u32 = 5;
+const MEMORY64_LIST_STREAM: u32 = 9;
+const MISC_INFO_STREAM: u32 = 15;
+const MEMORY_INFO_LIST_STREAM: u32 = 16;
+
+// Memory protections of Windows
+const PAGE_NOACCESS: u32 = 0x01;
+const PAGE_READWRITE: u32 = 0x04;
+const PAGE_WRITECOPY: u32 = 0x08;
+const PAGE_EXECUTE: u32 = 0x10;
+const PAGE_EXECUTE_READ: u32 = 0x20;
+const PAGE_EXECUTE_READWRITE: u32 = 0x40;
+const PAGE_EXECUTE_WRITECOPY: u32 = 0x80;
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes(&self, offset: u64, len: u64) -> Result<&[u8], String> {
+        usize::try_from(offset)
+            .ok()
+            .zip(usize::try_from(len).ok())
+            .and_then(|(o, l)| self.0.get(o..o.checked_add(l)?))
+            .ok_or_else(|| "The minidump is truncated.".to_string())
+    }
+    fn u16(&self, offset: u64) -> Result<u16, String> {
+        let b = self.bytes(offset, 2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+    fn u32(&self, offset: u64) -> Result<u32, String> {
+        let b = self.bytes(offset, 4)?;
+        Ok(u32::from_le_bytes(b.try_into().expect("4 bytes")))
+    }
+    fn u64(&self, offset: u64) -> Result<u64, String> {
+        let b = self.bytes(offset, 8)?;
+        Ok(u64::from_le_bytes(b.try_into().expect("8 bytes")))
+    }
+    // MINIDUMP_STRING: length in bytes, then UTF-16
+    fn string(&self, offset: u64) -> Result<String, String> {
+        let len = self.u32(offset)?;
+        let units: Vec<u16> = (0..u64::from(len / 2))
+            .map(|i| self.u16(offset + 4 + 2 * i))
+            .collect::<Result<_, _>>()?;
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
+
+fn parse_minidump(data: &[u8]) -> Result<DumpProcess, String> {
+    let r = Reader(data);
+    if !data.starts_with(MINIDUMP_SIGNATURE) {
+        return Err("Invalid minidump signature.".into());
+    }
+    let stream_count = r.u32(8)?;
+    let directory = u64::from(r.u32(12)?);
+
+    let mut pid = 0;
+    let mut ranges: Vec<(u64, u64, u64)> = Vec::new(); // (address, size, file offset)
+    let mut modules = Vec::new();
+    // (base, size, protect)
+    let mut infos: Vec<(u64, u64, u32)> = Vec::new();
+    for i in 0..u64::from(stream_count) {
+        let entry = directory + 12 * i;
+        let stream_type = r.u32(entry)?;
+        let rva = u64::from(r.u32(entry + 8)?);
+        match stream_type {
+            MEMORY64_LIST_STREAM => {
+                let count = r.u64(rva)?;
+                let mut file_offset = r.u64(rva + 8)?;
+                for j in 0..count {
+                    let descriptor = rva + 16 + 16 * j;
+                    let (start, size) = (r.u64(descriptor)?, r.u64(descriptor + 8)?);
+                    ranges.push((start, size, file_offset));
+                    file_offset += size;
+                }
+            }
+            MEMORY_LIST_STREAM => {
+                let count = r.u32(rva)?;
+                for j in 0..u64::from(count) {
+                    let descriptor = rva + 4 + 16 * j;
+                    let start = r.u64(descriptor)?;
+                    let size = u64::from(r.u32(descriptor + 8)?);
+                    let file_offset = u64::from(r.u32(descriptor + 12)?);
+                    ranges.push((start, size, file_offset));
+                }
+            }
+            MODULE_LIST_STREAM => {
+                let count = r.u32(rva)?;
+                for j in 0..u64::from(count) {
+                    // MINIDUMP_MODULE is 108 bytes
+                    let module = rva + 4 + 108 * j;
+                    let base = r.u64(module)?;
+                    let size = u64::from(r.u32(module + 8)?);
+                    let path = r.string(u64::from(r.u32(module + 20)?))?;
+                    modules.push(Module {
+                        name: module_name(&path),
+                        path: PathBuf::from(path),
+                        base,
+                        size,
+                    });
+                }
+            }
+            // ProcessId is valid with MINIDUMP_MISC1_PROCESS_ID
+            MISC_INFO_STREAM if r.u32(rva + 4)? & 1 != 0 => pid = r.u32(rva + 8)?,
+            MEMORY_INFO_LIST_STREAM => {
+                let header_size = u64::from(r.u32(rva)?);
+                let entry_size = u64::from(r.u32(rva + 4)?);
+                let count = r.u64(rva + 8)?;
+                for j in 0..count {
+                    let info = rva + header_size + entry_size * j;
+                    infos.push((r.u64(info)?, r.u64(info + 24)?, r.u32(info + 36)?));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut segments = Vec::with_capacity(ranges.len());
+    for (start, size, file_offset) in ranges {
+        // Dumps without memory information are assumed read-write
+        let protect = infos
+            .iter()
+            .find(|(base, len, _)| *base <= start && start < base + len)
+            .map_or(PAGE_READWRITE, |(_, _, protect)| *protect);
+        let writable = PAGE_READWRITE | PAGE_WRITECOPY;
+        let executable_writable = PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
+        let executable = PAGE_EXECUTE | PAGE_EXECUTE_READ | executable_writable;
+        segments.push(Segment {
+            region: MemoryRegion {
+                start,
+                size,
+                readable: protect & PAGE_NOACCESS == 0 && protect != PAGE_EXECUTE,
+                writable: protect & (writable | executable_writable) != 0,
+                executable: protect & executable != 0,
+                path: modules
+                    .iter()
+                    .find(|m: &&Module| m.contains(start))
+                    .map(|m| m.path.clone()),
+            },
+            data: r.bytes(file_offset, size)?.to_vec(),
+        });
+    }
+    Ok(DumpProcess::new(pid, segments, modules))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::SystemProcess;
+    use crate::typing::{IntSize, IntegerDataType};
+
+    fn u32_dt() -> IntegerDataType {
+        IntegerDataType::default()
+    }
+
+    #[test]
+    fn test_raw_dump() {
+        let mut dump = DumpProcess::from_raw((0u8..16).collect(), 0x1000);
+        assert!(dump.read_memory(0x1000, &u32_dt()).is_err());
+        dump.open().unwrap();
+        assert_eq!(
+            dump.read_memory(0x1004, &u32_dt()).unwrap(),
+            vec![4, 5, 6, 7]
+        );
+        assert!(dump.read_memory(0x100E, &u32_dt()).is_err());
+        dump.write_memory(0x100E, vec![0xAA, 0xBB]).unwrap();
+        let byte = IntegerDataType::default().with_size(IntSize::Integer8);
+        assert_eq!(dump.read_memory(0x100F, &byte).unwrap(), vec![0xBB]);
+    }
+
+    #[test]
+    fn test_core_dump() {
+        let mut core = Vec::new();
+        // ELF header, 2 program headers
+        core.extend_from_slice(&[0x7F, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        core.extend_from_slice(&elf::ET_CORE.to_le_bytes());
+        core.extend_from_slice(&elf::EM_X86_64.to_le_bytes());
+        core.extend_from_slice(&1u32.to_le_bytes());
+        core.extend_from_slice(&0u64.to_le_bytes()); // entry
+        core.extend_from_slice(&64u64.to_le_bytes()); // phoff
+        core.extend_from_slice(&0u64.to_le_bytes()); // shoff
+        core.extend_from_slice(&0u32.to_le_bytes()); // flags
+        for half in [64u16, 56, 2, 64, 0, 0] {
+            core.extend_from_slice(&half.to_le_bytes());
+        }
+
+        let mut notes = Vec::new();
+        let mut push_note = |n_type: u32, desc: &[u8]| {
+            notes.extend_from_slice(&5u32.to_le_bytes());
+            notes.extend_from_slice(&(desc.len() as u32).to_le_bytes());
+            notes.extend_from_slice(&n_type.to_le_bytes());
+            notes.extend_from_slice(b"CORE\0\0\0\0");
+            notes.extend_from_slice(desc);
+            notes.resize(notes.len().next_multiple_of(4), 0);
+        };
+        let mut prstatus = vec![0u8; 40];
+        prstatus[32..36].copy_from_slice(&1234u32.to_le_bytes());
+        push_note(elf::NT_PRSTATUS, &prstatus);
+        let mut file = Vec::new();
+        for w in [1u64, 0x1000, 0x40_0000, 0x40_1000, 0] {
+            file.extend_from_slice(&w.to_le_bytes());
+        }
+        file.extend_from_slice(b"/usr/bin/game\0");
+        push_note(elf::NT_FILE, &file);
+
+        let notes_offset = 64 + 2 * 56;
+        let load_offset = notes_offset + notes.len();
+        let mut phdr = |p_type: u32, flags: u32, offset: usize, vaddr: u64, size: usize| {
+            core.extend_from_slice(&p_type.to_le_bytes());
+            core.extend_from_slice(&flags.to_le_bytes());
+            for w in [offset as u64, vaddr, 0, size as u64, size as u64, 4] {
+                core.extend_from_slice(&w.to_le_bytes());
+            }
+        };
+        phdr(elf::PT_NOTE, 0, notes_offset, 0, notes.len());
+        phdr(
+            elf::PT_LOAD,
+            elf::PF_R | elf::PF_W,
+            load_offset,
+            0x40_0000,
+            8,
+        );
+        core.extend_from_slice(&notes);
+        core.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut dump = DumpProcess::from_core_dump(&core).unwrap();
+        dump.open().unwrap();
+        assert_eq!(dump.pid(), Pid::from_u32(1234));
+        assert_eq!(
+            dump.read_memory(0x40_0004, &u32_dt()).unwrap(),
+            vec![5, 6, 7, 8]
+        );
+        let modules = dump.modules().unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "game");
+        let regions = dump.regions().unwrap();
+        assert!(regions[0].writable && !regions[0].executable);
+        assert_eq!(regions[0].path, Some(PathBuf::from("/usr/bin/game")));
+    }
+
+    #[test]
+    fn test_minidump() {
+        let mut md = Vec::new();
+        md.extend_from_slice(b"MDMP");
+        md.extend_from_slice(&0xA793u32.to_le_bytes());
+        md.extend_from_slice(&2u32.to_le_bytes()); // streams
+        md.extend_from_slice(&32u32.to_le_bytes()); // directory
+        md.resize(32, 0);
+        // Directory: memory64 list at 56, module list at 88
+        for (stream, size, rva) in [
+            (MEMORY64_LIST_STREAM, 32, 56),
+            (MODULE_LIST_STREAM, 112, 88),
+        ] {
+            for v in [stream, size, rva] {
+                md.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        // Memory64 list: 1 range whose data is at 200
+        for v in [1u64, 200, 0x7FF0_0000, 4] {
+            md.extend_from_slice(&v.to_le_bytes());
+        }
+        // Module list: 1 module whose name is at 204
+        md.extend_from_slice(&1u32.to_le_bytes());
+        let mut module = vec![0u8; 108];
+        module[..8].copy_from_slice(&0x7FF0_0000u64.to_le_bytes());
+        module[8..12].copy_from_slice(&0x1000u32.to_le_bytes());
+        module[20..24].copy_from_slice(&204u32.to_le_bytes());
+        md.extend_from_slice(&module);
+        md.resize(200, 0);
+        md.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+        let name: Vec<u16> = "C:\\Game\\game.exe".encode_utf16().collect();
+        md.extend_from_slice(&(2 * name.len() as u32).to_le_bytes());
+        md.extend(name.iter().flat_map(|u| u.to_le_bytes()));
+
+        let mut dump = DumpProcess::from_minidump(&md).unwrap();
+        dump.open().unwrap();
+        assert_eq!(
+            dump.read_memory(0x7FF0_0000, &u32_dt()).unwrap(),
+            vec![0xDE, 0xAD, 0xBE, 0xEF]
+        );
+        let modules = dump.modules().unwrap();
+        assert_eq!(modules[0].name, "game.exe");
+        assert_eq!(modules[0].size, 0x1000);
+    }
+}
@@ -0,0 +1,237 @@
+use std::path::PathBuf;
+use std::process::{Child, Command};
+use sysinfo::Pid;
+
+use super::ProcessError;
+
+/// How to start a target process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    pub path: PathBuf,
+    pub args: Vec<String>,
+    // Working directory of RsClass when None
+    pub working_dir: Option<PathBuf>,
+    // Added to the environment of RsClass
+    pub env: Vec<(String, String)>,
+    // Stopped before running any code of the executable, until resumed
+    pub suspended: bool,
+}
+
+/// A process started by RsClass, its child.
+#[derive(Debug)]
+pub struct LaunchedProcess {
+    child: Child,
+    suspended: bool,
+}
+
+impl LaunchedProcess {
+    pub fn pid(&self) -> Pid {
+        Pid::from_u32(self.child.id())
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Lets a suspended process run. On Linux, this must be called from the thread that launched it.
+    pub fn resume(&mut self) -> Result<(), ProcessError> {
+        if self.suspended {
+            resume(&self.child)?;
+            self.suspended = false;
+        }
+        Ok(())
+    }
+
+    /// Whether the process exited, which is then reaped.
+    pub fn has_exited(&mut self) -> bool {
+        #[cfg(target_os = "linux")]
+        if self.suspended {
+            return traced_has_exited(&self.child);
+        }
+        self.child
+            .try_wait()
+            .map_or(true, |status| status.is_some())
+    }
+}
+
+fn io_error(err: std::io::Error) -> ProcessError {
+    match err.raw_os_error() {
+        Some(code) => ProcessError::Os(code),
+        None => ProcessError::Other(err.to_string()),
+    }
+}
+
+/// Starts a process, which can be opened right away.
+pub fn launch(options: &LaunchOptions) -> Result<LaunchedProcess, ProcessError> {
+    let mut command = Command::new(&options.path);
+    command
+        .args(&options.args)
+        .envs(options.env.iter().map(|(k, v)| (k, v)));
+    if let Some(dir) = &options.working_dir {
+        command.current_dir(dir);
+    }
+    let child = match options.suspended {
+        true => spawn_suspended(&mut command)?,
+        false => command.spawn().map_err(io_error)?,
+    };
+    Ok(LaunchedProcess {
+        child,
+        suspended: options.suspended,
+    })
+}
+
+// The child is traced and stops when its executable is loaded, before the dynamic loader runs
+#[cfg(target_os = "linux")]
+fn spawn_suspended(command: &mut Command) -> Result<Child, ProcessError> {
+    use std::os::unix::process::CommandExt;
+    use std::ptr::null_mut;
+
+    unsafe {
+        command.pre_exec(|| {
+            let r = libc::ptrace(
+                libc::PTRACE_TRACEME,
+                0,
+                null_mut::<libc::c_void>(),
+                null_mut::<libc::c_void>(),
+            );
+            match r {
+                -1 => Err(std::io::Error::last_os_error()),
+                _ => Ok(()),
+            }
+        });
+    }
+    let mut child = command.spawn().map_err(io_error)?;
+    let mut status = 0;
+    let r = unsafe { libc::waitpid(child.id() as libc::pid_t, &mut status, 0) };
+    if r == -1 {
+        let err = io_error(std::io::Error::last_os_error());
+        let _ = child.kill();
+        return Err(err);
+    }
+    if !libc::WIFSTOPPED(status) {
+        return Err(ProcessError::ProcessExited);
+    }
+    Ok(child)
+}
+
+// Waits for the traced child without blocking, its stops are reported too and are not exits
+#[cfg(target_os = "linux")]
+fn traced_has_exited(child: &Child) -> bool {
+    let mut status = 0;
+    let r = unsafe { libc::waitpid(child.id() as libc::pid_t, &mut status, libc::WNOHANG) };
+    match r {
+        0 => false,
+        -1 => true,
+        _ => libc::WIFEXITED(status) || libc::WIFSIGNALED(status),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn resume(child: &Child) -> Result<(), ProcessError> {
+    use std::ptr::null_mut;
+
+    let r = unsafe {
+        libc::ptrace(
+            libc::PTRACE_DETACH,
+            child.id() as libc::pid_t,
+            null_mut::<libc::c_void>(),
+            null_mut::<libc::c_void>(),
+        )
+    };
+    match r {
+        -1 => Err(io_error(std::io::Error::last_os_error())),
+        _ => Ok(()),
+    }
+}
+
+// The main thread of the child is created suspended
+#[cfg(target_os = "windows")]
+fn spawn_suspended(command: &mut Command) -> Result<Child, ProcessError> {
+    use std::os::windows::process::CommandExt;
+    use windows_sys::Win32::System::Threading::CREATE_SUSPENDED;
+
+    command.creation_flags(CREATE_SUSPENDED);
+    command.spawn().map_err(io_error)
+}
+
+#[cfg(target_os = "windows")]
+fn resume(child: &Child) -> Result<(), ProcessError> {
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows_sys::Win32::System::Threading::{OpenThread, ResumeThread, THREAD_SUSPEND_RESUME};
+
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Err(ProcessError::Os(unsafe { GetLastError() } as i32));
+    }
+    let mut entry: THREADENTRY32 = unsafe { std::mem::zeroed() };
+    entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+    let mut result = Ok(());
+    let mut more = unsafe { Thread32First(snapshot, &mut entry) } != 0;
+    while more {
+        if entry.th32OwnerProcessID == child.id() {
+            let thread = unsafe { OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID) };
+            if thread.is_null() || unsafe { ResumeThread(thread) } == u32::MAX {
+                result = Err(ProcessError::Os(unsafe { GetLastError() } as i32));
+            }
+            if !thread.is_null() {
+                unsafe { CloseHandle(thread) };
+            }
+        }
+        more = unsafe { Thread32Next(snapshot, &mut entry) } != 0;
+    }
+    unsafe { CloseHandle(snapshot) };
+    result
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn spawn_suspended(_command: &mut Command) -> Result<Child, ProcessError> {
+    Err(ProcessError::Other(
+        "Suspended launches are not supported on this system.".into(),
+    ))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn resume(_child: &Child) -> Result<(), ProcessError> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_launch_suspended() {
+        let options = LaunchOptions {
+            path: "/bin/sh".into(),
+            args: vec!["-c".into(), "exit 0".into()],
+            suspended: true,
+            ..Default::default()
+        };
+        let mut launched = launch(&options).expect("should launch a shell");
+        assert!(launched.is_suspended());
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", launched.pid())).unwrap();
+        let state = stat.rsplit(')').next().unwrap().split_whitespace().next();
+        assert_eq!(state, Some("t"));
+        assert!(!launched.has_exited());
+
+        launched.resume().expect("should resume the shell");
+        assert!(!launched.is_suspended());
+        let status = launched.child.wait().unwrap();
+        assert!(status.success());
+        assert!(launched.has_exited());
+
+        // Killed while suspended
+        let mut launched = launch(&options).expect("should launch a shell");
+        assert!(!launched.has_exited());
+        launched.child.kill().unwrap();
+        let start = std::time::Instant::now();
+        while !launched.has_exited() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(1));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+}
@@ -1,10 +1,18 @@
 use std::fs::File;
-use std::io::{BufReader, SeekFrom};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use sysinfo::Pid;
 
+use super::{pages_of, read_scatter_paged, MemoryRegion, Module, ProcessError, RangeRead};
 use crate::typing::DataType;
 
-#[derive(Debug, PartialEq, Default)]
+// errno values, /proc/pid/mem fails with EIO on unmapped pages
+const ESRCH: i32 = 3;
+const EIO: i32 = 5;
+// Maximum number of iovecs per process_vm_readv call (UIO_MAXIOV)
+const MAX_IOV: usize = 1024;
+
+#[derive(Debug, Default)]
 enum State {
     #[default]
     Created,
@@ -13,7 +21,7 @@ enum State {
     Closed,
 }
 impl State {
-    fn memfile(&self) -> Option<&HANDLE> {
+    fn memfile(&mut self) -> Option<&mut BufReader<File>> {
         match self {
             State::Open(file) => Some(file),
             _ => None,
@@ -34,78 +42,281 @@ impl LinProcess {
             state: State::Created,
         }
     }
+
+    fn exited(&self) -> bool {
+        !Path::new(&format!("/proc/{}", self.pid)).exists()
+    }
+
+    fn io_error(&self, err: std::io::Error) -> ProcessError {
+        match (err.kind(), err.raw_os_error()) {
+            (ErrorKind::PermissionDenied, _) => ProcessError::PermissionDenied,
+            (ErrorKind::NotFound, _) | (_, Some(ESRCH)) => ProcessError::ProcessExited,
+            _ if self.exited() => ProcessError::ProcessExited,
+            (_, Some(code)) => ProcessError::Os(code),
+            (_, None) => ProcessError::Other(err.to_string()),
+        }
+    }
 }
 impl super::SystemProcess for LinProcess {
     fn pid(&self) -> Pid {
         self.pid
     }
 
-    fn open(&mut self) -> Result<(), String> {
+    fn open(&mut self) -> Result<(), ProcessError> {
         match self.state {
             State::Created => {
                 let memfile = File::options()
                     .read(true)
                     .write(true)
-                    .open("/proc/{}/mem")
-                    .map_err(|err| err.to_string())?;
+                    .open(format!("/proc/{}/mem", self.pid))
+                    .map_err(|err| self.io_error(err))?;
                 self.state = State::Open(BufReader::new(memfile));
                 Ok(())
             }
-            _ => Err("This process has already been opened.".into()),
+            _ => Err(ProcessError::AlreadyOpened),
         }
     }
 
-    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, String> {
-        let mut memfile = self
-            .state
-            .memfile()
-            .ok_or("Process is closed or not yet opened.")?;
+    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, ProcessError> {
         let size = dt.get_size();
-        let mut read_buffer: Vec<u8> = Vec::with_capacity(size);
-
-        // SAFETY:
-        // 1. elements will get initialized by the read_exact if there are no errors (else the buffer is dropped)
-        // 2. size <= size, set with with_capacity.
-        // So set_len is safe to call
-        unsafe {
-            read_buffer.set_len(size);
+        let mut read_buffer = vec![0u8; size];
+        let mut read = 0;
+        let memfile = self.state.memfile().ok_or(ProcessError::NotOpened)?;
+        if let Err(err) = memfile.seek(SeekFrom::Start(location)) {
+            return Err(self.io_error(err));
         }
 
-        if location != memfile.stream_position() {
-            memfile.seek(SeekFrom::start(location));
+        // Reads stop at the first unmapped page
+        while read < size {
+            match memfile.read(&mut read_buffer[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) if err.raw_os_error() == Some(EIO) => break,
+                Err(err) => return Err(self.io_error(err)),
+            }
+        }
+        match read {
+            _ if read == size => Ok(read_buffer),
+            // The memory of a dead process reads as empty
+            _ if self.exited() => Err(ProcessError::ProcessExited),
+            0 => Err(ProcessError::Unmapped(location)),
+            _ => Err(ProcessError::PartialRead {
+                address: location,
+                read,
+                requested: size,
+            }),
         }
-
-        memfile
-            .read_exact(&mut read_buffer)
-            .map_err(|err| err.to_string());
-
-        return Ok(read_buffer);
     }
 
-    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), String> {
-        let mut memfile = self
-            .state
-            .memfile()
-            .ok_or("Process is closed or not yet opened.")?;
+    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), ProcessError> {
+        let memfile = self.state.memfile().ok_or(ProcessError::NotOpened)?;
         let size = what.len();
-        let mut bytes_written = 0usize;
 
-        if location != memfile.stream_position() {
-            memfile.seek(SeekFrom::start(location));
+        let result = memfile
+            .seek(SeekFrom::Start(location))
+            .and_then(|_| memfile.get_mut().write(&what));
+        match result {
+            Ok(bytes_written) if bytes_written == size => Ok(()),
+            Ok(bytes_written) => Err(ProcessError::Unmapped(location + bytes_written as u64)),
+            Err(err) if err.raw_os_error() == Some(EIO) => Err(ProcessError::Unmapped(location)),
+            Err(err) => Err(self.io_error(err)),
         }
+    }
 
-        let bytes_written = memfile
-            .get_mut_ref()
-            .write(&what)
-            .map_err(|err| err.to_string())?;
-        if bytes_written != size {
-            return Err(format!("Memory written was smaller than requested !"));
+    fn modules(&self) -> Result<Vec<Module>, ProcessError> {
+        // A module is mapped in several regions (one per segment), merge them by path
+        let mut modules: Vec<Module> = Vec::new();
+        for region in self.regions()? {
+            let Some(path) = region.path else {
+                continue;
+            };
+            match modules.iter_mut().find(|m| m.path == path) {
+                Some(m) => {
+                    let module_end = (m.base + m.size).max(region.start + region.size);
+                    m.base = m.base.min(region.start);
+                    m.size = module_end - m.base;
+                }
+                None => {
+                    modules.push(Module {
+                        name: path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                        path,
+                        base: region.start,
+                        size: region.size,
+                    });
+                }
+            }
         }
+        Ok(modules)
+    }
+
+    fn regions(&self) -> Result<Vec<MemoryRegion>, ProcessError> {
+        let maps = std::fs::read_to_string(format!("/proc/{}/maps", self.pid))
+            .map_err(|err| self.io_error(err))?;
 
-        return Ok(());
+        let mut regions = Vec::new();
+        for line in maps.lines() {
+            // start-end perms offset dev inode path
+            let mut fields = line.splitn(6, ' ');
+            let range = fields.next().unwrap_or_default();
+            let perms = fields.next().unwrap_or_default().as_bytes();
+            let path = fields.nth(3).unwrap_or_default().trim_start();
+            let Some((start, end)) = range.split_once('-').and_then(|(s, e)| {
+                Some((
+                    u64::from_str_radix(s, 16).ok()?,
+                    u64::from_str_radix(e, 16).ok()?,
+                ))
+            }) else {
+                continue;
+            };
+            regions.push(MemoryRegion {
+                start,
+                size: end - start,
+                readable: perms.first() == Some(&b'r'),
+                writable: perms.get(1) == Some(&b'w'),
+                executable: perms.get(2) == Some(&b'x'),
+                // Anonymous and special mappings ([heap], [stack]) have no file
+                path: path.starts_with('/').then(|| PathBuf::from(path)),
+            });
+        }
+        Ok(regions)
     }
 
     fn close(&mut self) {
         self.state = State::Closed;
     }
+
+    fn read_scatter(&mut self, ranges: &[(u64, usize)]) -> Result<Vec<RangeRead>, ProcessError> {
+        if self.state.memfile().is_none() {
+            return Err(ProcessError::NotOpened);
+        }
+        let mut reads: Vec<RangeRead> = ranges
+            .iter()
+            .map(|&(address, len)| RangeRead::new(address, len))
+            .collect();
+        // One iovec per page, so that a fault only invalidates its own page.
+        // (index of the range, page address, len)
+        let pages: Vec<(usize, u64, usize)> = ranges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &(address, len))| {
+                pages_of(address, len).map(move |(page, page_len)| (i, page, page_len))
+            })
+            .collect();
+        // Taken once, the buffers are neither borrowed nor reallocated while the kernel fills them
+        let buffers: Vec<*mut u8> = reads.iter_mut().map(|r| r.data.as_mut_ptr()).collect();
+
+        let mut next = 0;
+        while next < pages.len() {
+            let batch = &pages[next..pages.len().min(next + MAX_IOV)];
+            let local: Vec<libc::iovec> = batch
+                .iter()
+                .map(|&(i, page, len)| {
+                    let offset = (page - reads[i].address) as usize;
+                    libc::iovec {
+                        // SAFETY: the page lies in range `i`, so `offset + len` is at most the
+                        // length of its buffer.
+                        iov_base: unsafe { buffers[i].add(offset) }.cast(),
+                        iov_len: len,
+                    }
+                })
+                .collect();
+            let remote: Vec<libc::iovec> = batch
+                .iter()
+                .map(|&(_, page, len)| libc::iovec {
+                    iov_base: page as *mut libc::c_void,
+                    iov_len: len,
+                })
+                .collect();
+            // SAFETY: every local iovec points into a distinct page sized slice of a buffer
+            // which outlives the call, and the vectors hold `len()` iovecs.
+            let result = unsafe {
+                libc::process_vm_readv(
+                    self.pid.as_u32() as libc::pid_t,
+                    local.as_ptr(),
+                    local.len() as libc::c_ulong,
+                    remote.as_ptr(),
+                    remote.len() as libc::c_ulong,
+                    0,
+                )
+            };
+            if result < 0 {
+                let err = std::io::Error::last_os_error();
+                match err.raw_os_error() {
+                    // The first page of the batch is unreadable
+                    Some(libc::EFAULT) => next += 1,
+                    // Kernels without the syscall
+                    Some(libc::ENOSYS) => return read_scatter_paged(self, ranges),
+                    _ => return Err(self.io_error(err)),
+                }
+                continue;
+            }
+            // Pages are read in order until the first fault, which is skipped
+            let mut remaining = result as usize;
+            let mut done = 0;
+            for &(i, page, len) in batch {
+                if remaining < len {
+                    break;
+                }
+                remaining -= len;
+                reads[i].set_valid(page);
+                done += 1;
+            }
+            next += (done + 1).min(batch.len());
+        }
+        Ok(reads)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::SystemProcess;
+    use crate::typing::IntegerDataType;
+
+    #[test]
+    fn test_read_errors() {
+        let u32_dt = IntegerDataType::default();
+        let mut process = LinProcess::new(Pid::from_u32(std::process::id()));
+        assert_eq!(
+            process.read_memory(0, &u32_dt),
+            Err(ProcessError::NotOpened)
+        );
+        process.open().unwrap();
+        assert_eq!(process.open(), Err(ProcessError::AlreadyOpened));
+        assert_eq!(
+            process.read_memory(0, &u32_dt),
+            Err(ProcessError::Unmapped(0))
+        );
+
+        // The end of a readable region followed by a gap
+        let regions = process.regions().unwrap();
+        let end = regions
+            .windows(2)
+            .find(|w| w[0].readable && w[0].end() < w[1].start)
+            .map(|w| w[0].end())
+            .unwrap();
+        assert_eq!(
+            process.read_memory(end - 2, &u32_dt),
+            Err(ProcessError::PartialRead {
+                address: end - 2,
+                read: 2,
+                requested: 4
+            })
+        );
+
+        let data = vec![0x5Au8; 0x3000];
+        let start = data.as_ptr() as u64;
+        let reads = process
+            .read_scatter(&[(start, 0x3000), (end - 0x10, 0x20), (0, 8)])
+            .unwrap();
+        assert!(reads[0].is_complete());
+        assert_eq!(reads[0].data(), &data[..]);
+        assert_eq!(reads[1].get(end - 0x10, 0x10).map(<[u8]>::len), Some(0x10));
+        assert_eq!(reads[1].get(end - 0x10, 0x11), None);
+        assert_eq!(reads[2].pages(), &[false]);
+    }
 }
@@ -0,0 +1,315 @@
+use std::ops::Range;
+use sysinfo::Pid;
+
+use super::{MemoryRegion, Module, ProcessError};
+use crate::typing::DataType;
+
+/// An in-memory process with configurable regions, modules and faults, for deterministic tests.
+///
+/// ```
+/// use rs_class::ops::{MockProcess, SystemProcess};
+/// use rs_class::typing::IntegerDataType;
+///
+/// let mut process = MockProcess::new(42).with_region(0x1000, vec![1, 0, 0, 0], true, false);
+/// process.open().unwrap();
+/// assert_eq!(process.read_memory(0x1000, &IntegerDataType::default()).unwrap(), vec![1, 0, 0, 0]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockProcess {
+    pid: u32,
+    opened: bool,
+    closed: bool,
+    exited: bool,
+    // Sorted by address
+    regions: Vec<(MemoryRegion, Vec<u8>)>,
+    modules: Vec<Module>,
+    // Mapped but faulting ranges, e.g. guard pages
+    faults: Vec<Range<u64>>,
+    reads: usize,
+    writes: usize,
+}
+
+impl MockProcess {
+    pub fn new(pid: u32) -> Self {
+        Self {
+            pid,
+            ..Default::default()
+        }
+    }
+
+    /// Maps a readable region holding `data`.
+    pub fn add_region(&mut self, start: u64, data: Vec<u8>, writable: bool, executable: bool) {
+        let region = MemoryRegion {
+            start,
+            size: data.len() as u64,
+            readable: true,
+            writable,
+            executable,
+            path: None,
+        };
+        self.add_memory_region(region, data);
+    }
+    pub fn with_region(
+        mut self,
+        start: u64,
+        data: Vec<u8>,
+        writable: bool,
+        executable: bool,
+    ) -> Self {
+        self.add_region(start, data, writable, executable);
+        self
+    }
+
+    /// Maps a region with explicit permissions and file, `data` is resized to the region size.
+    pub fn add_memory_region(&mut self, region: MemoryRegion, mut data: Vec<u8>) {
+        data.resize(region.size as usize, 0);
+        let idx = self
+            .regions
+            .partition_point(|(r, _)| r.start < region.start);
+        self.regions.insert(idx, (region, data));
+    }
+    pub fn with_memory_region(mut self, region: MemoryRegion, data: Vec<u8>) -> Self {
+        self.add_memory_region(region, data);
+        self
+    }
+
+    /// Adds a module, its regions are mapped separately.
+    pub fn add_module(&mut self, module: Module) {
+        self.modules.push(module);
+    }
+    pub fn with_module(mut self, module: Module) -> Self {
+        self.add_module(module);
+        self
+    }
+
+    /// Makes a mapped range fault, reads stop before it.
+    pub fn add_fault(&mut self, range: Range<u64>) {
+        self.faults.push(range);
+    }
+    pub fn with_fault(mut self, range: Range<u64>) -> Self {
+        self.add_fault(range);
+        self
+    }
+    pub fn clear_faults(&mut self) {
+        self.faults.clear();
+    }
+
+    /// Simulates the end of the process, every operation fails afterwards.
+    pub fn set_exited(&mut self, exited: bool) {
+        self.exited = exited;
+    }
+
+    /// Number of `read_memory` calls, successful or not.
+    pub fn read_count(&self) -> usize {
+        self.reads
+    }
+    /// Number of `write_memory` calls, successful or not.
+    pub fn write_count(&self) -> usize {
+        self.writes
+    }
+
+    /// Direct access to the memory, without permission checks nor faults.
+    pub fn memory(&self, address: u64, len: usize) -> Option<&[u8]> {
+        let (region, data) = self.region_at(address)?;
+        let offset = (address - region.start) as usize;
+        data.get(offset..offset + len)
+    }
+
+    fn region_at(&self, address: u64) -> Option<&(MemoryRegion, Vec<u8>)> {
+        let idx = self
+            .regions
+            .partition_point(|(r, _)| r.start <= address)
+            .checked_sub(1)?;
+        let entry = &self.regions[idx];
+        (address < entry.0.end()).then_some(entry)
+    }
+
+    fn check_state(&self) -> Result<(), ProcessError> {
+        if self.exited {
+            return Err(ProcessError::ProcessExited);
+        }
+        if !self.opened || self.closed {
+            return Err(ProcessError::NotOpened);
+        }
+        Ok(())
+    }
+
+    // Number of readable bytes from `address`, up to `len`
+    fn readable_len(&self, address: u64, len: usize) -> usize {
+        let mut count = 0;
+        while count < len {
+            let current = address + count as u64;
+            if self.faults.iter().any(|f| f.contains(&current)) {
+                break;
+            }
+            match self.region_at(current) {
+                Some((region, _)) if region.readable => {
+                    let fault_start = self
+                        .faults
+                        .iter()
+                        .filter(|f| f.start > current)
+                        .map(|f| f.start)
+                        .min()
+                        .unwrap_or(u64::MAX);
+                    let end = region.end().min(fault_start);
+                    count += ((end - current) as usize).min(len - count);
+                }
+                _ => break,
+            }
+        }
+        count
+    }
+}
+
+impl super::SystemProcess for MockProcess {
+    fn pid(&self) -> Pid {
+        Pid::from_u32(self.pid)
+    }
+
+    fn open(&mut self) -> Result<(), ProcessError> {
+        if self.exited {
+            return Err(ProcessError::ProcessExited);
+        }
+        if self.opened {
+            return Err(ProcessError::AlreadyOpened);
+        }
+        self.opened = true;
+        Ok(())
+    }
+
+    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, ProcessError> {
+        self.reads += 1;
+        self.check_state()?;
+        let size = dt.get_size();
+        let readable = self.readable_len(location, size);
+        match readable {
+            0 if size > 0 => return Err(ProcessError::Unmapped(location)),
+            _ if readable < size => {
+                return Err(ProcessError::PartialRead {
+                    address: location,
+                    read: readable,
+                    requested: size,
+                })
+            }
+            _ => {}
+        }
+        let mut read_buffer = Vec::with_capacity(size);
+        while read_buffer.len() < size {
+            let address = location + read_buffer.len() as u64;
+            let (region, data) = self.region_at(address).expect("range checked readable");
+            let offset = (address - region.start) as usize;
+            let len = (size - read_buffer.len()).min(data.len() - offset);
+            read_buffer.extend_from_slice(&data[offset..offset + len]);
+        }
+        Ok(read_buffer)
+    }
+
+    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), ProcessError> {
+        self.writes += 1;
+        self.check_state()?;
+        // Check the whole range first, failed writes do not change anything
+        let mut address = location;
+        let end = location + what.len() as u64;
+        while address < end {
+            match self.region_at(address) {
+                Some((region, _)) if region.writable => address = region.end(),
+                Some(_) => {
+                    return Err(ProcessError::Other(format!(
+                        "Address {address:#X} is not writable."
+                    )))
+                }
+                None => return Err(ProcessError::Unmapped(address)),
+            }
+        }
+        let mut written = 0;
+        while written < what.len() {
+            let address = location + written as u64;
+            let idx = self.regions.partition_point(|(r, _)| r.start <= address) - 1;
+            let (region, data) = &mut self.regions[idx];
+            let offset = (address - region.start) as usize;
+            let len = (what.len() - written).min(data.len() - offset);
+            data[offset..offset + len].copy_from_slice(&what[written..written + len]);
+            written += len;
+        }
+        Ok(())
+    }
+
+    fn modules(&self) -> Result<Vec<Module>, ProcessError> {
+        if self.exited {
+            return Err(ProcessError::ProcessExited);
+        }
+        Ok(self.modules.clone())
+    }
+
+    fn regions(&self) -> Result<Vec<MemoryRegion>, ProcessError> {
+        if self.exited {
+            return Err(ProcessError::ProcessExited);
+        }
+        Ok(self.regions.iter().map(|(r, _)| r.clone()).collect())
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::SystemProcess;
+    use crate::typing::{IntSize, IntegerDataType};
+
+    #[test]
+    fn test_mock_faults() {
+        let u32_dt = IntegerDataType::default();
+        let mut process = MockProcess::new(1)
+            .with_region(0x1000, vec![0xAA; 0x10], true, false)
+            .with_region(0x1010, vec![0xBB; 0x10], false, false)
+            .with_fault(0x1018..0x101C);
+        assert!(process.read_memory(0x1000, &u32_dt).is_err());
+        process.open().unwrap();
+
+        // Reads span adjacent regions
+        assert_eq!(
+            process.read_memory(0x100E, &u32_dt).unwrap(),
+            vec![0xAA, 0xAA, 0xBB, 0xBB]
+        );
+        assert_eq!(
+            process.read_memory(0x1016, &u32_dt),
+            Err(ProcessError::PartialRead {
+                address: 0x1016,
+                read: 2,
+                requested: 4
+            })
+        );
+        assert_eq!(
+            process.read_memory(0x1020, &u32_dt),
+            Err(ProcessError::Unmapped(0x1020))
+        );
+
+        assert!(process.write_memory(0x100E, vec![1, 2, 3]).is_err());
+        assert_eq!(process.memory(0x100E, 2).unwrap(), &[0xAA, 0xAA]);
+        process.write_memory(0x1000, vec![1, 2]).unwrap();
+        let byte = IntegerDataType::default().with_size(IntSize::Integer8);
+        assert_eq!(process.read_memory(0x1001, &byte).unwrap(), vec![2]);
+        assert_eq!(process.read_count(), 5);
+
+        // Range reads skip the faulting page
+        let read = process.read_range(0x1000, 0x1000).unwrap();
+        assert_eq!(read.pages(), &[false]);
+        process.add_region(0x2000, vec![0xCC; 0x2000], false, false);
+        process.add_fault(0x2000..0x2001);
+        let read = process.read_range(0x1FFE, 0x1004).unwrap();
+        assert_eq!(read.pages(), &[false, false, true]);
+        assert_eq!(read.get(0x3000, 2), Some(&[0xCC, 0xCC][..]));
+        assert_eq!(read.valid_runs(), vec![(0x3000, &[0xCC, 0xCC][..])]);
+        assert_eq!(read.get(0x1FFE, 2), None);
+
+        process.set_exited(true);
+        assert_eq!(
+            process.read_memory(0x1000, &byte),
+            Err(ProcessError::ProcessExited)
+        );
+        assert!(process.regions().is_err());
+    }
+}
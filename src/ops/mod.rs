@@ -1,6 +1,23 @@
-use crate::typing::DataType;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use crate::typing::{ArrayDataType, DataType, IntSize, IntegerDataType};
+use serde::{Deserialize, Serialize};
 use sysinfo::Pid;
 
+mod cache;
+pub use cache::CachedProcess;
+mod dump;
+pub use dump::DumpProcess;
+mod launch;
+pub use launch::{launch, LaunchOptions, LaunchedProcess};
+mod mock;
+pub use mock::MockProcess;
+mod reader;
+pub use reader::{MemoryReader, ViewData, ViewId};
+mod target;
+pub use target::Target;
+
 #[cfg(target_os = "windows")]
 mod win;
 #[cfg(target_os = "windows")]
@@ -11,10 +28,271 @@ mod linux;
 #[cfg(target_os = "linux")]
 pub use linux::LinProcess as Process;
 
+/// An executable image (executable or shared library) mapped in a process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Module {
+    pub name: String,
+    pub path: PathBuf,
+    pub base: u64,
+    pub size: u64,
+}
+impl Module {
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base && address - self.base < self.size
+    }
+}
+
+/// A range of mapped memory with uniform protections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub start: u64,
+    pub size: u64,
+    pub readable: bool,
+    pub writable: bool,
+    pub executable: bool,
+    // Mapped file, for the regions of modules
+    pub path: Option<PathBuf>,
+}
+impl MemoryRegion {
+    pub fn end(&self) -> u64 {
+        self.start + self.size
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessError {
+    PermissionDenied,
+    ProcessExited,
+    NotOpened,
+    AlreadyOpened,
+    // Nothing could be read or written at this address
+    Unmapped(u64),
+    // The range starts readable but ends in unmapped memory
+    PartialRead {
+        address: u64,
+        read: usize,
+        requested: usize,
+    },
+    // Raw error code of the OS (errno, GetLastError)
+    Os(i32),
+    Other(String),
+}
+
+impl Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(target_os = "linux")]
+            ProcessError::PermissionDenied => write!(
+                f,
+                "Permission denied. Attaching to a process may be restricted by \
+                /proc/sys/kernel/yama/ptrace_scope, set it to 0 or run as root."
+            ),
+            #[cfg(target_os = "windows")]
+            ProcessError::PermissionDenied => write!(
+                f,
+                "Access denied. Elevated and protected processes need the SeDebugPrivilege, \
+                run as administrator."
+            ),
+            #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+            ProcessError::PermissionDenied => write!(f, "Permission denied."),
+            ProcessError::ProcessExited => write!(f, "The process has exited."),
+            ProcessError::NotOpened => write!(f, "Process is closed or not yet opened."),
+            ProcessError::AlreadyOpened => write!(f, "This process has already been opened."),
+            ProcessError::Unmapped(address) => write!(f, "Address {address:#X} is not mapped."),
+            ProcessError::PartialRead {
+                address,
+                read,
+                requested,
+            } => write!(
+                f,
+                "Partial read: {read} of {requested} bytes at {address:#X}."
+            ),
+            ProcessError::Os(code) => {
+                write!(f, "OS error: {}", std::io::Error::from_raw_os_error(*code))
+            }
+            ProcessError::Other(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+impl ProcessError {
+    /// Whether nothing else can be read from the process, as opposed to a failure of this read only.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            ProcessError::PermissionDenied | ProcessError::ProcessExited | ProcessError::NotOpened
+        )
+    }
+}
+
+pub const PAGE_SIZE: u64 = 0x1000;
+
+/// Bytes of a range of memory, with the pages which could be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeRead {
+    address: u64,
+    // Bytes of unreadable pages are zeroed
+    data: Vec<u8>,
+    // One entry per page touched by the range, from the page of `address`
+    pages: Vec<bool>,
+}
+
+impl RangeRead {
+    /// A range where nothing was read yet.
+    pub fn new(address: u64, len: usize) -> Self {
+        let pages = match len {
+            0 => 0,
+            _ => ((address + len as u64 - 1) / PAGE_SIZE - address / PAGE_SIZE + 1) as usize,
+        };
+        Self {
+            address,
+            data: vec![0; len],
+            pages: vec![false; pages],
+        }
+    }
+
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn end(&self) -> u64 {
+        self.address + self.data.len() as u64
+    }
+
+    /// All the bytes of the range, zero where unreadable.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Validity of each page touched by the range.
+    pub fn pages(&self) -> &[bool] {
+        &self.pages
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pages.iter().all(|&valid| valid)
+    }
+
+    /// Whether all the bytes from `address` to `address + len` were read.
+    pub fn is_valid(&self, address: u64, len: usize) -> bool {
+        if address < self.address || address + len as u64 > self.end() {
+            return false;
+        }
+        if len == 0 {
+            return true;
+        }
+        let first = (address / PAGE_SIZE - self.address / PAGE_SIZE) as usize;
+        let last = ((address + len as u64 - 1) / PAGE_SIZE - self.address / PAGE_SIZE) as usize;
+        self.pages[first..=last].iter().all(|&valid| valid)
+    }
+
+    /// Bytes read at `address`, if they were all readable.
+    pub fn get(&self, address: u64, len: usize) -> Option<&[u8]> {
+        let offset = (address.checked_sub(self.address)?) as usize;
+        self.is_valid(address, len)
+            .then(|| &self.data[offset..offset + len])
+    }
+
+    /// Contiguous readable parts of the range, as (address, bytes).
+    pub fn valid_runs(&self) -> Vec<(u64, &[u8])> {
+        let mut runs: Vec<(u64, &[u8])> = Vec::new();
+        let mut run_start = None;
+        for ((page, len), &valid) in pages_of(self.address, self.data.len()).zip(&self.pages) {
+            match (valid, run_start) {
+                (true, None) => run_start = Some(page),
+                (false, Some(start)) => {
+                    runs.push((start, self.slice(start, page)));
+                    run_start = None;
+                }
+                _ => {}
+            }
+            if valid && page + len as u64 == self.end() {
+                let start = run_start.take().unwrap_or(page);
+                runs.push((start, self.slice(start, self.end())));
+            }
+        }
+        runs
+    }
+
+    fn slice(&self, start: u64, end: u64) -> &[u8] {
+        &self.data[(start - self.address) as usize..(end - self.address) as usize]
+    }
+
+    // Stores the bytes read at `address`, which must cover their pages within the range
+    fn fill(&mut self, address: u64, bytes: &[u8]) {
+        let offset = (address - self.address) as usize;
+        self.data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        for (page, _) in pages_of(address, bytes.len()) {
+            self.set_valid(page);
+        }
+    }
+
+    // Marks the page of `address` as read
+    fn set_valid(&mut self, address: u64) {
+        self.pages[(address / PAGE_SIZE - self.address / PAGE_SIZE) as usize] = true;
+    }
+}
+
+// Splits a range at page boundaries, as (address, len)
+fn pages_of(address: u64, len: usize) -> impl Iterator<Item = (u64, usize)> {
+    let end = address + len as u64;
+    std::iter::successors(Some(address).filter(|&a| a < end), move |&a| {
+        Some((a / PAGE_SIZE + 1) * PAGE_SIZE).filter(|&next| next < end)
+    })
+    .map(move |a| (a, (((a / PAGE_SIZE + 1) * PAGE_SIZE).min(end) - a) as usize))
+}
+
+fn byte_array(len: usize) -> ArrayDataType {
+    let bytes = IntegerDataType::default().with_size(IntSize::Integer8);
+    ArrayDataType::new(bytes.into(), len)
+}
+
+// Reads each range whole, or page by page when part of it cannot be read
+fn read_scatter_paged<P: SystemProcess + ?Sized>(
+    process: &mut P,
+    ranges: &[(u64, usize)],
+) -> Result<Vec<RangeRead>, ProcessError> {
+    let mut reads = Vec::with_capacity(ranges.len());
+    for &(address, len) in ranges {
+        let mut read = RangeRead::new(address, len);
+        match process.read_memory(address, &byte_array(len)) {
+            Ok(data) => read.fill(address, &data),
+            Err(err) if err.is_fatal() => return Err(err),
+            Err(_) => {
+                for (page, page_len) in pages_of(address, len) {
+                    match process.read_memory(page, &byte_array(page_len)) {
+                        Ok(data) => read.fill(page, &data),
+                        Err(err) if err.is_fatal() => return Err(err),
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+        reads.push(read);
+    }
+    Ok(reads)
+}
+
 pub trait SystemProcess {
-    fn open(&mut self) -> Result<(), String>;
+    fn open(&mut self) -> Result<(), ProcessError>;
     fn pid(&self) -> Pid;
-    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, String>;
-    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), String>;
+    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, ProcessError>;
+    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), ProcessError>;
+    fn modules(&self) -> Result<Vec<Module>, ProcessError>;
+    fn regions(&self) -> Result<Vec<MemoryRegion>, ProcessError>;
     fn close(&mut self);
+
+    /// Reads a range of bytes, the unreadable pages are reported instead of failing the read.
+    fn read_range(&mut self, location: u64, len: usize) -> Result<RangeRead, ProcessError> {
+        let mut reads = self.read_scatter(&[(location, len)])?;
+        Ok(reads.remove(0))
+    }
+
+    /// Reads many (address, len) ranges at once, e.g. all the fields of a view.
+    /// Fails only when the process cannot be read at all.
+    fn read_scatter(&mut self, ranges: &[(u64, usize)]) -> Result<Vec<RangeRead>, ProcessError> {
+        read_scatter_paged(self, ranges)
+    }
 }
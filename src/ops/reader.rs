@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+use sysinfo::Pid;
+
+use super::{byte_array, CachedProcess, ProcessError, RangeRead, SystemProcess};
+use crate::typing::Bitfield;
+
+pub type ViewId = u64;
+// Address and bit offset of a frozen value, whole values have no bit offset
+type FreezeKey = (u64, Option<u8>);
+
+const DEFAULT_REFRESH_RATE: Duration = Duration::from_millis(100);
+const DEFAULT_FREEZE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Last read of a watched range.
+#[derive(Debug, Clone)]
+pub struct ViewData {
+    pub read: RangeRead,
+    pub updated: Instant,
+}
+
+// Bits set in `mask` are written from `bytes`, the others are written back as read
+#[derive(Debug)]
+struct FrozenValue {
+    bytes: Vec<u8>,
+    mask: Option<Vec<u8>>,
+}
+
+#[derive(Debug)]
+struct View {
+    address: u64,
+    len: usize,
+    data: Option<ViewData>,
+}
+
+enum Command {
+    Watch(ViewId, u64, usize),
+    Unwatch(ViewId),
+    SetRefreshRate(Duration),
+    Write(u64, Vec<u8>),
+    Freeze(FreezeKey, FrozenValue),
+    Unfreeze(FreezeKey),
+    SetFreezeInterval(Duration),
+    Stop,
+}
+
+enum Event {
+    Data(ViewId, RangeRead, Instant),
+    Error(ProcessError),
+}
+
+/// Reads the watched ranges of a process from a worker thread, so that a slow or hung
+/// target never blocks the UI. The UI sends commands and polls the last reads without waiting.
+#[derive(Debug)]
+pub struct MemoryReader {
+    pid: Pid,
+    commands: Sender<Command>,
+    events: Receiver<Event>,
+    views: HashMap<ViewId, View>,
+    next_id: ViewId,
+    refresh_rate: Duration,
+    // Values re-written by the worker
+    frozen: HashMap<FreezeKey, Vec<u8>>,
+    freeze_interval: Duration,
+    last_error: Option<ProcessError>,
+}
+
+impl MemoryReader {
+    /// Moves an opened process to a new worker thread.
+    pub fn spawn<P: SystemProcess + Send + 'static>(process: P) -> Self {
+        let (commands, command_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        let pid = process.pid();
+        std::thread::spawn(move || {
+            run(
+                process,
+                command_receiver,
+                event_sender,
+                DEFAULT_REFRESH_RATE,
+                DEFAULT_FREEZE_INTERVAL,
+            );
+        });
+        Self {
+            pid,
+            commands,
+            events,
+            views: HashMap::new(),
+            next_id: 0,
+            refresh_rate: DEFAULT_REFRESH_RATE,
+            frozen: HashMap::new(),
+            freeze_interval: DEFAULT_FREEZE_INTERVAL,
+            last_error: None,
+        }
+    }
+
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    pub fn get_refresh_rate(&self) -> Duration {
+        self.refresh_rate
+    }
+    pub fn set_refresh_rate(&mut self, refresh_rate: Duration) {
+        self.refresh_rate = refresh_rate;
+        self.send(Command::SetRefreshRate(refresh_rate));
+    }
+
+    /// Starts reading a range at every refresh.
+    pub fn watch(&mut self, address: u64, len: usize) -> ViewId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.views.insert(
+            id,
+            View {
+                address,
+                len,
+                data: None,
+            },
+        );
+        self.send(Command::Watch(id, address, len));
+        id
+    }
+
+    /// Moves a view to another range, its data is cleared until the new range is read.
+    pub fn set_range(&mut self, id: ViewId, address: u64, len: usize) {
+        if let Some(view) = self.views.get_mut(&id) {
+            if view.address != address || view.len != len {
+                *view = View {
+                    address,
+                    len,
+                    data: None,
+                };
+                self.send(Command::Watch(id, address, len));
+            }
+        }
+    }
+
+    pub fn unwatch(&mut self, id: ViewId) {
+        if self.views.remove(&id).is_some() {
+            self.send(Command::Unwatch(id));
+        }
+    }
+
+    /// Queues a write, failures are reported by `last_error`.
+    pub fn write(&mut self, address: u64, bytes: Vec<u8>) {
+        self.send(Command::Write(address, bytes));
+    }
+
+    /// Keeps writing `bytes` at `address` every freeze interval, replacing a previous freeze
+    /// of the same value. Only the bits of `bitfield` are kept when given: the storage unit is
+    /// read, and written back with them. Failures are reported by `last_error`.
+    pub fn freeze(&mut self, address: u64, bitfield: Option<Bitfield>, bytes: Vec<u8>) {
+        let key = (address, bitfield.map(|b| b.bit_offset));
+        let mask = bitfield.map(|b| {
+            let mut mask = vec![0; bytes.len()];
+            b.insert(&mut mask, u64::MAX);
+            mask
+        });
+        self.frozen.insert(key, bytes.clone());
+        self.send(Command::Freeze(key, FrozenValue { bytes, mask }));
+    }
+
+    pub fn unfreeze(&mut self, address: u64, bitfield: Option<Bitfield>) {
+        let key = (address, bitfield.map(|b| b.bit_offset));
+        if self.frozen.remove(&key).is_some() {
+            self.send(Command::Unfreeze(key));
+        }
+    }
+
+    /// Value frozen at an address, with the storage unit of a bitfield.
+    pub fn get_frozen(&self, address: u64, bitfield: Option<Bitfield>) -> Option<&[u8]> {
+        let key = (address, bitfield.map(|b| b.bit_offset));
+        self.frozen.get(&key).map(Vec::as_slice)
+    }
+
+    pub fn get_freeze_interval(&self) -> Duration {
+        self.freeze_interval
+    }
+    pub fn set_freeze_interval(&mut self, freeze_interval: Duration) {
+        self.freeze_interval = freeze_interval;
+        self.send(Command::SetFreezeInterval(freeze_interval));
+    }
+
+    /// Takes the reads published by the worker since the last call, never blocks.
+    pub fn poll(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Event::Data(id, read, updated) => {
+                    // Reads of a previous range of the view are dropped
+                    if let Some(view) = self
+                        .views
+                        .get_mut(&id)
+                        .filter(|v| v.address == read.address() && v.len == read.data().len())
+                    {
+                        view.data = Some(ViewData { read, updated });
+                    }
+                }
+                Event::Error(err) => self.last_error = Some(err),
+            }
+        }
+    }
+
+    pub fn get(&self, id: ViewId) -> Option<&ViewData> {
+        self.views.get(&id)?.data.as_ref()
+    }
+
+    /// Time since the last read of a view, None if it was never read.
+    pub fn staleness(&self, id: ViewId) -> Option<Duration> {
+        self.get(id).map(|data| data.updated.elapsed())
+    }
+
+    /// Whether a view missed several refreshes, e.g. because the target is hung.
+    pub fn is_stale(&self, id: ViewId) -> bool {
+        self.staleness(id)
+            .is_none_or(|staleness| staleness > self.refresh_rate * 3)
+    }
+
+    pub fn last_error(&self) -> Option<&ProcessError> {
+        self.last_error.as_ref()
+    }
+
+    /// Whether the worker stopped after a fatal error, e.g. the process exited.
+    pub fn is_stopped(&self) -> bool {
+        self.last_error.as_ref().is_some_and(ProcessError::is_fatal)
+    }
+
+    fn send(&self, command: Command) {
+        // The worker is gone after a fatal error, which is already reported
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Drop for MemoryReader {
+    fn drop(&mut self) {
+        // Not joined, the worker may be stuck in a read of a hung process
+        self.send(Command::Stop);
+    }
+}
+
+fn run<P: SystemProcess>(
+    process: P,
+    commands: Receiver<Command>,
+    events: Sender<Event>,
+    mut refresh_rate: Duration,
+    mut freeze_interval: Duration,
+) {
+    // Overlapping views, e.g. a struct shown in the hex view too, read their pages once.
+    // Pages expire before the next refresh.
+    let mut process = CachedProcess::new(process).with_ttl(refresh_rate / 2);
+    let mut views: BTreeMap<ViewId, (u64, usize)> = BTreeMap::new();
+    let mut frozen: BTreeMap<FreezeKey, FrozenValue> = BTreeMap::new();
+    let mut next_refresh = Instant::now();
+    let mut next_freeze = Instant::now();
+    'worker: loop {
+        let next = match frozen.is_empty() {
+            true => next_refresh,
+            false => next_refresh.min(next_freeze),
+        };
+        let timeout = next.saturating_duration_since(Instant::now());
+        match commands.recv_timeout(timeout) {
+            Ok(Command::Watch(id, address, len)) => {
+                views.insert(id, (address, len));
+                // New ranges are read right away
+                next_refresh = Instant::now();
+            }
+            Ok(Command::Unwatch(id)) => {
+                views.remove(&id);
+            }
+            Ok(Command::SetRefreshRate(rate)) => {
+                refresh_rate = rate;
+                process.set_ttl(rate / 2);
+            }
+            Ok(Command::Write(address, bytes)) => {
+                if let Err(err) = process.write_memory(address, bytes) {
+                    let _ = events.send(Event::Error(err));
+                }
+                next_refresh = Instant::now();
+            }
+            Ok(Command::Freeze(key, value)) => {
+                frozen.insert(key, value);
+                next_freeze = Instant::now();
+            }
+            Ok(Command::Unfreeze(key)) => {
+                frozen.remove(&key);
+            }
+            Ok(Command::SetFreezeInterval(interval)) => freeze_interval = interval,
+            Ok(Command::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                if !frozen.is_empty() && now >= next_freeze {
+                    next_freeze = now + freeze_interval;
+                    for (&(address, _), value) in &frozen {
+                        if let Err(err) = write_frozen(&mut process, address, value) {
+                            let fatal = err.is_fatal();
+                            let _ = events.send(Event::Error(err));
+                            if fatal {
+                                break 'worker;
+                            }
+                        }
+                    }
+                }
+                if now < next_refresh {
+                    continue;
+                }
+                next_refresh = now + refresh_rate;
+                if views.is_empty() {
+                    continue;
+                }
+                let ranges: Vec<(u64, usize)> = views.values().copied().collect();
+                match process.read_scatter(&ranges) {
+                    Ok(reads) => {
+                        let now = Instant::now();
+                        for (&id, read) in views.keys().zip(reads) {
+                            if events.send(Event::Data(id, read, now)).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        let fatal = err.is_fatal();
+                        let _ = events.send(Event::Error(err));
+                        if fatal {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    process.close();
+}
+
+// Writes a frozen value, merged in the current bytes when it is masked
+fn write_frozen<P: SystemProcess>(
+    process: &mut P,
+    address: u64,
+    value: &FrozenValue,
+) -> Result<(), ProcessError> {
+    let bytes = match &value.mask {
+        None => value.bytes.clone(),
+        Some(mask) => {
+            let current = process.read_memory(address, &byte_array(value.bytes.len()))?;
+            current
+                .iter()
+                .zip(&value.bytes)
+                .zip(mask)
+                .map(|((current, frozen), mask)| (current & !mask) | (frozen & mask))
+                .collect()
+        }
+    };
+    process.write_memory(address, bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::MockProcess;
+
+    // Polls until `done` or a second has passed
+    fn wait(reader: &mut MemoryReader, done: impl Fn(&MemoryReader) -> bool) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(1) {
+            reader.poll();
+            if done(reader) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_reader() {
+        let mut process = MockProcess::new(1).with_region(0x1000, vec![1; 0x100], true, false);
+        process.open().unwrap();
+        let mut reader = MemoryReader::spawn(process);
+        reader.set_refresh_rate(Duration::from_millis(10));
+        let view = reader.watch(0x10F0, 0x20);
+        assert!(reader.is_stale(view));
+        assert!(wait(&mut reader, |r| r.get(view).is_some()));
+        assert_eq!(reader.get(view).unwrap().read.pages(), &[false]);
+
+        reader.set_range(view, 0x1000, 4);
+        reader.write(0x1000, vec![2, 3]);
+        assert!(wait(&mut reader, |r| r
+            .get(view)
+            .is_some_and(|d| d.read.data() == [2, 3, 1, 1])));
+        assert!(!reader.is_stale(view));
+
+        // Frozen values are written back after other writes
+        reader.freeze(0x1001, None, vec![5]);
+        reader.write(0x1000, vec![6, 6]);
+        assert!(wait(&mut reader, |r| r
+            .get(view)
+            .is_some_and(|d| d.read.data() == [6, 5, 1, 1])));
+        assert_eq!(reader.get_frozen(0x1001, None), Some(&[5][..]));
+        reader.unfreeze(0x1001, None);
+        reader.write(0x1001, vec![7]);
+        assert!(wait(&mut reader, |r| r
+            .get(view)
+            .is_some_and(|d| d.read.data() == [6, 7, 1, 1])));
+
+        // Frozen bitfields keep the other bits of their storage unit
+        let bitfield = Bitfield {
+            bit_offset: 2,
+            bit_size: 3,
+        };
+        reader.freeze(0x1002, Some(bitfield), vec![0b101 << 2]);
+        reader.write(0x1002, vec![0b1110_0011]);
+        assert!(wait(&mut reader, |r| r
+            .get(view)
+            .is_some_and(|d| d.read.data() == [6, 7, 0b1111_0111, 1])));
+        assert!(reader.get_frozen(0x1002, None).is_none());
+        reader.unfreeze(0x1002, Some(bitfield));
+
+        // Fatal errors stop the worker
+        let mut reader = MemoryReader::spawn(MockProcess::new(2));
+        reader.watch(0x1000, 4);
+        assert!(wait(&mut reader, MemoryReader::is_stopped));
+        assert_eq!(reader.last_error(), Some(&ProcessError::NotOpened));
+    }
+}
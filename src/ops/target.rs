@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use sysinfo::{Pid, ProcessStatus, System};
+
+/// The executable a project targets, to find its process again after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    pub name: String,
+    // Unknown when the executable of the process cannot be read
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+impl Target {
+    pub fn of(process: &sysinfo::Process) -> Self {
+        Self {
+            name: process.name().to_string_lossy().into_owned(),
+            path: process.exe().map(|p| p.to_owned()),
+        }
+    }
+
+    /// Whether a process runs the target executable, by path when both are known, by name otherwise.
+    pub fn matches(&self, process: &sysinfo::Process) -> bool {
+        let exited = matches!(
+            process.status(),
+            ProcessStatus::Zombie | ProcessStatus::Dead
+        );
+        if exited || process.thread_kind().is_some() {
+            return false;
+        }
+        match (&self.path, process.exe()) {
+            (Some(path), Some(exe)) => path == exe,
+            _ => process.name().to_string_lossy() == self.name,
+        }
+    }
+
+    /// The most recently started process running the target.
+    pub fn find(&self, system: &System) -> Option<Pid> {
+        system
+            .processes()
+            .iter()
+            .filter(|(_, p)| self.matches(p))
+            .max_by_key(|(pid, p)| (p.start_time(), **pid))
+            .map(|(pid, _)| *pid)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, RefreshKind};
+
+    #[test]
+    fn test_target() {
+        let mut system = System::new_with_specifics(
+            RefreshKind::nothing().with_processes(ProcessRefreshKind::everything()),
+        );
+        let pid = Pid::from_u32(std::process::id());
+        system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        let process = system.process(pid).expect("own process should be listed");
+
+        let target = Target::of(process);
+        assert!(target.matches(process));
+        assert_eq!(target.find(&system), Some(pid));
+
+        // The name is only used when the path is unknown
+        let renamed = Target {
+            name: "renamed".into(),
+            ..target.clone()
+        };
+        assert!(renamed.matches(process));
+        let by_name = Target {
+            path: None,
+            ..target.clone()
+        };
+        assert!(by_name.matches(process));
+        let other = Target {
+            name: "renamed".into(),
+            path: None,
+        };
+        assert!(!other.matches(process));
+        assert_eq!(other.find(&system), None);
+    }
+}
@@ -1,11 +1,25 @@
+use std::path::PathBuf;
 use sysinfo::Pid;
 
+use super::{MemoryRegion, Module, ProcessError};
 use crate::typing::DataType;
 
-use windows_sys::Win32::Foundation::{GetLastError, HANDLE};
+use windows_sys::Win32::Foundation::{
+    CloseHandle, GetLastError, ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER, ERROR_NOACCESS,
+    ERROR_PARTIAL_COPY, HANDLE, HMODULE, MAX_PATH, STILL_ACTIVE,
+};
 use windows_sys::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
+use windows_sys::Win32::System::Memory::{
+    VirtualQueryEx, MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_IMAGE, PAGE_EXECUTE,
+    PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_NOACCESS,
+    PAGE_READWRITE, PAGE_WRITECOPY,
+};
+use windows_sys::Win32::System::ProcessStatus::{
+    EnumProcessModulesEx, GetModuleFileNameExW, GetModuleInformation, LIST_MODULES_ALL, MODULEINFO,
+};
 use windows_sys::Win32::System::Threading::{
-    OpenProcess, PROCESS_VM_OPERATION, PROCESS_VM_READ, PROCESS_VM_WRITE,
+    GetExitCodeProcess, OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_OPERATION,
+    PROCESS_VM_READ, PROCESS_VM_WRITE,
 };
 
 #[derive(Debug, PartialEq, Default)]
@@ -30,6 +44,9 @@ pub struct WinProcess {
     state: State,
 }
 
+// Process handles can be used from any thread, e.g. by a memory reader
+unsafe impl Send for WinProcess {}
+
 impl WinProcess {
     pub fn new(pid: Pid) -> Self {
         Self {
@@ -37,40 +54,67 @@ impl WinProcess {
             state: State::Created,
         }
     }
+
+    fn handle(&self) -> Result<HANDLE, ProcessError> {
+        self.state.handle().ok_or(ProcessError::NotOpened)
+    }
+
+    fn exited(handle: HANDLE) -> bool {
+        let mut exit_code = 0u32;
+        let r = unsafe { GetExitCodeProcess(handle, &mut exit_code) };
+        r != 0 && exit_code != STILL_ACTIVE as u32
+    }
+
+    // Must be called right after the failed call, before anything can overwrite the last error
+    fn last_error(handle: HANDLE) -> ProcessError {
+        let code = unsafe { GetLastError() };
+        match code {
+            ERROR_ACCESS_DENIED => ProcessError::PermissionDenied,
+            _ if Self::exited(handle) => ProcessError::ProcessExited,
+            code => ProcessError::Os(code as i32),
+        }
+    }
+}
+// Errors of the memory functions on unmapped or protected pages
+fn is_fault(code: i32) -> bool {
+    code == ERROR_PARTIAL_COPY as i32 || code == ERROR_NOACCESS as i32
 }
+
 impl super::SystemProcess for WinProcess {
     fn pid(&self) -> Pid {
         self.pid
     }
 
-    fn open(&mut self) -> Result<(), String> {
+    fn open(&mut self) -> Result<(), ProcessError> {
         match self.state {
             State::Created => {
                 let handle = unsafe {
                     OpenProcess(
-                        PROCESS_VM_OPERATION | PROCESS_VM_READ | PROCESS_VM_WRITE,
+                        PROCESS_VM_OPERATION
+                            | PROCESS_VM_READ
+                            | PROCESS_VM_WRITE
+                            | PROCESS_QUERY_INFORMATION,
                         true.into(),
                         self.pid.as_u32(),
                     )
                 };
                 if handle.is_null() {
-                    return Err(format!(
-                        "Could not get an handle on the process, error : {}",
-                        unsafe { GetLastError() }
-                    ));
+                    // OpenProcess fails with an invalid parameter for unknown pids
+                    return Err(match unsafe { GetLastError() } {
+                        ERROR_ACCESS_DENIED => ProcessError::PermissionDenied,
+                        ERROR_INVALID_PARAMETER => ProcessError::ProcessExited,
+                        code => ProcessError::Os(code as i32),
+                    });
                 }
                 self.state = State::Open(handle);
                 Ok(())
             }
-            _ => Err("This process has already been opened.".into()),
+            _ => Err(ProcessError::AlreadyOpened),
         }
     }
 
-    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, String> {
-        let handle = self
-            .state
-            .handle()
-            .ok_or("Handle is closed or not yet opened.")?;
+    fn read_memory(&mut self, location: u64, dt: &impl DataType) -> Result<Vec<u8>, ProcessError> {
+        let handle = self.handle()?;
         let size = dt.get_size();
         let mut read_buffer: Vec<u8> = Vec::with_capacity(size);
         let mut bytes_read = 0usize;
@@ -82,22 +126,26 @@ impl super::SystemProcess for WinProcess {
                 size,
                 &mut bytes_read,
             );
-            if r == 0 {
-                return Err(format!("Could not read memory, error: {}.", GetLastError()));
-            }
-            if bytes_read != size {
-                return Err("Memory read has a smaller size than requested.".to_string());
+            if r == 0 || bytes_read != size {
+                return Err(match Self::last_error(handle) {
+                    ProcessError::Os(code) if is_fault(code) && bytes_read == 0 => {
+                        ProcessError::Unmapped(location)
+                    }
+                    ProcessError::Os(code) if is_fault(code) => ProcessError::PartialRead {
+                        address: location,
+                        read: bytes_read,
+                        requested: size,
+                    },
+                    error => error,
+                });
             }
             read_buffer.set_len(bytes_read);
         };
         Ok(read_buffer)
     }
 
-    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), String> {
-        let handle = self
-            .state
-            .handle()
-            .ok_or("Handle is closed or not yet opened.")?;
+    fn write_memory(&mut self, location: u64, what: Vec<u8>) -> Result<(), ProcessError> {
+        let handle = self.handle()?;
         let size = what.len();
         let mut bytes_written = 0usize;
         unsafe {
@@ -108,20 +156,141 @@ impl super::SystemProcess for WinProcess {
                 size,
                 &mut bytes_written,
             );
-            if r == 0 {
-                return Err(format!(
-                    "Could not write memory, error: {}.",
-                    GetLastError()
-                ));
-            }
-            if bytes_written != size {
-                return Err("Memory written was smaller than requested !".to_string());
+            if r == 0 || bytes_written != size {
+                return Err(match Self::last_error(handle) {
+                    ProcessError::Os(code) if is_fault(code) => {
+                        ProcessError::Unmapped(location + bytes_written as u64)
+                    }
+                    error => error,
+                });
             }
         };
         Ok(())
     }
 
+    fn modules(&self) -> Result<Vec<Module>, ProcessError> {
+        let handle = self.handle()?;
+
+        let mut handles: Vec<HMODULE> = Vec::new();
+        let mut needed = 0u32;
+        // The module list can grow between calls, retry until the buffer is large enough
+        loop {
+            let cb = (handles.len() * std::mem::size_of::<HMODULE>()) as u32;
+            let r = unsafe {
+                EnumProcessModulesEx(
+                    handle,
+                    handles.as_mut_ptr(),
+                    cb,
+                    &mut needed,
+                    LIST_MODULES_ALL,
+                )
+            };
+            if r == 0 {
+                return Err(Self::last_error(handle));
+            }
+            if needed <= cb {
+                handles.truncate(needed as usize / std::mem::size_of::<HMODULE>());
+                break;
+            }
+            handles.resize(
+                needed as usize / std::mem::size_of::<HMODULE>(),
+                std::ptr::null_mut(),
+            );
+        }
+
+        let mut modules = Vec::with_capacity(handles.len());
+        for hmodule in handles {
+            let mut name_buffer = [0u16; MAX_PATH as usize];
+            let mut info: MODULEINFO = unsafe { std::mem::zeroed() };
+            unsafe {
+                let len = GetModuleFileNameExW(
+                    handle,
+                    hmodule,
+                    name_buffer.as_mut_ptr(),
+                    name_buffer.len() as u32,
+                );
+                if len == 0
+                    || GetModuleInformation(
+                        handle,
+                        hmodule,
+                        &mut info,
+                        std::mem::size_of::<MODULEINFO>() as u32,
+                    ) == 0
+                {
+                    continue;
+                }
+                let path = PathBuf::from(String::from_utf16_lossy(&name_buffer[..len as usize]));
+                modules.push(Module {
+                    name: path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    path,
+                    base: info.lpBaseOfDll as u64,
+                    size: u64::from(info.SizeOfImage),
+                });
+            }
+        }
+        Ok(modules)
+    }
+
+    fn regions(&self) -> Result<Vec<MemoryRegion>, ProcessError> {
+        let handle = self.handle()?;
+        let modules = self.modules()?;
+
+        let mut regions = Vec::new();
+        let mut address = 0u64;
+        loop {
+            let mut info: MEMORY_BASIC_INFORMATION = unsafe { std::mem::zeroed() };
+            let written = unsafe {
+                VirtualQueryEx(
+                    handle,
+                    address as *const std::ffi::c_void,
+                    &mut info,
+                    std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+                )
+            };
+            // Fails once past the last region of the address space
+            if written == 0 || info.RegionSize == 0 {
+                break;
+            }
+            let start = info.BaseAddress as u64;
+            let size = info.RegionSize as u64;
+            let protect = info.Protect;
+            if info.State == MEM_COMMIT && protect & (PAGE_NOACCESS | PAGE_GUARD) == 0 {
+                let writable = PAGE_READWRITE | PAGE_WRITECOPY;
+                let executable_writable = PAGE_EXECUTE_READWRITE | PAGE_EXECUTE_WRITECOPY;
+                let executable = PAGE_EXECUTE | PAGE_EXECUTE_READ | executable_writable;
+                regions.push(MemoryRegion {
+                    start,
+                    size,
+                    readable: protect != PAGE_EXECUTE,
+                    writable: protect & (writable | executable_writable) != 0,
+                    executable: protect & executable != 0,
+                    path: (info.Type == MEM_IMAGE)
+                        .then(|| modules.iter().find(|m| m.contains(start)))
+                        .flatten()
+                        .map(|m| m.path.clone()),
+                });
+            }
+            address = match start.checked_add(size) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(regions)
+    }
+
     fn close(&mut self) {
+        if let Some(handle) = self.state.handle() {
+            unsafe { CloseHandle(handle) };
+        }
         self.state = State::Closed;
     }
 }
+
+impl Drop for WinProcess {
+    fn drop(&mut self) {
+        super::SystemProcess::close(self);
+    }
+}
@@ -0,0 +1,732 @@
+/***
+ * Memory scanner
+ * Finds the addresses holding a value, then narrows them down with repeated scans
+ * as the value changes in the target process.
+ */
+
+pub mod pointer;
+pub mod signature;
+
+use std::fmt::Display;
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use crate::ops::{MemoryRegion, ProcessError, SystemProcess};
+use crate::typing::{
+    ArrayDataType, DataType, DataTypeEnum, Endianness, FloatPrecision, IntSize, IntegerDataType,
+};
+
+// Regions are read by chunks, so that an unreadable page only loses its chunk
+const CHUNK_SIZE: usize = 0x10_0000;
+// Results closer than this are refreshed with a single read
+const GROUP_SIZE: u64 = 0x1000;
+
+#[derive(Debug, Clone)]
+pub enum ScanError {
+    NoPreviousScan,
+    UnsupportedType(String),
+    InvalidValue(String),
+    NotFound(String),
+    ProcessError(ProcessError),
+    IoError(String),
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanError::NoPreviousScan => {
+                write!(f, "This scan needs the values of a previous scan.")
+            }
+            ScanError::UnsupportedType(name) => write!(f, "Cannot scan for values of type {name}."),
+            ScanError::InvalidValue(s) => write!(f, "Invalid value: {s}"),
+            ScanError::NotFound(s) => write!(f, "Could not find {s}."),
+            ScanError::ProcessError(s) => write!(f, "Process error: {s}"),
+            ScanError::IoError(s) => write!(f, "IO error: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+impl From<ProcessError> for ScanError {
+    fn from(err: ProcessError) -> Self {
+        ScanError::ProcessError(err)
+    }
+}
+
+/// A decoded primitive value, comparable with the values of the same data type.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum ScanValue {
+    Int(i128),
+    Float(f64),
+}
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    Int {
+        size: usize,
+        signed: bool,
+        endianness: Endianness,
+    },
+    Float {
+        precision: FloatPrecision,
+        endianness: Endianness,
+    },
+}
+
+impl Encoding {
+    fn of(dt: &DataTypeEnum) -> Result<Self, ScanError> {
+        let encoding = match dt {
+            DataTypeEnum::IntegerDataType(i) => Encoding::Int {
+                size: i.get_size(),
+                signed: i.is_signed(),
+                endianness: i.get_endianness(),
+            },
+            DataTypeEnum::EnumDataType(e) => Encoding::Int {
+                size: e.get_size(),
+                signed: true,
+                endianness: e.get_endianness(),
+            },
+            DataTypeEnum::BooleanDataType(b) => Encoding::Int {
+                size: b.get_size(),
+                signed: false,
+                endianness: Endianness::Little,
+            },
+            DataTypeEnum::PointerDataType(p) => Encoding::Int {
+                size: p.get_size(),
+                signed: false,
+                endianness: Endianness::Little,
+            },
+            DataTypeEnum::FloatDataType(f) => Encoding::Float {
+                precision: f.get_precision(),
+                endianness: f.get_endianness(),
+            },
+            _ => return Err(ScanError::UnsupportedType(dt.get_name())),
+        };
+        match encoding {
+            // Empty or wider integers do not fit in an u64
+            Encoding::Int { size, .. } if !(1..=8).contains(&size) => {
+                Err(ScanError::UnsupportedType(dt.get_name()))
+            }
+            _ => Ok(encoding),
+        }
+    }
+
+    fn decode(&self, data: &[u8]) -> ScanValue {
+        match *self {
+            Encoding::Int {
+                size,
+                signed,
+                endianness,
+            } => {
+                let val = match endianness {
+                    Endianness::Little => LittleEndian::read_uint(data, size),
+                    Endianness::Big => BigEndian::read_uint(data, size),
+                };
+                let shift = 64 - 8 * size as u32;
+                ScanValue::Int(match signed {
+                    true => i128::from(((val << shift) as i64) >> shift),
+                    false => i128::from(val),
+                })
+            }
+            Encoding::Float {
+                precision,
+                endianness,
+            } => ScanValue::Float(match (precision, endianness) {
+                (FloatPrecision::Simple, Endianness::Little) => {
+                    f64::from(LittleEndian::read_f32(data))
+                }
+                (FloatPrecision::Simple, Endianness::Big) => f64::from(BigEndian::read_f32(data)),
+                (FloatPrecision::Double, Endianness::Little) => LittleEndian::read_f64(data),
+                (FloatPrecision::Double, Endianness::Big) => BigEndian::read_f64(data),
+            }),
+        }
+    }
+
+    fn encode(&self, value: ScanValue) -> Result<Vec<u8>, ScanError> {
+        match (*self, value) {
+            (
+                Encoding::Int {
+                    size,
+                    signed,
+                    endianness,
+                },
+                ScanValue::Int(value),
+            ) => {
+                let bits = 8 * size as u32;
+                // Signed values can also be given by their bit pattern, as hex values are displayed
+                let min = match signed {
+                    true => -(1i128 << (bits - 1)),
+                    false => 0,
+                };
+                let max = (1i128 << bits) - 1;
+                if value < min || value > max {
+                    return Err(ScanError::InvalidValue(format!(
+                        "{value} does not fit in {size} bytes"
+                    )));
+                }
+                // Two's complement truncated to the size
+                let value = (value as u64) & (u64::MAX >> (64 - bits));
+                let mut data = vec![0; size];
+                match endianness {
+                    Endianness::Little => LittleEndian::write_uint(&mut data, value, size),
+                    Endianness::Big => BigEndian::write_uint(&mut data, value, size),
+                }
+                Ok(data)
+            }
+            (Encoding::Int { .. }, ScanValue::Float(value)) => Err(ScanError::InvalidValue(
+                format!("{value} is not an integer"),
+            )),
+            (
+                Encoding::Float {
+                    precision,
+                    endianness,
+                },
+                value,
+            ) => {
+                let value = match value {
+                    ScanValue::Int(value) => value as f64,
+                    ScanValue::Float(value) => value,
+                };
+                Ok(match (precision, endianness) {
+                    (FloatPrecision::Simple, Endianness::Little) => {
+                        (value as f32).to_le_bytes().to_vec()
+                    }
+                    (FloatPrecision::Simple, Endianness::Big) => {
+                        (value as f32).to_be_bytes().to_vec()
+                    }
+                    (FloatPrecision::Double, Endianness::Little) => value.to_le_bytes().to_vec(),
+                    (FloatPrecision::Double, Endianness::Big) => value.to_be_bytes().to_vec(),
+                })
+            }
+        }
+    }
+}
+
+impl ScanValue {
+    /// Decodes a value of `dt`, which must be a primitive (integer, enum, boolean, pointer or float).
+    pub fn decode(dt: &DataTypeEnum, data: &[u8]) -> Result<Self, ScanError> {
+        if data.len() != dt.get_size() {
+            return Err(ScanError::InvalidValue(format!(
+                "expected {} bytes, got {}",
+                dt.get_size(),
+                data.len()
+            )));
+        }
+        Ok(Encoding::of(dt)?.decode(data))
+    }
+
+    /// Encodes the value as the bytes of `dt`, integers that do not fit in `dt` are rejected.
+    pub fn encode(self, dt: &DataTypeEnum) -> Result<Vec<u8>, ScanError> {
+        Encoding::of(dt)?.encode(self)
+    }
+
+    /// Parses a user value for `dt`, integers can be written in hexadecimal with a `0x` prefix
+    /// and booleans as `true` or `false`.
+    pub fn parse(dt: &DataTypeEnum, s: &str) -> Result<Self, ScanError> {
+        let s = s.trim();
+        let invalid = || ScanError::InvalidValue(s.into());
+        if let DataTypeEnum::BooleanDataType(_) = dt {
+            match s {
+                "true" => return Ok(ScanValue::Int(1)),
+                "false" => return Ok(ScanValue::Int(0)),
+                _ => {}
+            }
+        }
+        match Encoding::of(dt)? {
+            Encoding::Int { .. } => {
+                let (negative, digits) = match s.strip_prefix('-') {
+                    Some(digits) => (true, digits),
+                    None => (false, s),
+                };
+                let value = match digits
+                    .strip_prefix("0x")
+                    .or_else(|| digits.strip_prefix("0X"))
+                {
+                    Some(hex) => i128::from_str_radix(hex, 16),
+                    None => digits.parse::<i128>(),
+                }
+                .map_err(|_| invalid())?;
+                Ok(ScanValue::Int(if negative { -value } else { value }))
+            }
+            Encoding::Float { .. } => s.parse().map(ScanValue::Float).map_err(|_| invalid()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanType {
+    Exact(ScanValue),
+    /// Inclusive range
+    Between(ScanValue, ScanValue),
+    /// Remembers every value, for a value that can only be compared to its previous state
+    Unknown,
+    Changed,
+    Unchanged,
+    Increased,
+    Decreased,
+}
+
+impl ScanType {
+    /// An exact scan for a user value. Floats match when they round to the given
+    /// number of decimals, as displayed values are rounded.
+    pub fn exact(dt: &DataTypeEnum, s: &str) -> Result<Self, ScanError> {
+        match ScanValue::parse(dt, s)? {
+            ScanValue::Float(value) => {
+                let decimals = s.trim().split_once('.').map_or(0, |(_, d)| d.len());
+                let tolerance = 0.5 * 10f64.powi(-(decimals as i32));
+                Ok(ScanType::Between(
+                    ScanValue::Float(value - tolerance),
+                    ScanValue::Float(value + tolerance),
+                ))
+            }
+            value => Ok(ScanType::Exact(value)),
+        }
+    }
+
+    fn needs_previous(&self) -> bool {
+        matches!(
+            self,
+            ScanType::Changed | ScanType::Unchanged | ScanType::Increased | ScanType::Decreased
+        )
+    }
+
+    fn matches(&self, value: ScanValue, previous: Option<ScanValue>) -> bool {
+        match (self, previous) {
+            (ScanType::Exact(v), _) => value == *v,
+            (ScanType::Between(low, high), _) => *low <= value && value <= *high,
+            (ScanType::Unknown, _) => true,
+            (ScanType::Changed, Some(p)) => value != p,
+            (ScanType::Unchanged, Some(p)) => value == p,
+            (ScanType::Increased, Some(p)) => value > p,
+            (ScanType::Decreased, Some(p)) => value < p,
+            (_, None) => false,
+        }
+    }
+}
+
+/// An address matching the scans, with its value at the last scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanResult {
+    pub address: u64,
+    pub value: Vec<u8>,
+}
+
+// Contiguous readable memory
+#[derive(Debug, Clone)]
+struct Block {
+    start: u64,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+enum ScanState {
+    #[default]
+    Empty,
+    // Whole memory copy, after an unknown initial value scan
+    Snapshot(Vec<Block>),
+    Results(Vec<ScanResult>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    datatype: DataTypeEnum,
+    alignment: usize,
+    writable_only: bool,
+    modules_only: bool,
+    state: ScanState,
+}
+
+impl Scanner {
+    /// Creates a scanner for values of `dt`, aligned on their size by default.
+    pub fn new(dt: DataTypeEnum) -> Self {
+        Self {
+            alignment: dt.get_size().clamp(1, 8),
+            datatype: dt,
+            writable_only: true,
+            modules_only: false,
+            state: ScanState::Empty,
+        }
+    }
+
+    pub fn get_datatype(&self) -> &DataTypeEnum {
+        &self.datatype
+    }
+    /// Changes the scanned type, which starts a new scan.
+    pub fn set_datatype(&mut self, dt: DataTypeEnum) {
+        self.datatype = dt;
+        self.reset();
+    }
+
+    pub fn get_alignment(&self) -> usize {
+        self.alignment
+    }
+    pub fn set_alignment(&mut self, alignment: usize) {
+        self.alignment = alignment.max(1);
+    }
+    pub fn with_alignment(mut self, alignment: usize) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
+
+    pub fn is_writable_only(&self) -> bool {
+        self.writable_only
+    }
+    pub fn set_writable_only(&mut self, writable_only: bool) {
+        self.writable_only = writable_only;
+    }
+    pub fn with_writable_only(mut self, writable_only: bool) -> Self {
+        self.set_writable_only(writable_only);
+        self
+    }
+
+    pub fn is_modules_only(&self) -> bool {
+        self.modules_only
+    }
+    pub fn set_modules_only(&mut self, modules_only: bool) {
+        self.modules_only = modules_only;
+    }
+    pub fn with_modules_only(mut self, modules_only: bool) -> Self {
+        self.set_modules_only(modules_only);
+        self
+    }
+
+    /// Forgets the previous scans, the next scan is a first scan.
+    pub fn reset(&mut self) {
+        self.state = ScanState::Empty;
+    }
+
+    pub fn is_first_scan(&self) -> bool {
+        matches!(self.state, ScanState::Empty)
+    }
+
+    /// Results of the last scan, empty after an unknown initial value scan.
+    pub fn results(&self) -> &[ScanResult] {
+        match &self.state {
+            ScanState::Results(results) => results,
+            _ => &[],
+        }
+    }
+
+    /// Number of candidate addresses, which is every aligned address after an unknown initial value scan.
+    pub fn result_count(&self) -> usize {
+        match &self.state {
+            ScanState::Empty => 0,
+            ScanState::Snapshot(blocks) => blocks
+                .iter()
+                .map(|b| self.offsets(b.start, b.data.len()).count())
+                .sum(),
+            ScanState::Results(results) => results.len(),
+        }
+    }
+
+    /// Runs a first scan over the selected regions, or narrows down the results of the previous scan.
+    /// Returns the number of remaining results.
+    pub fn scan(
+        &mut self,
+        process: &mut impl SystemProcess,
+        scan_type: ScanType,
+    ) -> Result<usize, ScanError> {
+        let encoding = Encoding::of(&self.datatype)?;
+        self.state = match std::mem::take(&mut self.state) {
+            ScanState::Empty if scan_type.needs_previous() => {
+                return Err(ScanError::NoPreviousScan)
+            }
+            ScanState::Empty if scan_type == ScanType::Unknown => {
+                ScanState::Snapshot(self.read_regions(process)?)
+            }
+            ScanState::Empty => {
+                // Only the matches are kept, not the memory they were found in
+                let mut results = Vec::new();
+                let overlap = self.datatype.get_size().saturating_sub(1);
+                for region in process.regions()?.iter().filter(|r| self.is_scanned(r)) {
+                    for_each_block(process, region.start, region.size as usize, overlap, |b| {
+                        results.extend(self.filter_block(b, &encoding, scan_type, None));
+                    });
+                }
+                ScanState::Results(results)
+            }
+            ScanState::Snapshot(previous) => {
+                let mut results = Vec::new();
+                let overlap = self.datatype.get_size().saturating_sub(1);
+                for old in &previous {
+                    for_each_block(process, old.start, old.data.len(), overlap, |new| {
+                        let offset = (new.start - old.start) as usize;
+                        let old_data = &old.data[offset..offset + new.data.len()];
+                        results.extend(self.filter_block(
+                            new,
+                            &encoding,
+                            scan_type,
+                            Some(old_data),
+                        ));
+                    });
+                }
+                ScanState::Results(results)
+            }
+            ScanState::Results(previous) => {
+                ScanState::Results(self.refresh(process, &previous, &encoding, scan_type))
+            }
+        };
+        Ok(self.result_count())
+    }
+
+    // Copy of the scanned regions, the values an unknown initial value scan compares to
+    fn read_regions(&self, process: &mut impl SystemProcess) -> Result<Vec<Block>, ScanError> {
+        let regions = process.regions()?;
+        Ok(regions
+            .iter()
+            .filter(|r| self.is_scanned(r))
+            .flat_map(|r| read_blocks(process, r.start, r.size as usize))
+            .collect())
+    }
+
+    fn is_scanned(&self, region: &MemoryRegion) -> bool {
+        region.readable
+            && (!self.writable_only || region.writable)
+            && (!self.modules_only || region.path.is_some())
+    }
+
+    // Offsets in a block of the aligned addresses which can hold a value
+    fn offsets(&self, start: u64, len: usize) -> impl Iterator<Item = usize> {
+        let size = self.datatype.get_size().max(1);
+        let alignment = self.alignment as u64;
+        let first = ((alignment - start % alignment) % alignment) as usize;
+        (first..(len + 1).saturating_sub(size)).step_by(self.alignment)
+    }
+
+    fn filter_block(
+        &self,
+        block: &Block,
+        encoding: &Encoding,
+        scan_type: ScanType,
+        previous: Option<&[u8]>,
+    ) -> Vec<ScanResult> {
+        let size = self.datatype.get_size();
+        self.offsets(block.start, block.data.len())
+            .filter_map(|offset| {
+                let data = &block.data[offset..offset + size];
+                let old = previous.map(|p| encoding.decode(&p[offset..offset + size]));
+                scan_type
+                    .matches(encoding.decode(data), old)
+                    .then(|| ScanResult {
+                        address: block.start + offset as u64,
+                        value: data.to_vec(),
+                    })
+            })
+            .collect()
+    }
+
+    fn refresh(
+        &self,
+        process: &mut impl SystemProcess,
+        previous: &[ScanResult],
+        encoding: &Encoding,
+        scan_type: ScanType,
+    ) -> Vec<ScanResult> {
+        let size = self.datatype.get_size();
+        let mut results = Vec::new();
+        let mut remaining = previous;
+        while let Some(first) = remaining.first() {
+            // Results are sorted by address, read the close ones together
+            let count = remaining
+                .iter()
+                .take_while(|r| r.address + size as u64 <= first.address + GROUP_SIZE)
+                .count()
+                .max(1);
+            let (group, rest) = remaining.split_at(count);
+            remaining = rest;
+
+            let last = group.last().expect("groups are not empty");
+            let len = (last.address - first.address) as usize + size;
+            for block in read_blocks(process, first.address, len) {
+                for result in group {
+                    let Some(offset) = result.address.checked_sub(block.start) else {
+                        continue;
+                    };
+                    let Some(data) = block.data.get(offset as usize..offset as usize + size) else {
+                        continue;
+                    };
+                    let old = encoding.decode(&result.value);
+                    if scan_type.matches(encoding.decode(data), Some(old)) {
+                        results.push(ScanResult {
+                            address: result.address,
+                            value: data.to_vec(),
+                        });
+                    }
+                }
+            }
+        }
+        results
+    }
+}
+
+fn read_bytes(
+    process: &mut impl SystemProcess,
+    address: u64,
+    len: usize,
+) -> Result<Vec<u8>, ProcessError> {
+    let bytes = IntegerDataType::default().with_size(IntSize::Integer8);
+    process.read_memory(address, &ArrayDataType::new(bytes.into(), len))
+}
+
+// Reads a range as readable blocks, skipping the unreadable pages
+fn read_blocks(process: &mut impl SystemProcess, start: u64, len: usize) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut offset = 0;
+    while offset < len {
+        let chunk_len = CHUNK_SIZE.min(len - offset);
+        let Ok(chunk) = process.read_range(start + offset as u64, chunk_len) else {
+            break;
+        };
+        for (address, data) in chunk.valid_runs() {
+            match blocks.last_mut() {
+                Some(b) if b.start + b.data.len() as u64 == address => b.data.extend(data),
+                _ => blocks.push(Block {
+                    start: address,
+                    data: data.to_vec(),
+                }),
+            }
+        }
+        offset += chunk_len;
+    }
+    blocks
+}
+
+// Calls `f` with the readable memory of a range a chunk at a time, instead of collecting it.
+// Each block starts with the last `overlap` bytes of the previous one when they are contiguous,
+// so that values straddling two chunks are seen once.
+fn for_each_block(
+    process: &mut impl SystemProcess,
+    start: u64,
+    len: usize,
+    overlap: usize,
+    mut f: impl FnMut(&Block),
+) {
+    let mut tail: Option<Block> = None;
+    let mut offset = 0;
+    while offset < len {
+        let chunk_len = CHUNK_SIZE.min(len - offset);
+        let Ok(chunk) = process.read_range(start + offset as u64, chunk_len) else {
+            break;
+        };
+        for (address, data) in chunk.valid_runs() {
+            let mut block = match tail.take() {
+                Some(mut t) if t.start + t.data.len() as u64 == address => {
+                    t.data.extend_from_slice(data);
+                    t
+                }
+                _ => Block {
+                    start: address,
+                    data: data.to_vec(),
+                },
+            };
+            f(&block);
+            let skipped = block.data.len() - overlap.min(block.data.len());
+            block.data.drain(..skipped);
+            block.start += skipped as u64;
+            tail = Some(block);
+        }
+        offset += chunk_len;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::typing::{BooleanDataType, FloatDataType};
+
+    #[test]
+    fn test_scan_value() {
+        let int: DataTypeEnum = IntegerDataType::default().with_signed(true).into();
+        let data = (-5i32).to_le_bytes();
+        assert_eq!(ScanValue::decode(&int, &data).unwrap(), ScanValue::Int(-5));
+        assert_eq!(ScanValue::parse(&int, "0x10").unwrap(), ScanValue::Int(16));
+        assert_eq!(ScanValue::Int(-5).encode(&int).unwrap(), data);
+        let byte: DataTypeEnum = IntegerDataType::default()
+            .with_size(IntSize::Integer8)
+            .into();
+        assert_eq!(ScanValue::Int(255).encode(&byte).unwrap(), vec![255]);
+        assert!(ScanValue::Int(256).encode(&byte).is_err());
+        assert_eq!(ScanValue::Int(0xFFFFFFFF).encode(&int).unwrap(), [0xFF; 4]);
+        assert!(ScanValue::Float(1.5).encode(&byte).is_err());
+        // Booleans of debug information can have any size
+        for size in [0, 16] {
+            let boolean: DataTypeEnum = BooleanDataType::default().with_size(size).into();
+            let data = vec![0; size];
+            assert!(matches!(
+                ScanValue::decode(&boolean, &data),
+                Err(ScanError::UnsupportedType(_))
+            ));
+        }
+
+        let float: DataTypeEnum = FloatDataType::default().into();
+        let scan = ScanType::exact(&float, "1.5").unwrap();
+        assert_eq!(
+            ScanValue::Float(1.5).encode(&float).unwrap(),
+            1.5f32.to_le_bytes()
+        );
+        let value = ScanValue::decode(&float, &1.52f32.to_le_bytes()).unwrap();
+        assert!(scan.matches(value, None));
+        assert!(!scan.matches(ScanValue::Float(1.6), None));
+    }
+
+    #[test]
+    fn test_unknown_then_narrow() {
+        use crate::ops::MockProcess;
+
+        let mut data = vec![0u8; 0x20];
+        data[0x8] = 100;
+        data[0x10] = 50;
+        let mut process = MockProcess::new(1)
+            .with_region(0x1000, data, true, false)
+            .with_region(0x2000, vec![100; 0x10], false, false);
+        process.open().unwrap();
+
+        let dt: DataTypeEnum = IntegerDataType::default().into();
+        let mut scanner = Scanner::new(dt);
+        assert!(matches!(
+            scanner.scan(&mut process, ScanType::Decreased),
+            Err(ScanError::NoPreviousScan)
+        ));
+        // Only the writable region, every 4 bytes
+        assert_eq!(scanner.scan(&mut process, ScanType::Unknown).unwrap(), 8);
+
+        process.write_memory(0x1008, vec![90]).unwrap();
+        process.write_memory(0x1010, vec![60]).unwrap();
+        assert_eq!(scanner.scan(&mut process, ScanType::Decreased).unwrap(), 1);
+        assert_eq!(scanner.results()[0].address, 0x1008);
+        assert_eq!(scanner.results()[0].value, vec![90, 0, 0, 0]);
+
+        process.write_memory(0x1008, vec![95]).unwrap();
+        let between = ScanType::Between(ScanValue::Int(91), ScanValue::Int(99));
+        assert_eq!(scanner.scan(&mut process, between).unwrap(), 1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_scan_own_process() {
+        use crate::ops::Process;
+
+        let pid = sysinfo::Pid::from_u32(std::process::id());
+        let mut process = Process::new(pid);
+        process.open().unwrap();
+
+        let mut target = Box::new(0x1234_5678_9ABC_u64);
+        let address = &*target as *const u64 as u64;
+        let dt: DataTypeEnum = IntegerDataType::default()
+            .with_size(IntSize::Integer64)
+            .into();
+        let mut scanner = Scanner::new(dt.clone());
+
+        let value = ScanType::exact(&dt, "0x123456789ABC").unwrap();
+        scanner.scan(&mut process, value).unwrap();
+        assert!(scanner.results().iter().any(|r| r.address == address));
+
+        *target += 1;
+        std::hint::black_box(&target);
+        scanner.scan(&mut process, ScanType::Increased).unwrap();
+        assert!(scanner.results().iter().any(|r| r.address == address));
+        scanner.scan(&mut process, ScanType::Unchanged).unwrap();
+        assert!(scanner.results().iter().any(|r| r.address == address));
+    }
+}
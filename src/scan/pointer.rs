@@ -0,0 +1,452 @@
+/***
+ * Pointer scanner
+ * Finds chains of pointers from static module addresses to a dynamic address, so that
+ * heap objects can be found again in later sessions.
+ */
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{read_blocks, read_bytes, ScanError};
+use crate::ops::{MemoryRegion, Module, ProcessError, SystemProcess};
+
+/// A pointer chain rooted in a module, written `[[game.exe+0x1234]+0x10]+0x8`:
+/// the pointer stored at `game.exe+0x1234` is read, `0x10` is added, the pointer
+/// stored there is read, and `0x8` is added to get the final address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PointerPath {
+    pub module: String,
+    pub module_offset: u64,
+    // Added after each dereference
+    pub offsets: Vec<i64>,
+}
+
+impl PointerPath {
+    /// Follows the chain in `process`, `pointer_size` being 4 or 8 bytes.
+    pub fn resolve(
+        &self,
+        process: &mut impl SystemProcess,
+        modules: &[Module],
+        pointer_size: usize,
+    ) -> Result<u64, ScanError> {
+        let module = modules
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(&self.module))
+            .ok_or_else(|| ScanError::NotFound(format!("module {}", self.module)))?;
+        let mut address = module.base + self.module_offset;
+        for offset in &self.offsets {
+            let pointer = read_pointer(process, address, pointer_size)?;
+            address = pointer.wrapping_add_signed(*offset);
+        }
+        Ok(address)
+    }
+
+    /// Follows the chain with `read_pointer`, e.g. over memory read by another thread.
+    pub fn resolve_with(
+        &self,
+        modules: &[Module],
+        mut read_pointer: impl FnMut(u64) -> Option<u64>,
+    ) -> Option<u64> {
+        let module = modules
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(&self.module))?;
+        let mut address = module.base + self.module_offset;
+        for offset in &self.offsets {
+            address = read_pointer(address)?.wrapping_add_signed(*offset);
+        }
+        Some(address)
+    }
+}
+
+fn format_offset(offset: i64) -> String {
+    match offset {
+        ..0 => format!("-{:#x}", offset.unsigned_abs()),
+        _ => format!("+{offset:#x}"),
+    }
+}
+
+fn parse_offset(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let digits = digits.trim();
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(digits);
+    let value = i64::from_str_radix(digits, 16).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+impl Display for PointerPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}+{:#x}",
+            "[".repeat(self.offsets.len()),
+            self.module,
+            self.module_offset
+        )?;
+        for offset in &self.offsets {
+            write!(f, "]{}", format_offset(*offset))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PointerPath {
+    type Err = ScanError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ScanError::InvalidValue(format!("invalid pointer path {s}"));
+        let s = s.trim();
+        let rest = s.trim_start_matches('[');
+        let depth = s.len() - rest.len();
+        let mut parts = rest.split(']');
+        let base = parts.next().ok_or_else(invalid)?;
+        let (module, module_offset) = base.rsplit_once('+').ok_or_else(invalid)?;
+        let module_offset = parse_offset(module_offset)
+            .and_then(|o| u64::try_from(o).ok())
+            .ok_or_else(invalid)?;
+        let offsets = parts
+            .map(|o| parse_offset(o).ok_or_else(invalid))
+            .collect::<Result<Vec<_>, _>>()?;
+        if offsets.len() != depth || module.trim().is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            module: module.trim().into(),
+            module_offset,
+            offsets,
+        })
+    }
+}
+
+/// Pointer paths found for an address, kept to be checked again after the target restarts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PointerScanResults {
+    pub target: u64,
+    pub pointer_size: usize,
+    pub paths: Vec<PointerPath>,
+    // The scan stopped expanding a level at the node limit, some paths may be missing
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl PointerScanResults {
+    pub fn save(&self, path: &Path) -> Result<(), ScanError> {
+        let file = std::fs::File::create(path).map_err(|e| ScanError::IoError(e.to_string()))?;
+        ron::ser::to_writer_pretty(file, self, ron::ser::PrettyConfig::default())
+            .map_err(|e| ScanError::IoError(e.to_string()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ScanError> {
+        let file = std::fs::File::open(path).map_err(|e| ScanError::IoError(e.to_string()))?;
+        ron::de::from_reader(file).map_err(|e| ScanError::IoError(e.to_string()))
+    }
+
+    /// Keeps the paths which still resolve, to `target` when given (e.g. the address
+    /// of the object found again after a restart), or to readable memory otherwise.
+    /// Returns the number of remaining paths.
+    pub fn revalidate(&mut self, process: &mut impl SystemProcess, target: Option<u64>) -> usize {
+        let modules = process.modules().unwrap_or_default();
+        let pointer_size = self.pointer_size;
+        self.paths.retain(
+            |path| match (path.resolve(process, &modules, pointer_size), target) {
+                (Ok(address), Some(target)) => address == target,
+                (Ok(address), None) => read_bytes(process, address, 1).is_ok(),
+                (Err(_), _) => false,
+            },
+        );
+        if let Some(target) = target {
+            self.target = target;
+        }
+        self.paths.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PointerScanner {
+    max_depth: usize,
+    max_offset: u64,
+    max_results: usize,
+    // Pointers followed per level, bounds the memory and time of deep scans
+    max_nodes: usize,
+    pointer_size: usize,
+}
+
+impl Default for PointerScanner {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_offset: 0x1000,
+            max_results: 10_000,
+            max_nodes: 1_000_000,
+            pointer_size: 8,
+        }
+    }
+}
+
+// Static ranges are the modules, with their zero initialized data (.bss) which is an
+// anonymous mapping following the module on Linux
+struct StaticRange<'a> {
+    module: &'a Module,
+    end: u64,
+}
+
+impl PointerScanner {
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth.max(1);
+    }
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.set_max_depth(max_depth);
+        self
+    }
+
+    pub fn get_max_offset(&self) -> u64 {
+        self.max_offset
+    }
+    pub fn set_max_offset(&mut self, max_offset: u64) {
+        self.max_offset = max_offset;
+    }
+    pub fn with_max_offset(mut self, max_offset: u64) -> Self {
+        self.set_max_offset(max_offset);
+        self
+    }
+
+    pub fn get_max_results(&self) -> usize {
+        self.max_results
+    }
+    pub fn set_max_results(&mut self, max_results: usize) {
+        self.max_results = max_results;
+    }
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.set_max_results(max_results);
+        self
+    }
+
+    pub fn get_max_nodes(&self) -> usize {
+        self.max_nodes
+    }
+    pub fn set_max_nodes(&mut self, max_nodes: usize) {
+        self.max_nodes = max_nodes.max(1);
+    }
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.set_max_nodes(max_nodes);
+        self
+    }
+
+    pub fn get_pointer_size(&self) -> usize {
+        self.pointer_size
+    }
+    pub fn set_pointer_size(&mut self, pointer_size: usize) {
+        self.pointer_size = pointer_size;
+    }
+    pub fn with_pointer_size(mut self, pointer_size: usize) -> Self {
+        self.set_pointer_size(pointer_size);
+        self
+    }
+
+    /// Finds the pointer paths from a static address to `target`, the shortest first.
+    pub fn scan(
+        &self,
+        process: &mut impl SystemProcess,
+        target: u64,
+    ) -> Result<PointerScanResults, ScanError> {
+        if !matches!(self.pointer_size, 4 | 8) {
+            return Err(ScanError::InvalidValue(format!(
+                "pointer size {}",
+                self.pointer_size
+            )));
+        }
+        let regions = process.regions()?;
+        let modules = process.modules()?;
+        let statics: Vec<StaticRange> = modules
+            .iter()
+            .map(|module| {
+                let end = module.base + module.size;
+                let bss = regions
+                    .iter()
+                    .find(|r| r.start == end && r.path.is_none() && r.writable);
+                StaticRange {
+                    module,
+                    end: bss.map_or(end, MemoryRegion::end),
+                }
+            })
+            .collect();
+
+        let pointers = self.pointer_map(process, &regions);
+        let mut results = PointerScanResults {
+            target,
+            pointer_size: self.pointer_size,
+            paths: Vec::new(),
+            truncated: false,
+        };
+        // Breadth first, so that the shortest paths are found before the results limit
+        let mut level: Vec<(u64, Vec<i64>)> = vec![(target, Vec::new())];
+        for _ in 0..self.max_depth {
+            let mut next_level = Vec::new();
+            // A location reached again on the same level only adds longer variants of its paths
+            let mut visited = HashSet::new();
+            for (address, offsets) in &level {
+                let low = address.saturating_sub(self.max_offset);
+                let first = pointers.partition_point(|&(value, _)| value < low);
+                for &(value, location) in pointers[first..]
+                    .iter()
+                    .take_while(|&&(value, _)| value <= *address)
+                {
+                    let mut path_offsets = vec![(address - value) as i64];
+                    path_offsets.extend(offsets);
+                    match statics
+                        .iter()
+                        .find(|s| s.module.base <= location && location < s.end)
+                    {
+                        Some(s) => {
+                            results.paths.push(PointerPath {
+                                module: s.module.name.clone(),
+                                module_offset: location - s.module.base,
+                                offsets: path_offsets,
+                            });
+                            if results.paths.len() >= self.max_results {
+                                return Ok(results);
+                            }
+                        }
+                        None if next_level.len() >= self.max_nodes => results.truncated = true,
+                        None => {
+                            if visited.insert(location) {
+                                next_level.push((location, path_offsets));
+                            }
+                        }
+                    }
+                }
+            }
+            level = next_level;
+        }
+        Ok(results)
+    }
+
+    // Every aligned pointer of the writable memory pointing to readable memory, as (value, location) sorted by value
+    fn pointer_map(
+        &self,
+        process: &mut impl SystemProcess,
+        regions: &[MemoryRegion],
+    ) -> Vec<(u64, u64)> {
+        let readable: Vec<(u64, u64)> = regions
+            .iter()
+            .filter(|r| r.readable)
+            .map(|r| (r.start, r.end()))
+            .collect();
+        let is_mapped = |value: u64| {
+            let idx = readable.partition_point(|&(start, _)| start <= value);
+            idx > 0 && value < readable[idx - 1].1
+        };
+
+        let mut pointers = Vec::new();
+        for region in regions.iter().filter(|r| r.readable && r.writable) {
+            for block in read_blocks(process, region.start, region.size as usize) {
+                for (i, bytes) in block.data.chunks_exact(self.pointer_size).enumerate() {
+                    let value = decode_pointer(bytes);
+                    if value != 0 && is_mapped(value) {
+                        pointers.push((value, block.start + (i * self.pointer_size) as u64));
+                    }
+                }
+            }
+        }
+        pointers.sort_unstable();
+        pointers
+    }
+}
+
+fn decode_pointer(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &b| (acc << 8) | u64::from(b))
+}
+
+fn read_pointer(
+    process: &mut impl SystemProcess,
+    address: u64,
+    size: usize,
+) -> Result<u64, ProcessError> {
+    read_bytes(process, address, size).map(|bytes| decode_pointer(&bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pointer_path_format() {
+        let path = PointerPath {
+            module: "game.exe".into(),
+            module_offset: 0x1234,
+            offsets: vec![0x10, -0x8],
+        };
+        let s = path.to_string();
+        assert_eq!(s, "[[game.exe+0x1234]+0x10]-0x8");
+        assert_eq!(s.parse::<PointerPath>().unwrap(), path);
+        assert!("[game.exe+0x10".parse::<PointerPath>().is_err());
+    }
+
+    #[test]
+    fn test_pointer_path_resolve_with() {
+        let path: PointerPath = "[[game.exe+0x10]+0x8]-0x4".parse().unwrap();
+        let modules = [Module {
+            name: "game.exe".into(),
+            base: 0x1000,
+            size: 0x100,
+            path: "game.exe".into(),
+        }];
+        let memory = std::collections::HashMap::from([(0x1010, 0x5000), (0x5008, 0x6000)]);
+        let read = |address| memory.get(&address).copied();
+        assert_eq!(path.resolve_with(&modules, read), Some(0x5FFC));
+        assert_eq!(path.resolve_with(&[], read), None);
+        let broken: PointerPath = "[[game.exe+0x20]+0x8]-0x4".parse().unwrap();
+        assert_eq!(broken.resolve_with(&modules, read), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pointer_scan_own_process() {
+        use crate::ops::Process;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static ROOT: AtomicUsize = AtomicUsize::new(0);
+        let leaf = Box::new([0u64; 4]);
+        let node = Box::new([0usize, 0, &*leaf as *const _ as usize]);
+        ROOT.store(&*node as *const _ as usize, Ordering::SeqCst);
+        let target = &leaf[1] as *const u64 as u64;
+
+        let pid = sysinfo::Pid::from_u32(std::process::id());
+        let mut process = Process::new(pid);
+        process.open().unwrap();
+        let scanner = PointerScanner::default()
+            .with_max_depth(2)
+            .with_max_offset(0x40);
+        let mut results = scanner.scan(&mut process, target).unwrap();
+        let modules = process.modules().unwrap();
+        let root_path = results
+            .paths
+            .iter()
+            .find(|p| p.offsets == [0x10, 0x8])
+            .expect("the static root is found");
+        assert_eq!(
+            root_path.resolve(&mut process, &modules, 8).unwrap(),
+            target
+        );
+
+        results.revalidate(&mut process, Some(target));
+        assert!(results.paths.iter().any(|p| p.offsets == [0x10, 0x8]));
+        drop((leaf, node));
+    }
+}
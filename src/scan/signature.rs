@@ -0,0 +1,292 @@
+/***
+ * Byte signatures
+ * IDA-style patterns (`48 8B 05 ?? ?? ?? ??`) used to find code again after the target is updated,
+ * and the static addresses referenced by the matched instructions.
+ */
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::{read_blocks, read_bytes, ScanError};
+use crate::ops::SystemProcess;
+
+/// A byte pattern, each byte is matched under a mask so that nibbles can be wildcards (`4?`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    // (value, mask)
+    bytes: Vec<(u8, u8)>,
+}
+
+impl Pattern {
+    /// Parses space separated bytes in hexadecimal, `?` and `??` are whole byte wildcards.
+    pub fn parse(s: &str) -> Result<Self, ScanError> {
+        let bytes = s
+            .split_whitespace()
+            .map(|token| {
+                let invalid = || ScanError::InvalidValue(format!("invalid pattern byte {token}"));
+                if token == "?" || token == "??" {
+                    return Ok((0, 0));
+                }
+                let mut chars = token.chars();
+                let (Some(high), Some(low), None) = (chars.next(), chars.next(), chars.next())
+                else {
+                    return Err(invalid());
+                };
+                let nibble = |c: char| match c {
+                    '?' => Some((0, 0)),
+                    c => c.to_digit(16).map(|d| (d as u8, 0xF)),
+                };
+                let ((high, high_mask), (low, low_mask)) = (
+                    nibble(high).ok_or_else(invalid)?,
+                    nibble(low).ok_or_else(invalid)?,
+                );
+                Ok(((high << 4) | low, (high_mask << 4) | low_mask))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if bytes.is_empty() {
+            return Err(ScanError::InvalidValue("empty pattern".into()));
+        }
+        Ok(Self { bytes })
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.bytes.len()
+            && self
+                .bytes
+                .iter()
+                .zip(data)
+                .all(|(&(value, mask), &b)| b & mask == value)
+    }
+
+    /// Offsets of all the matches in `data`.
+    pub fn find_all(&self, data: &[u8]) -> Vec<usize> {
+        (0..(data.len() + 1).saturating_sub(self.len()))
+            .filter(|&offset| self.matches(&data[offset..]))
+            .collect()
+    }
+
+    /// Addresses of all the matches in a range of the process memory.
+    pub fn scan_range(&self, process: &mut impl SystemProcess, start: u64, len: usize) -> Vec<u64> {
+        read_blocks(process, start, len)
+            .into_iter()
+            .flat_map(|block| {
+                self.find_all(&block.data)
+                    .into_iter()
+                    .map(move |offset| block.start + offset as u64)
+            })
+            .collect()
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tokens: Vec<String> = self
+            .bytes
+            .iter()
+            .map(|&(value, mask)| {
+                let nibble = |v: u8, m: u8| match m {
+                    0 => '?',
+                    _ => char::from_digit(u32::from(v), 16)
+                        .unwrap_or('?')
+                        .to_ascii_uppercase(),
+                };
+                format!(
+                    "{}{}",
+                    nibble(value >> 4, mask >> 4),
+                    nibble(value & 0xF, mask & 0xF)
+                )
+            })
+            .collect();
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+/// Location of a 32-bit displacement relative to the end of its instruction (x86-64 RIP-relative addressing).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RipRelative {
+    // Offset of the displacement from the start of the match
+    pub operand_offset: usize,
+    // Length of the instruction starting at the match
+    pub instruction_length: usize,
+}
+
+/// A pattern searched in a module, resolving to the matched address, or to the
+/// static address referenced by the matched instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pattern: String,
+    // Module to search, all the executable regions otherwise
+    module: Option<String>,
+    rip_relative: Option<RipRelative>,
+    // Added to the resolved address
+    offset: i64,
+}
+
+impl Signature {
+    pub fn new(pattern: &str) -> Result<Self, ScanError> {
+        Ok(Self {
+            pattern: Pattern::parse(pattern)?.to_string(),
+            module: None,
+            rip_relative: None,
+            offset: 0,
+        })
+    }
+
+    pub fn get_pattern(&self) -> Pattern {
+        Pattern::parse(&self.pattern).expect("signature patterns are validated")
+    }
+
+    pub fn get_module(&self) -> Option<&String> {
+        self.module.as_ref()
+    }
+    pub fn set_module(&mut self, module: Option<String>) {
+        self.module = module;
+    }
+    pub fn with_module(mut self, module: &str) -> Self {
+        self.set_module(Some(module.into()));
+        self
+    }
+
+    pub fn get_rip_relative(&self) -> Option<RipRelative> {
+        self.rip_relative
+    }
+    pub fn set_rip_relative(&mut self, rip_relative: Option<RipRelative>) {
+        self.rip_relative = rip_relative;
+    }
+    pub fn with_rip_relative(mut self, operand_offset: usize, instruction_length: usize) -> Self {
+        self.set_rip_relative(Some(RipRelative {
+            operand_offset,
+            instruction_length,
+        }));
+        self
+    }
+
+    pub fn get_offset(&self) -> i64 {
+        self.offset
+    }
+    pub fn set_offset(&mut self, offset: i64) {
+        self.offset = offset;
+    }
+    pub fn with_offset(mut self, offset: i64) -> Self {
+        self.set_offset(offset);
+        self
+    }
+
+    /// Addresses of all the matches of the pattern.
+    pub fn find(&self, process: &mut impl SystemProcess) -> Result<Vec<u64>, ScanError> {
+        let pattern = self.get_pattern();
+        let module = match &self.module {
+            Some(name) => {
+                let modules = process.modules()?;
+                let module = modules
+                    .into_iter()
+                    .find(|m| m.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| ScanError::NotFound(format!("module {name}")))?;
+                Some(module)
+            }
+            None => None,
+        };
+        // Modules have gaps between their segments, only scan their mapped regions
+        let ranges: Vec<(u64, u64)> = process
+            .regions()?
+            .into_iter()
+            .filter(|r| r.readable)
+            .filter(|r| match &module {
+                Some(m) => r.start < m.base + m.size && m.base < r.end(),
+                None => r.executable,
+            })
+            .map(|r| match &module {
+                Some(m) => {
+                    let start = r.start.max(m.base);
+                    (start, r.end().min(m.base + m.size) - start)
+                }
+                None => (r.start, r.size),
+            })
+            .collect();
+        Ok(ranges
+            .into_iter()
+            .flat_map(|(start, size)| pattern.scan_range(process, start, size as usize))
+            .collect())
+    }
+
+    /// Resolves the signature with its first match.
+    pub fn resolve(&self, process: &mut impl SystemProcess) -> Result<u64, ScanError> {
+        let address = *self
+            .find(process)?
+            .first()
+            .ok_or_else(|| ScanError::NotFound(format!("pattern {}", self.pattern)))?;
+        self.resolve_match(process, address)
+    }
+
+    /// Applies the RIP-relative extraction and the offset to a match of the pattern.
+    pub fn resolve_match(
+        &self,
+        process: &mut impl SystemProcess,
+        address: u64,
+    ) -> Result<u64, ScanError> {
+        let address = match self.rip_relative {
+            Some(rip) => {
+                let operand = address + rip.operand_offset as u64;
+                let displacement = read_bytes(process, operand, 4)?;
+                let displacement =
+                    i32::from_le_bytes(displacement.try_into().expect("displacements are 4 bytes"));
+                (address + rip.instruction_length as u64).wrapping_add_signed(displacement.into())
+            }
+            None => address,
+        };
+        Ok(address.wrapping_add_signed(self.offset))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pattern() {
+        let pattern = Pattern::parse("48 8b 05 ?? ? 4? ?0").unwrap();
+        assert_eq!(pattern.to_string(), "48 8B 05 ?? ?? 4? ?0");
+        assert!(pattern.matches(&[0x48, 0x8B, 0x05, 0x12, 0x34, 0x4F, 0xA0]));
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x05, 0x12, 0x34, 0x5F, 0xA0]));
+        assert!(!pattern.matches(&[0x48, 0x8B, 0x05, 0x12, 0x34, 0x4F, 0xA1]));
+        assert!(Pattern::parse("48 8G").is_err());
+
+        let data = [0x90, 0x48, 0x8B, 0x05, 0, 0, 0x40, 0x10, 0x48];
+        assert_eq!(pattern.find_all(&data), vec![1]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_rip_relative() {
+        use crate::ops::Process;
+
+        // mov rax, [rip - 0x10] ; ret
+        let code: Box<[u8]> = Box::new([0x48, 0x8B, 0x05, 0xF0, 0xFF, 0xFF, 0xFF, 0xC3, 0x5A]);
+        let start = code.as_ptr() as u64;
+
+        let pid = sysinfo::Pid::from_u32(std::process::id());
+        let mut process = Process::new(pid);
+        process.open().unwrap();
+        let signature = Signature::new("48 8B 05 ?? ?? ?? ?? C3 5A")
+            .unwrap()
+            .with_rip_relative(3, 7)
+            .with_offset(8);
+        let matches = signature
+            .get_pattern()
+            .scan_range(&mut process, start, code.len());
+        assert_eq!(matches, vec![start]);
+        assert_eq!(
+            signature.resolve_match(&mut process, start).unwrap(),
+            start + 7 - 0x10 + 8
+        );
+    }
+}
@@ -0,0 +1,84 @@
+/***
+ * User settings, kept between sessions in the configuration directory
+ */
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/*
+ * From ReClassEx : https://github.com/ajkhoury/ReClassEx/blob/master/ReClass/DialogProcSelect.cpp
+ */
+#[cfg(target_os = "windows")]
+#[rustfmt::skip]
+const DEFAULT_IGNORED_PROCESSES: &[&str] = &[
+    "svchost.exe", "System", "conhost.exe", "wininit.exe", "smss.exe", "winint.exe", "wlanext.exe", "Code.exe", "taskhostw.exe", "SearchIndexer.exe", "Idle", "SearchApp.exe", "dllhost.exe",
+    "spoolsv.exe", "notepad.exe", "explorer.exe", "itunes.exe",
+    "sqlservr.exe", "nvtray.exe","nvxdsync.exe", "lsass.exe", "jusched.exe",
+    "chrome.exe", "firefox.exe", "winamp.exe", "TrustedInstaller.exe",
+    "WinRAR.exe", "calc.exe", "taskhostex.exe", "Taskmgr.exe","dwm.exe","SpotifyWebHelper.exe",
+    "plugin-container.exe", "services.exe", "devenv.exe", "flux.exe", "skype.exe", "spotify.exe",
+    "csrss.exe", "taskeng.exe", "spotifyhelper.exe", "vcpkgsrv.exe", "msbuild.exe", "cmd.exe", "taskhost.exe",
+    "SettingSyncHost.exe", "SkyDrive.exe", "ctfmon.exe", "RuntimeBroker.exe", "BTTray.exe", "BTStackServer.exe",
+    "Bluetooth Headset Helper.exe", "winlogon.exe", "PnkBstrA.exe", "armsvc.exe", "MSIAfterburner.exe", "vmnat.exe",
+    "vmware-authd.exe", "vmnetdhcp.exe", "pia_manager.exe", "Dropbox.exe", "Viber.exe", "idaq.exe",
+    "idaq64.exe", "CoreSync.exe", "SpotifyCrashService.exe", "RzSynapse.exe", "acrotray.exe",
+    "CCLibrary.exe", "pia_tray.exe", "rubyw.exe", "netsession_win.exe", "NvBackend.exe", "TeamViewer_Service.exe",
+    "DisplayFusionHookAppWIN6032.exe", "DisplayFusionHookAppWIN6064.exe", "GameScannerService.exe", "AdobeUpdateService.exe",
+    "steamwebhelper.exe", "c2c_service.exe", "Sync Server.exe", "NvNetworkService.exe", "Creative Cloud.exe", "foobar2000.exe",
+    "code.exe", "ReClass.exe", "ReClass64.exe", "Discord.exe", "node.exe", "TeamViewer.exe", "Everything.exe",
+];
+#[cfg(not(target_os = "windows"))]
+#[rustfmt::skip]
+const DEFAULT_IGNORED_PROCESSES: &[&str] = &[
+    "systemd", "systemd-journald", "systemd-udevd", "systemd-logind", "systemd-resolved",
+    "dbus-daemon", "dbus-broker", "polkitd", "udisksd", "upowerd", "NetworkManager", "wpa_supplicant",
+    "pipewire", "pipewire-pulse", "wireplumber", "pulseaudio", "gvfsd", "at-spi-bus-launcher",
+    "at-spi2-registryd", "xdg-desktop-portal", "xdg-document-portal", "xdg-permission-store",
+    "sshd", "cron", "crond", "agetty", "login",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Process names hidden from the process selection dialog
+    pub ignored_processes: Vec<String>,
+    // Attach again when the target process exits and restarts
+    pub auto_reattach: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ignored_processes: DEFAULT_IGNORED_PROCESSES
+                .iter()
+                .map(|&s| s.to_owned())
+                .collect(),
+            auto_reattach: true,
+        }
+    }
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("rs_class").join("settings.ron"))
+    }
+
+    /// Loads the saved settings, the defaults when there are none yet.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path().filter(|p| p.exists()) else {
+            return Ok(Self::default());
+        };
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        ron::de::from_reader(file).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("no configuration directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        ron::ser::to_writer_pretty(file, self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+    }
+}
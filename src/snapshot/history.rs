@@ -0,0 +1,115 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+const DEFAULT_CAPACITY: usize = 256;
+
+#[derive(Debug, Default)]
+struct Field {
+    // Oldest first
+    samples: VecDeque<(Instant, Vec<u8>)>,
+    last_change: Option<Instant>,
+}
+
+/// Bounded history of the bytes of the fields of a struct, one sample per read of the process.
+#[derive(Debug)]
+pub struct FieldHistory {
+    capacity: usize,
+    // By field offset
+    fields: HashMap<usize, Field>,
+}
+
+impl Default for FieldHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl FieldHistory {
+    /// Keeps at most `capacity` samples per field.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds the bytes read at `time` for the field at `offset`, returns whether they changed.
+    /// Reads older or as old as the last sample are ignored, so a read can be recorded every frame.
+    /// The history of the field restarts when its size changes, e.g. after it was retyped.
+    pub fn record(&mut self, offset: usize, bytes: &[u8], time: Instant) -> bool {
+        let field = self.fields.entry(offset).or_default();
+        if field
+            .samples
+            .back()
+            .is_some_and(|(_, last)| last.len() != bytes.len())
+        {
+            *field = Field::default();
+        }
+        let changed = match field.samples.back() {
+            Some((last, _)) if *last >= time => return false,
+            Some((_, last_bytes)) => last_bytes.as_slice() != bytes,
+            None => false,
+        };
+        if changed {
+            field.last_change = Some(time);
+        }
+        if field.samples.len() == self.capacity {
+            field.samples.pop_front();
+        }
+        field.samples.push_back((time, bytes.to_vec()));
+        changed
+    }
+
+    /// Time of the last change of a field, the first sample is not a change.
+    pub fn last_change(&self, offset: usize) -> Option<Instant> {
+        self.fields.get(&offset)?.last_change
+    }
+
+    /// Samples of a field, oldest first.
+    pub fn samples(&self, offset: usize) -> impl Iterator<Item = (Instant, &[u8])> {
+        self.fields.get(&offset).into_iter().flat_map(|f| {
+            f.samples
+                .iter()
+                .map(|(time, bytes)| (*time, bytes.as_slice()))
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_field_history() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut history = FieldHistory::new(3);
+        assert!(!history.record(0, &[1], at(0)));
+        assert!(!history.record(0, &[1], at(10)));
+        // The same read recorded again
+        assert!(!history.record(0, &[2], at(10)));
+        assert!(history.record(0, &[2], at(20)));
+        assert_eq!(history.last_change(0), Some(at(20)));
+        assert_eq!(history.last_change(4), None);
+
+        history.record(0, &[3], at(30));
+        let samples: Vec<_> = history.samples(0).collect();
+        assert_eq!(
+            samples,
+            vec![(at(10), &[1][..]), (at(20), &[2]), (at(30), &[3])]
+        );
+        // Retyped field
+        assert!(!history.record(0, &[3, 0], at(40)));
+        assert_eq!(history.samples(0).count(), 1);
+        assert_eq!(history.last_change(0), None);
+    }
+}
@@ -0,0 +1,295 @@
+/***
+ * Memory snapshots
+ * Copies of process memory taken at different times, compared to find which bytes,
+ * and which fields of a struct, changed in between.
+ */
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ops::{MemoryRegion, SystemProcess};
+use crate::typing::{DataType, DataTypeEnum, StructDataType};
+
+mod history;
+pub use history::FieldHistory;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotBlock {
+    pub start: u64,
+    pub data: Vec<u8>,
+}
+
+impl SnapshotBlock {
+    pub fn end(&self) -> u64 {
+        self.start + self.data.len() as u64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    // Seconds since the epoch
+    pub timestamp: u64,
+    blocks: Vec<SnapshotBlock>,
+}
+
+/// Consecutive bytes that differ between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteChange {
+    pub address: u64,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+/// A changed field of a struct instance, or bytes not covered by any field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    // Dotted path through nested structs, None for padding and undefined bytes
+    pub field: Option<String>,
+    pub offset: usize,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+impl Snapshot {
+    /// A snapshot of known bytes, e.g. loaded from a dump.
+    pub fn from_bytes(start: u64, data: Vec<u8>) -> Self {
+        Self {
+            timestamp: now(),
+            blocks: vec![SnapshotBlock { start, data }],
+        }
+    }
+
+    /// Captures the readable parts of `regions`.
+    pub fn capture_regions(process: &mut impl SystemProcess, regions: &[MemoryRegion]) -> Self {
+        let mut snapshot = Self {
+            timestamp: now(),
+            blocks: Vec::new(),
+        };
+        for region in regions.iter().filter(|r| r.readable) {
+            snapshot.capture(process, region.start, region.size as usize);
+        }
+        snapshot
+    }
+
+    /// Captures a range of memory, unreadable pages are left out.
+    pub fn capture_range(process: &mut impl SystemProcess, start: u64, len: usize) -> Self {
+        let mut snapshot = Self {
+            timestamp: now(),
+            blocks: Vec::new(),
+        };
+        snapshot.capture(process, start, len);
+        snapshot
+    }
+
+    /// Captures one instance of a struct.
+    pub fn capture_struct(
+        process: &mut impl SystemProcess,
+        address: u64,
+        s: &StructDataType,
+    ) -> Self {
+        Self::capture_range(process, address, s.get_size())
+    }
+
+    fn capture(&mut self, process: &mut impl SystemProcess, start: u64, len: usize) {
+        let Ok(read) = process.read_range(start, len) else {
+            return;
+        };
+        for (address, data) in read.valid_runs() {
+            match self.blocks.last_mut() {
+                Some(b) if b.end() == address => b.data.extend(data),
+                _ => self.blocks.push(SnapshotBlock {
+                    start: address,
+                    data: data.to_vec(),
+                }),
+            }
+        }
+    }
+
+    pub fn blocks(&self) -> &[SnapshotBlock] {
+        &self.blocks
+    }
+
+    /// Bytes captured at `address`, if the whole range was captured.
+    pub fn read(&self, address: u64, len: usize) -> Option<&[u8]> {
+        let block = self
+            .blocks
+            .iter()
+            .find(|b| b.start <= address && address + len as u64 <= b.end())?;
+        let offset = (address - block.start) as usize;
+        Some(&block.data[offset..offset + len])
+    }
+
+    /// Changed bytes between this snapshot and a later one, on the ranges captured by both.
+    pub fn diff(&self, later: &Snapshot) -> Vec<ByteChange> {
+        let mut changes: Vec<ByteChange> = Vec::new();
+        for old in &self.blocks {
+            for new in &later.blocks {
+                let start = old.start.max(new.start);
+                let end = old.end().min(new.end());
+                for address in start..end {
+                    let o = old.data[(address - old.start) as usize];
+                    let n = new.data[(address - new.start) as usize];
+                    if o == n {
+                        continue;
+                    }
+                    match changes.last_mut() {
+                        Some(c) if c.address + c.old.len() as u64 == address => {
+                            c.old.push(o);
+                            c.new.push(n);
+                        }
+                        _ => changes.push(ByteChange {
+                            address,
+                            old: vec![o],
+                            new: vec![n],
+                        }),
+                    }
+                }
+            }
+        }
+        changes.sort_by_key(|c| c.address);
+        changes
+    }
+
+    /// Changes between two snapshots of a struct instance at `address`, by field.
+    /// Fields are reported whole with their old and new values, other bytes by contiguous runs.
+    pub fn diff_struct(
+        &self,
+        later: &Snapshot,
+        address: u64,
+        s: &StructDataType,
+    ) -> Vec<FieldChange> {
+        let mut fields: Vec<FieldChange> = Vec::new();
+        for change in self.diff(later) {
+            for i in 0..change.old.len() {
+                let changed = change.address + i as u64;
+                let Some(offset) = changed
+                    .checked_sub(address)
+                    .map(|o| o as usize)
+                    .filter(|&o| o < s.get_size())
+                else {
+                    continue;
+                };
+                match (field_at(s, offset), fields.last_mut()) {
+                    // Fields are reported whole, once
+                    (Some((name, field_offset, _)), Some(last))
+                        if last.field.as_ref() == Some(&name) && last.offset == field_offset => {}
+                    (Some((name, field_offset, size)), _) => {
+                        let field_address = address + field_offset as u64;
+                        if let (Some(old), Some(new)) = (
+                            self.read(field_address, size),
+                            later.read(field_address, size),
+                        ) {
+                            fields.push(FieldChange {
+                                field: Some(name),
+                                offset: field_offset,
+                                old: old.to_vec(),
+                                new: new.to_vec(),
+                            });
+                        }
+                    }
+                    (None, Some(last))
+                        if last.field.is_none() && last.offset + last.old.len() == offset =>
+                    {
+                        last.old.push(change.old[i]);
+                        last.new.push(change.new[i]);
+                    }
+                    (None, _) => fields.push(FieldChange {
+                        field: None,
+                        offset,
+                        old: vec![change.old[i]],
+                        new: vec![change.new[i]],
+                    }),
+                }
+            }
+        }
+        fields
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        ron::ser::to_writer(file, self).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        ron::de::from_reader(file).map_err(|e| e.to_string())
+    }
+}
+
+// Innermost field covering `offset`, as (dotted path, offset in the outer struct, size)
+fn field_at(s: &StructDataType, offset: usize) -> Option<(String, usize, usize)> {
+    let entry = s
+        .get_entries()
+        .iter()
+        .find(|e| e.get_offset() <= offset && offset < e.get_offset() + e.get_size())?;
+    if let DataTypeEnum::StructDataType(inner) = entry.get_datatype() {
+        if let Some((path, o, size)) = field_at(inner, offset - entry.get_offset()) {
+            return Some((
+                format!("{}.{path}", entry.get_name()),
+                entry.get_offset() + o,
+                size,
+            ));
+        }
+    }
+    Some((
+        entry.get_name().clone(),
+        entry.get_offset(),
+        entry.get_size(),
+    ))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::typing::{IntegerDataType, StructEntry};
+
+    #[test]
+    fn test_diff() {
+        let old = Snapshot::from_bytes(0x1000, vec![0, 1, 2, 3, 4, 5]);
+        let new = Snapshot::from_bytes(0x1002, vec![2, 9, 9, 5, 6]);
+        let changes = old.diff(&new);
+        assert_eq!(
+            changes,
+            vec![ByteChange {
+                address: 0x1003,
+                old: vec![3, 4],
+                new: vec![9, 9],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_struct() {
+        let int = || IntegerDataType::default().into();
+        let mut position = StructDataType::new("Position".into(), vec![]);
+        position.push_entry(StructEntry::new("x".into(), int()));
+        position.push_entry(StructEntry::new("y".into(), int()));
+        let mut player = StructDataType::new("Player".into(), vec![]);
+        player.push_entry(StructEntry::new("health".into(), int()));
+        player.push_entry(StructEntry::new("position".into(), position.into()));
+        let player = player.with_size(16);
+
+        let old = Snapshot::from_bytes(0x100, vec![0; 16]);
+        let mut data = vec![0; 16];
+        data[8] = 1; // position.y
+        data[9] = 1;
+        data[13] = 7; // trailing padding
+        let new = Snapshot::from_bytes(0x100, data);
+
+        let changes = old.diff_struct(&new, 0x100, &player);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field.as_deref(), Some("position.y"));
+        assert_eq!(changes[0].offset, 8);
+        assert_eq!(changes[0].new, vec![1, 1, 0, 0]);
+        assert_eq!(changes[1].field, None);
+        assert_eq!(changes[1].offset, 13);
+    }
+}
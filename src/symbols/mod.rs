@@ -0,0 +1,264 @@
+/***
+ * Symbol tables and address <-> name resolution
+ */
+
+pub mod module;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ops::{Module, SystemProcess};
+use crate::typing::{DataType, DataTypeEnum};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    // Offset from the base of the module
+    pub rva: u64,
+    // 0 when unknown
+    pub size: u64,
+}
+
+/// Symbols of a single module, sorted by address.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new(symbols: Vec<Symbol>) -> Self {
+        let mut table = Self::default();
+        table.extend(symbols);
+        table
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Adds symbols to the table, the first symbol seen at an address is kept.
+    pub fn extend(&mut self, symbols: impl IntoIterator<Item = Symbol>) {
+        self.symbols.extend(symbols);
+        self.symbols.sort_by_key(|s| s.rva);
+        self.symbols.dedup_by_key(|s| s.rva);
+    }
+
+    /// Finds the symbol covering `rva`, and the offset of `rva` in it.
+    pub fn lookup(&self, rva: u64) -> Option<(&Symbol, u64)> {
+        let idx = self
+            .symbols
+            .partition_point(|s| s.rva <= rva)
+            .checked_sub(1)?;
+        let symbol = &self.symbols[idx];
+        let offset = rva - symbol.rva;
+        if symbol.size != 0 && offset >= symbol.size {
+            return None;
+        }
+        Some((symbol, offset))
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|s| s.name == name)
+    }
+}
+
+/// Loads the export and symbol tables of modules, from their file when it is readable
+/// and from their image in memory otherwise. Slow for big modules, meant for a worker thread.
+pub fn load_module_symbols(
+    modules: &[Module],
+    process: &mut impl SystemProcess,
+) -> Vec<(String, SymbolTable)> {
+    let mut tables = Vec::new();
+    for m in modules {
+        match module::load_file(&m.path).or_else(|_| module::load_memory(process, m)) {
+            Ok(table) => tables.push((m.name.clone(), table)),
+            Err(e) => eprintln!("WARNING: No symbols for {}: {e}", m.name),
+        }
+    }
+    tables
+}
+
+/// Resolves addresses of a process to `module!symbol+offset`, and back.
+/// Symbol tables are indexed by module name without its extension,
+/// so that `game.pdb` symbols apply to `game.exe`.
+#[derive(Debug, Default)]
+pub struct Symbolizer {
+    modules: Vec<Module>,
+    tables: HashMap<String, SymbolTable>,
+}
+
+impl Symbolizer {
+    pub fn set_modules(&mut self, modules: Vec<Module>) {
+        self.modules = modules;
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Adds symbols to the table of `module`, which can be a module or a symbol file name.
+    pub fn add_symbols(&mut self, module: &str, symbols: SymbolTable) {
+        self.tables
+            .entry(table_key(module))
+            .or_default()
+            .extend(symbols.symbols);
+    }
+
+    pub fn table(&self, module: &str) -> Option<&SymbolTable> {
+        self.tables.get(&table_key(module))
+    }
+
+    pub fn module_at(&self, address: u64) -> Option<&Module> {
+        self.modules.iter().find(|m| m.contains(address))
+    }
+
+    /// Formats an address as `module!symbol+0x10`, or `module+0x1234` when no symbol covers it.
+    pub fn symbolize(&self, address: u64) -> Option<String> {
+        let module = self.module_at(address)?;
+        let rva = address - module.base;
+        let symbol = self.table(&module.name).and_then(|t| t.lookup(rva));
+        Some(match symbol {
+            Some((symbol, 0)) => format!("{}!{}", module.name, symbol.name),
+            Some((symbol, offset)) => format!("{}!{}+{offset:#x}", module.name, symbol.name),
+            None => format!("{}+{rva:#x}", module.name),
+        })
+    }
+
+    /// Formats the value of a pointer field (data, vtable or function pointer)
+    /// followed by the symbol it points to, e.g. `0x7F12345678 (libc.so.6!malloc)`.
+    pub fn format_pointer(&self, dt: &DataTypeEnum, data: &[u8]) -> Option<String> {
+        let DataTypeEnum::PointerDataType(pointer) = dt else {
+            return None;
+        };
+        let value = pointer.bytes_to_string(data).ok()?;
+        let address = pointer.address(data)?;
+        Some(match self.symbolize(address) {
+            Some(symbol) => format!("{value} ({symbol})"),
+            None => value,
+        })
+    }
+
+    /// Resolves `module!symbol`, `module+0x10` or `module!symbol+0x10` to an address.
+    pub fn resolve(&self, expression: &str) -> Option<u64> {
+        let expression = expression.trim();
+        // Symbol names may contain a '+' themselves (operator+)
+        let (base, offset) = expression
+            .rsplit_once('+')
+            .and_then(|(base, offset)| Some((base.trim(), parse_offset(offset.trim())?)))
+            .unwrap_or((expression, 0));
+        let address = match base.split_once('!') {
+            Some((module_name, symbol_name)) => {
+                let module = self.module_by_name(module_name)?;
+                module.base + self.table(module_name)?.find_by_name(symbol_name)?.rva
+            }
+            None => self.module_by_name(base)?.base,
+        };
+        address.checked_add(offset)
+    }
+
+    fn module_by_name(&self, name: &str) -> Option<&Module> {
+        self.modules
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name))
+            .or_else(|| {
+                self.modules
+                    .iter()
+                    .find(|m| table_key(&m.name) == table_key(name))
+            })
+    }
+}
+
+fn table_key(module: &str) -> String {
+    let name = std::path::Path::new(module)
+        .file_name()
+        .map_or_else(|| module.into(), |n| n.to_string_lossy());
+    // Shared libraries are versioned after their extension (libc.so.6)
+    let shared_object = name
+        .find(".so")
+        .filter(|&idx| matches!(name[idx + 3..].chars().next(), None | Some('.')));
+    let stem = match shared_object {
+        Some(idx) => &name[..idx],
+        None => name.rsplit_once('.').map_or(&*name, |(stem, _)| stem),
+    };
+    stem.to_lowercase()
+}
+
+fn parse_offset(s: &str) -> Option<u64> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => u64::from_str_radix(s, 16).ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn symbolizer() -> Symbolizer {
+        let mut s = Symbolizer::default();
+        s.set_modules(vec![Module {
+            name: "game.exe".into(),
+            path: PathBuf::from("game.exe"),
+            base: 0x40_0000,
+            size: 0x1_0000,
+        }]);
+        s.add_symbols(
+            "game.pdb",
+            SymbolTable::new(vec![
+                Symbol {
+                    name: "main".into(),
+                    rva: 0x1000,
+                    size: 0x20,
+                },
+                Symbol {
+                    name: "g_player".into(),
+                    rva: 0x8000,
+                    size: 0,
+                },
+            ]),
+        );
+        s
+    }
+
+    #[test]
+    fn test_symbolize() {
+        let s = symbolizer();
+        assert_eq!(s.symbolize(0x40_1000).unwrap(), "game.exe!main");
+        assert_eq!(s.symbolize(0x40_1010).unwrap(), "game.exe!main+0x10");
+        assert_eq!(s.symbolize(0x40_1030).unwrap(), "game.exe+0x1030");
+        assert_eq!(s.symbolize(0x40_8008).unwrap(), "game.exe!g_player+0x8");
+        assert!(s.symbolize(0x50_0000).is_none());
+    }
+
+    #[test]
+    fn test_format_pointer() {
+        use crate::typing::{FunctionDataType, PointerDataType};
+        let s = symbolizer();
+        let f = FunctionDataType::new("main".into(), "int".into());
+        let p: DataTypeEnum = PointerDataType::new(f.into()).with_size(8).into();
+        let data = 0x40_1000u64.to_le_bytes();
+        assert_eq!(
+            s.format_pointer(&p, &data).unwrap(),
+            "0x401000 (game.exe!main)"
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let s = symbolizer();
+        assert_eq!(s.resolve("game.exe!main+0x10"), Some(0x40_1010));
+        assert_eq!(s.resolve("game+1030"), Some(0x40_1030));
+        assert_eq!(s.resolve("game.exe!g_player"), Some(0x40_8000));
+        assert_eq!(s.resolve("other.dll!main"), None);
+    }
+}
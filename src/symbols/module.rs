@@ -0,0 +1,352 @@
+/***
+ * Export and symbol tables of executable images
+ * ELF `.symtab`/`.dynsym` and PE export tables, read from the file on disk or from the mapped image.
+ */
+
+use std::path::Path;
+
+use object::{Object, ObjectSegment, ObjectSymbol, SymbolKind};
+
+use super::{Symbol, SymbolTable};
+use crate::interop::ImportError;
+use crate::ops::{Module, SystemProcess};
+use crate::typing::{ArrayDataType, IntSize, IntegerDataType};
+
+const PAGE_SIZE: u64 = 0x1000;
+
+/// Loads the symbols of an executable image file, relative to the base the image is mapped at.
+pub fn load_file(path: &Path) -> Result<SymbolTable, ImportError> {
+    let data = std::fs::read(path)?;
+    let file = object::File::parse(&*data)?;
+
+    let mut symbols = Vec::new();
+    match file.format() {
+        object::BinaryFormat::Elf => {
+            // Modules start at the page of their first segment
+            let base = file.segments().map(|s| s.address()).min().unwrap_or(0) & !(PAGE_SIZE - 1);
+            for symbol in file.symbols().chain(file.dynamic_symbols()) {
+                if !symbol.is_definition()
+                    || !matches!(symbol.kind(), SymbolKind::Text | SymbolKind::Data)
+                {
+                    continue;
+                }
+                let Ok(name) = symbol.name() else {
+                    continue;
+                };
+                if name.is_empty() || symbol.address() < base {
+                    continue;
+                }
+                symbols.push(Symbol {
+                    name: name.into(),
+                    rva: symbol.address() - base,
+                    size: symbol.size(),
+                });
+            }
+        }
+        object::BinaryFormat::Pe => {
+            let base = file.relative_address_base();
+            for export in file.exports()? {
+                symbols.push(Symbol {
+                    name: String::from_utf8_lossy(export.name()).into_owned(),
+                    rva: export.address().wrapping_sub(base),
+                    size: 0,
+                });
+            }
+        }
+        format => {
+            return Err(ImportError::UnsupportedError(format!(
+                "{format:?} images are not supported"
+            )))
+        }
+    }
+    Ok(SymbolTable::new(symbols))
+}
+
+/// Loads the symbols of a module from its image in the process memory,
+/// for modules whose file is not available (deleted, other machine, packed).
+pub fn load_memory(
+    process: &mut impl SystemProcess,
+    module: &Module,
+) -> Result<SymbolTable, ImportError> {
+    let mut image = Image { process, module };
+    let magic = image.read(0, 4)?;
+    if magic.starts_with(b"MZ") {
+        pe_exports(&mut image)
+    } else if magic == b"\x7fELF" {
+        elf_dynamic_symbols(&mut image)
+    } else {
+        Err(ImportError::UnsupportedError(format!(
+            "{} is not an ELF or PE image",
+            module.name
+        )))
+    }
+}
+
+struct Image<'a, P: SystemProcess> {
+    process: &'a mut P,
+    module: &'a Module,
+}
+
+impl<P: SystemProcess> Image<'_, P> {
+    fn read_at(&mut self, address: u64, len: usize) -> Result<Vec<u8>, ImportError> {
+        let bytes = IntegerDataType::default().with_size(IntSize::Integer8);
+        self.process
+            .read_memory(address, &ArrayDataType::new(bytes.into(), len))
+            .map_err(|err| ImportError::IoError(err.to_string()))
+    }
+
+    fn read(&mut self, rva: u64, len: usize) -> Result<Vec<u8>, ImportError> {
+        self.read_at(self.module.base + rva, len)
+    }
+}
+
+fn truncated() -> ImportError {
+    ImportError::ParseError("truncated image header".into())
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16, ImportError> {
+    let bytes = data.get(offset..offset + 2).ok_or_else(truncated)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, ImportError> {
+    let bytes = data.get(offset..offset + 4).ok_or_else(truncated)?;
+    Ok(u32::from_le_bytes(
+        bytes.try_into().expect("slice of 4 bytes"),
+    ))
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Result<u64, ImportError> {
+    let bytes = data.get(offset..offset + 8).ok_or_else(truncated)?;
+    Ok(u64::from_le_bytes(
+        bytes.try_into().expect("slice of 8 bytes"),
+    ))
+}
+
+fn c_str_at(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..)?;
+    let end = bytes.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+fn pe_exports(image: &mut Image<impl SystemProcess>) -> Result<SymbolTable, ImportError> {
+    let dos_header = image.read(0, 0x40)?;
+    let nt_offset = u64::from(u32_at(&dos_header, 0x3C)?);
+    let nt_headers = image.read(nt_offset, 0x108)?;
+    if !nt_headers.starts_with(b"PE\0\0") {
+        return Err(ImportError::ParseError("invalid PE signature".into()));
+    }
+    // Data directories follow the optional header fields, which are wider in PE32+
+    let optional_header = 24;
+    let data_directories = match u16_at(&nt_headers, optional_header)? {
+        0x10b => optional_header + 96,
+        0x20b => optional_header + 112,
+        magic => {
+            return Err(ImportError::ParseError(format!(
+                "unknown optional header magic {magic:#x}"
+            )))
+        }
+    };
+    let export_rva = u32_at(&nt_headers, data_directories)?;
+    let export_size = u32_at(&nt_headers, data_directories + 4)?;
+    if export_rva == 0 || export_size == 0 {
+        return Ok(SymbolTable::default());
+    }
+
+    // Names and their arrays are stored in the export directory by the linkers
+    let exports = image.read(u64::from(export_rva), export_size as usize)?;
+    let local = |rva: u32| rva.checked_sub(export_rva).map(|o| o as usize);
+    let names_count = u32_at(&exports, 24)?;
+    let functions = u32_at(&exports, 28)?;
+    let names = u32_at(&exports, 32)?;
+    let ordinals = u32_at(&exports, 36)?;
+    let (Some(functions), Some(names), Some(ordinals)) =
+        (local(functions), local(names), local(ordinals))
+    else {
+        return Err(ImportError::UnsupportedError(
+            "export arrays outside of the export directory".into(),
+        ));
+    };
+
+    let mut symbols = Vec::new();
+    for i in 0..names_count as usize {
+        let name_rva = u32_at(&exports, names + 4 * i)?;
+        let ordinal = usize::from(u16_at(&exports, ordinals + 2 * i)?);
+        let function_rva = u32_at(&exports, functions + 4 * ordinal)?;
+        // Forwarders point to a "dll.function" string in the export directory
+        if local(function_rva).is_some_and(|o| o < exports.len()) {
+            continue;
+        }
+        let Some(name) = local(name_rva).and_then(|o| c_str_at(&exports, o)) else {
+            continue;
+        };
+        symbols.push(Symbol {
+            name,
+            rva: u64::from(function_rva),
+            size: 0,
+        });
+    }
+    Ok(SymbolTable::new(symbols))
+}
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const DT_NULL: u64 = 0;
+const DT_HASH: u64 = 4;
+const DT_STRTAB: u64 = 5;
+const DT_SYMTAB: u64 = 6;
+const DT_STRSZ: u64 = 10;
+const DT_SYMENT: u64 = 11;
+
+/// Reads `.dynsym` through the dynamic segment, the only symbol table that is mapped.
+fn elf_dynamic_symbols(image: &mut Image<impl SystemProcess>) -> Result<SymbolTable, ImportError> {
+    let header = image.read(0, 0x40)?;
+    let is_64 = match header[4] {
+        1 => false,
+        2 => true,
+        _ => return Err(ImportError::ParseError("invalid ELF class".into())),
+    };
+    if header[5] != 1 {
+        return Err(ImportError::UnsupportedError(
+            "big endian ELF images are not supported".into(),
+        ));
+    }
+    let word = |data: &[u8], offset: usize| -> Result<u64, ImportError> {
+        if is_64 {
+            u64_at(data, offset)
+        } else {
+            u32_at(data, offset).map(u64::from)
+        }
+    };
+    let word_size = if is_64 { 8 } else { 4 };
+
+    let (phoff, phentsize, phnum) = if is_64 {
+        (
+            u64_at(&header, 0x20)?,
+            u16_at(&header, 0x36)?,
+            u16_at(&header, 0x38)?,
+        )
+    } else {
+        let phoff = u64::from(u32_at(&header, 0x1C)?);
+        (phoff, u16_at(&header, 0x2A)?, u16_at(&header, 0x2C)?)
+    };
+    let program_headers = image.read(phoff, usize::from(phentsize) * usize::from(phnum))?;
+
+    let mut first_load = None;
+    let mut dynamic = None;
+    for ph in program_headers.chunks_exact(usize::from(phentsize)) {
+        let (vaddr, memsz) = if is_64 {
+            (u64_at(ph, 16)?, u64_at(ph, 40)?)
+        } else {
+            (u64::from(u32_at(ph, 8)?), u64::from(u32_at(ph, 20)?))
+        };
+        match u32_at(ph, 0)? {
+            PT_LOAD => first_load = Some(first_load.map_or(vaddr, |v: u64| v.min(vaddr))),
+            PT_DYNAMIC => dynamic = Some((vaddr, memsz)),
+            _ => {}
+        }
+    }
+    let (Some(first_load), Some((dynamic_vaddr, dynamic_size))) = (first_load, dynamic) else {
+        return Ok(SymbolTable::default());
+    };
+    // Difference between the addresses in the image and the mapped ones, 0 for non PIE executables
+    let bias = image
+        .module
+        .base
+        .wrapping_sub(first_load & !(PAGE_SIZE - 1));
+
+    let dynamic = image.read_at(bias.wrapping_add(dynamic_vaddr), dynamic_size as usize)?;
+    let (mut hash, mut strtab, mut symtab, mut strsz, mut syment) = (None, None, None, None, None);
+    for entry in dynamic.chunks_exact(2 * word_size) {
+        let tag = word(entry, 0)?;
+        let value = word(entry, word_size)?;
+        // The loader relocates these addresses in place on most architectures
+        let address = if value < bias { value + bias } else { value };
+        match tag {
+            DT_NULL => break,
+            DT_HASH => hash = Some(address),
+            DT_STRTAB => strtab = Some(address),
+            DT_SYMTAB => symtab = Some(address),
+            DT_STRSZ => strsz = Some(value),
+            DT_SYMENT => syment = Some(value),
+            _ => {}
+        }
+    }
+    let (Some(strtab), Some(symtab), Some(strsz)) = (strtab, symtab, strsz) else {
+        return Ok(SymbolTable::default());
+    };
+    let syment = syment.unwrap_or(if is_64 { 24 } else { 16 });
+
+    // The symbol count is the number of chains of the SysV hash table, or the
+    // space before the string table which follows the symbols in standard layouts
+    let count = match hash {
+        Some(hash) => u64::from(u32_at(&image.read_at(hash, 8)?, 4)?),
+        None if strtab > symtab => (strtab - symtab) / syment,
+        None => {
+            return Err(ImportError::UnsupportedError(
+                "cannot find the number of dynamic symbols".into(),
+            ))
+        }
+    };
+    let symbol_data = image.read_at(symtab, (count * syment) as usize)?;
+    let strings = image.read_at(strtab, strsz as usize)?;
+
+    let mut symbols = Vec::new();
+    for sym in symbol_data.chunks_exact(syment as usize) {
+        let (name, info, shndx, value, size) = if is_64 {
+            (
+                u32_at(sym, 0)?,
+                sym[4],
+                u16_at(sym, 6)?,
+                u64_at(sym, 8)?,
+                u64_at(sym, 16)?,
+            )
+        } else {
+            let value = u64::from(u32_at(sym, 4)?);
+            let size = u64::from(u32_at(sym, 8)?);
+            (u32_at(sym, 0)?, sym[12], u16_at(sym, 14)?, value, size)
+        };
+        // Undefined symbols, and types other than objects, functions and indirect functions
+        if shndx == 0 || !matches!(info & 0xf, 1 | 2 | 10) {
+            continue;
+        }
+        let Some(name) = c_str_at(&strings, name as usize).filter(|n| !n.is_empty()) else {
+            continue;
+        };
+        let Some(rva) = bias.wrapping_add(value).checked_sub(image.module.base) else {
+            continue;
+        };
+        symbols.push(Symbol { name, rva, size });
+    }
+    Ok(SymbolTable::new(symbols))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load_own_symbols() {
+        let exe = std::env::current_exe().unwrap();
+        let table = load_file(&exe).unwrap();
+        assert!(!table.is_empty());
+        assert!(table.symbols().iter().any(|s| s.name == "main"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_load_memory_symbols() {
+        let pid = sysinfo::Pid::from_u32(std::process::id());
+        let mut process = crate::ops::Process::new(pid);
+        process.open().unwrap();
+        let libc = process
+            .modules()
+            .unwrap()
+            .into_iter()
+            .find(|m| m.name.starts_with("libc.so") || m.name.starts_with("libc-"))
+            .expect("tests are linked to libc");
+        let table = load_memory(&mut process, &libc).unwrap();
+        let malloc = table.find_by_name("malloc").expect("libc exports malloc");
+        assert!(malloc.rva < libc.size);
+    }
+}
@@ -0,0 +1,370 @@
+use super::{DataType, StructDataType, TypeLibrary, Typedef};
+
+#[derive(Clone, Debug)]
+enum Change {
+    // None before for an added struct, None after for a removed one
+    Struct {
+        index: usize,
+        before: Option<StructDataType>,
+        after: Option<StructDataType>,
+    },
+    Typedef {
+        name: String,
+        before: Option<Typedef>,
+        after: Option<Typedef>,
+    },
+}
+
+impl Change {
+    fn apply(&self, structs: &mut Vec<StructDataType>, typedefs: &mut TypeLibrary) {
+        match self {
+            Change::Struct {
+                index,
+                before,
+                after,
+            } => match (before, after) {
+                (None, Some(after)) => structs.insert(*index, after.clone()),
+                (Some(_), None) => {
+                    structs.remove(*index);
+                }
+                (_, Some(after)) => structs[*index] = after.clone(),
+                (None, None) => {}
+            },
+            Change::Typedef { name, after, .. } => match after {
+                Some(after) => {
+                    typedefs.insert(name.clone(), after.clone());
+                }
+                None => {
+                    typedefs.remove(name);
+                }
+            },
+        }
+    }
+
+    fn inverse(&self) -> Change {
+        match self.clone() {
+            Change::Struct {
+                index,
+                before,
+                after,
+            } => Change::Struct {
+                index,
+                before: after,
+                after: before,
+            },
+            Change::Typedef {
+                name,
+                before,
+                after,
+            } => Change::Typedef {
+                name,
+                before: after,
+                after: before,
+            },
+        }
+    }
+
+    fn struct_change(&self) -> Option<StructChange> {
+        match self {
+            Change::Struct {
+                index,
+                before,
+                after,
+            } => match (before, after) {
+                (None, Some(_)) => Some(StructChange::Inserted(*index)),
+                (Some(_), None) => Some(StructChange::Removed(*index)),
+                (Some(before), Some(after)) if before.get_name() != after.get_name() => {
+                    Some(StructChange::Renamed {
+                        from: before.get_name(),
+                        to: after.get_name(),
+                    })
+                }
+                _ => None,
+            },
+            Change::Typedef { .. } => None,
+        }
+    }
+
+    // Whether both changes modify the same struct or typedef
+    fn same_target(&self, other: &Change) -> bool {
+        match (self, other) {
+            (Change::Struct { index: a, .. }, Change::Struct { index: b, .. }) => a == b,
+            (Change::Typedef { name: a, .. }, Change::Typedef { name: b, .. }) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// A change of the struct list, for the state kept alongside the structs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StructChange {
+    Inserted(usize),
+    Removed(usize),
+    Renamed { from: String, to: String },
+}
+
+/// A user edit of the project structs and typedefs, made of the states before and after each
+/// changed struct or typedef, so that it can be reverted and applied again.
+#[derive(Clone, Debug)]
+pub struct Edit {
+    label: String,
+    changes: Vec<Change>,
+}
+
+impl Edit {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// A struct added at `index` of the struct list.
+    pub fn add_struct(&mut self, index: usize, s: StructDataType) {
+        self.changes.push(Change::Struct {
+            index,
+            before: None,
+            after: Some(s),
+        });
+    }
+    pub fn with_added_struct(mut self, index: usize, s: StructDataType) -> Self {
+        self.add_struct(index, s);
+        self
+    }
+
+    /// A struct removed from `index` of the struct list.
+    pub fn remove_struct(&mut self, index: usize, s: StructDataType) {
+        self.changes.push(Change::Struct {
+            index,
+            before: Some(s),
+            after: None,
+        });
+    }
+    pub fn with_removed_struct(mut self, index: usize, s: StructDataType) -> Self {
+        self.remove_struct(index, s);
+        self
+    }
+
+    /// A struct modified in place.
+    pub fn modify_struct(&mut self, index: usize, before: StructDataType, after: StructDataType) {
+        self.changes.push(Change::Struct {
+            index,
+            before: Some(before),
+            after: Some(after),
+        });
+    }
+    pub fn with_modified_struct(
+        mut self,
+        index: usize,
+        before: StructDataType,
+        after: StructDataType,
+    ) -> Self {
+        self.modify_struct(index, before, after);
+        self
+    }
+
+    /// A typedef added (no `before`), removed (no `after`) or modified.
+    pub fn set_typedef(&mut self, name: String, before: Option<Typedef>, after: Option<Typedef>) {
+        self.changes.push(Change::Typedef {
+            name,
+            before,
+            after,
+        });
+    }
+    pub fn with_typedef(
+        mut self,
+        name: String,
+        before: Option<Typedef>,
+        after: Option<Typedef>,
+    ) -> Self {
+        self.set_typedef(name, before, after);
+        self
+    }
+
+    /// Insertions, removals and renames of structs made by applying the edit, in order.
+    pub fn struct_changes(&self) -> Vec<StructChange> {
+        self.changes
+            .iter()
+            .filter_map(Change::struct_change)
+            .collect()
+    }
+
+    /// Insertions, removals and renames of structs made by reverting the edit, in order.
+    pub fn reverted_struct_changes(&self) -> Vec<StructChange> {
+        self.changes
+            .iter()
+            .rev()
+            .filter_map(|change| change.inverse().struct_change())
+            .collect()
+    }
+
+    /// Applies the changes, for an edit that was not made directly on the project.
+    pub fn apply(&self, structs: &mut Vec<StructDataType>, typedefs: &mut TypeLibrary) {
+        for change in &self.changes {
+            change.apply(structs, typedefs);
+        }
+    }
+
+    /// Restores the states before the edit.
+    pub fn revert(&self, structs: &mut Vec<StructDataType>, typedefs: &mut TypeLibrary) {
+        for change in self.changes.iter().rev() {
+            change.inverse().apply(structs, typedefs);
+        }
+    }
+
+    // Merges a later edit of the same targets, e.g. the next keystroke of a rename
+    fn merge(&mut self, later: Edit) -> Result<(), Edit> {
+        let mergeable = self.label == later.label
+            && self.changes.len() == later.changes.len()
+            && self
+                .changes
+                .iter()
+                .zip(&later.changes)
+                .all(|(a, b)| a.same_target(b));
+        if !mergeable {
+            return Err(later);
+        }
+        for (change, later) in self.changes.iter_mut().zip(later.changes) {
+            match (change, later) {
+                (Change::Struct { after, .. }, Change::Struct { after: later, .. }) => {
+                    *after = later
+                }
+                (Change::Typedef { after, .. }, Change::Typedef { after: later, .. }) => {
+                    *after = later
+                }
+                _ => unreachable!("targets checked"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Unbounded undo and redo stacks of edits, which also tell whether the project changed
+/// since it was last saved.
+#[derive(Debug)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    // Undo stack length when saved, None once that state cannot be reached anymore
+    saved: Option<usize>,
+    // Whether the last edit can absorb the next one
+    open: bool,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            saved: Some(0),
+            open: false,
+        }
+    }
+}
+
+impl EditHistory {
+    /// Records an edit already made on the project, the redo stack is dropped.
+    pub fn record(&mut self, edit: Edit) {
+        if edit.is_empty() {
+            return;
+        }
+        if self.saved.is_some_and(|saved| saved > self.undo.len()) {
+            self.saved = None;
+        }
+        self.redo.clear();
+        self.undo.push(edit);
+        self.open = false;
+    }
+
+    /// Records an edit, merged in the previous one if it has the same label and targets and
+    /// the history was not sealed in between. Used for edits made a keystroke at a time.
+    pub fn record_merged(&mut self, edit: Edit) {
+        if edit.is_empty() {
+            return;
+        }
+        let edit = match self.undo.last_mut() {
+            Some(last) if self.open && self.redo.is_empty() => match last.merge(edit) {
+                Ok(()) => return,
+                Err(edit) => edit,
+            },
+            _ => edit,
+        };
+        self.record(edit);
+        self.open = true;
+    }
+
+    /// Stops merging into the last edit, e.g. when a text field loses the focus.
+    pub fn seal(&mut self) {
+        self.open = false;
+    }
+
+    /// Reverts the last edit, returns its label.
+    pub fn undo(
+        &mut self,
+        structs: &mut Vec<StructDataType>,
+        typedefs: &mut TypeLibrary,
+    ) -> Option<&str> {
+        let edit = self.undo.pop()?;
+        edit.revert(structs, typedefs);
+        self.redo.push(edit);
+        self.open = false;
+        self.redo.last().map(Edit::get_label)
+    }
+
+    /// Applies the last undone edit again, returns its label.
+    pub fn redo(
+        &mut self,
+        structs: &mut Vec<StructDataType>,
+        typedefs: &mut TypeLibrary,
+    ) -> Option<&str> {
+        let edit = self.redo.pop()?;
+        edit.apply(structs, typedefs);
+        self.undo.push(edit);
+        self.open = false;
+        self.undo.last().map(Edit::get_label)
+    }
+
+    /// Struct changes made by the next undo.
+    pub fn undo_struct_changes(&self) -> Vec<StructChange> {
+        self.undo
+            .last()
+            .map_or_else(Vec::new, Edit::reverted_struct_changes)
+    }
+    /// Struct changes made by the next redo.
+    pub fn redo_struct_changes(&self) -> Vec<StructChange> {
+        self.redo.last().map_or_else(Vec::new, Edit::struct_changes)
+    }
+
+    /// Label of the edit undone next.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(Edit::get_label)
+    }
+    /// Label of the edit redone next.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(Edit::get_label)
+    }
+
+    /// Marks the current state as saved.
+    pub fn mark_saved(&mut self) {
+        self.saved = Some(self.undo.len());
+        self.open = false;
+    }
+
+    /// Whether the project differs from its last saved state.
+    pub fn is_dirty(&self) -> bool {
+        self.saved != Some(self.undo.len())
+    }
+
+    /// Forgets every edit, e.g. when another project is loaded, which is then the saved state.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
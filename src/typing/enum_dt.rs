@@ -0,0 +1,80 @@
+use super::{ConversionError, DataType, Endianness, IntSize};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnumDataType {
+    name: String,
+    size: IntSize,
+    endianness: Endianness,
+    variants: Vec<(String, i64)>,
+}
+impl Default for EnumDataType {
+    fn default() -> Self {
+        Self {
+            name: "ENUM".into(),
+            size: IntSize::Integer32,
+            endianness: Endianness::Little,
+            variants: Vec::new(),
+        }
+    }
+}
+impl DataType for EnumDataType {
+    fn get_size(&self) -> usize {
+        self.size.into()
+    }
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    fn bytes_to_string(&self, data: &[u8]) -> Result<String, ConversionError> {
+        if data.len() != self.get_size() {
+            return Err(ConversionError::SizeError);
+        }
+        let val = match self.endianness {
+            Endianness::Little => LittleEndian::read_uint(data, self.get_size()),
+            Endianness::Big => BigEndian::read_uint(data, self.get_size()),
+        };
+
+        // Variants are stored as signed values, compare on the enum's width only
+        let mask = match self.size {
+            IntSize::Integer64 => u64::MAX,
+            _ => (1u64 << (8 * self.get_size())) - 1,
+        };
+        let s = self
+            .variants
+            .iter()
+            .find(|(_, v)| (*v as u64) & mask == val)
+            .map_or_else(|| format!("{val:#X}"), |(name, _)| name.clone());
+        Ok(s)
+    }
+}
+impl EnumDataType {
+    pub fn new(name: String, size: IntSize) -> Self {
+        Self {
+            name,
+            size,
+            ..Default::default()
+        }
+    }
+    pub fn get_variants(&self) -> &Vec<(String, i64)> {
+        &self.variants
+    }
+    pub fn push_variant(&mut self, name: String, value: i64) {
+        self.variants.push((name, value));
+    }
+    pub fn with_variant(mut self, name: String, value: i64) -> Self {
+        self.push_variant(name, value);
+        self
+    }
+
+    pub fn get_endianness(&self) -> Endianness {
+        self.endianness
+    }
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+    pub fn with_endianness(mut self, endianness: Endianness) -> Self {
+        self.set_endianness(endianness);
+        self
+    }
+}
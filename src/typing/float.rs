@@ -59,6 +59,9 @@ impl DataType for FloatDataType {
     }
 }
 impl FloatDataType {
+    pub fn get_precision(&self) -> FloatPrecision {
+        self.precision
+    }
     pub fn set_precision(&mut self, precision: FloatPrecision) {
         self.precision = precision;
     }
@@ -70,6 +73,9 @@ impl FloatDataType {
         self
     }
 
+    pub fn get_endianness(&self) -> Endianness {
+        self.endianness
+    }
     pub fn set_endianness(&mut self, endianness: Endianness) {
         self.endianness = endianness;
     }
@@ -0,0 +1,70 @@
+use super::{ConversionError, DataType};
+use serde::{Deserialize, Serialize};
+
+/// A function signature. Types are kept by name, as signatures only matter
+/// for display and export, and a by-value layout could be self-referencing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FunctionDataType {
+    name: String,
+    return_type: String,
+    // (Parameter name, Type name)
+    parameters: Vec<(String, String)>,
+    variadic: bool,
+}
+impl DataType for FunctionDataType {
+    fn get_size(&self) -> usize {
+        0
+    }
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    fn bytes_to_string(&self, _data: &[u8]) -> Result<String, ConversionError> {
+        Err(ConversionError::NotConvertibleError)
+    }
+}
+impl FunctionDataType {
+    pub fn new(name: String, return_type: String) -> Self {
+        Self {
+            name,
+            return_type,
+            ..Default::default()
+        }
+    }
+    pub fn get_return_type(&self) -> &String {
+        &self.return_type
+    }
+    pub fn get_parameters(&self) -> &Vec<(String, String)> {
+        &self.parameters
+    }
+    pub fn push_parameter(&mut self, name: String, type_name: String) {
+        self.parameters.push((name, type_name));
+    }
+    pub fn with_parameter(mut self, name: String, type_name: String) -> Self {
+        self.push_parameter(name, type_name);
+        self
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+    pub fn set_variadic(&mut self, variadic: bool) {
+        self.variadic = variadic;
+    }
+    pub fn with_variadic(mut self, variadic: bool) -> Self {
+        self.set_variadic(variadic);
+        self
+    }
+
+    /// C-like prototype, e.g. `int foo(char * s, ...)`
+    pub fn prototype(&self) -> String {
+        let mut params: Vec<String> = self
+            .parameters
+            .iter()
+            .map(|(name, ty)| format!("{ty} {name}"))
+            .collect();
+        if self.variadic {
+            params.push("...".into());
+        }
+        format!("{} {}({})", self.return_type, self.name, params.join(", "))
+    }
+}
@@ -87,6 +87,9 @@ impl DataType for IntegerDataType {
     }
 }
 impl IntegerDataType {
+    pub fn is_hex(&self) -> bool {
+        self.hex
+    }
     pub fn set_hex(&mut self, hex: bool) {
         self.hex = hex;
     }
@@ -98,6 +101,9 @@ impl IntegerDataType {
         self
     }
 
+    pub fn is_signed(&self) -> bool {
+        self.signed
+    }
     pub fn set_signed(&mut self, signed: bool) {
         self.signed = signed;
     }
@@ -109,6 +115,9 @@ impl IntegerDataType {
         self
     }
 
+    pub fn get_endianness(&self) -> Endianness {
+        self.endianness
+    }
     pub fn set_endianness(&mut self, endianness: Endianness) {
         self.endianness = endianness;
     }
@@ -120,6 +129,9 @@ impl IntegerDataType {
         self
     }
 
+    pub fn get_int_size(&self) -> IntSize {
+        self.size
+    }
     pub fn set_size(&mut self, size: IntSize) {
         self.size = size;
     }
@@ -5,10 +5,21 @@ pub use int::{IntSize, IntegerDataType};
 pub mod float;
 pub use float::{FloatDataType, FloatPrecision};
 pub mod str;
-pub use str::StrDataType;
+pub use str::{StrDataType, StrEncoding};
 pub mod struct_dt;
-pub use struct_dt::{StructDataType, StructEntry};
-
+pub use struct_dt::{Bitfield, StructDataType, StructEntry};
+pub mod union_dt;
+pub use union_dt::UnionDataType;
+pub mod enum_dt;
+pub use enum_dt::EnumDataType;
+pub mod function_dt;
+pub use function_dt::FunctionDataType;
+pub mod edit;
+pub use edit::{Edit, EditHistory, StructChange};
+pub mod search;
+pub use search::{TypeKind, TypeQuery};
+
+use std::collections::HashMap;
 use std::fmt::Display;
 
 use enum_dispatch::enum_dispatch;
@@ -98,29 +109,72 @@ pub enum DataTypeEnum {
     StructDataType,
     PointerDataType,
     ArrayDataType,
-    //Enums TODO!
-    //FUNCTIONS
+    UnionDataType,
+    EnumDataType,
+    FunctionDataType,
     //CLASS (with VTABLES)
 }
 
+// (Description: String, dt: DataTypeEnum)
+pub type Typedef = (String, DataTypeEnum);
+// Project type library, indexed by (namespaced) type name
+pub type TypeLibrary = HashMap<String, Typedef>;
+
 /* BOOLEANS */
 
 /* POINTER */
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PointerDataType {
     pointed_datatype: Box<DataTypeEnum>,
+    // Explicit pointer size, falls back on the architecture size otherwise
+    #[serde(default)]
+    size: Option<usize>,
 }
 impl DataType for PointerDataType {
     fn get_size(&self) -> usize {
-        ARCH_SIZE.get().get_size()
+        self.size.unwrap_or_else(|| ARCH_SIZE.get().get_size())
     }
 
     fn get_name(&self) -> String {
         format!("Pointer to {}", self.pointed_datatype.get_name())
     }
 
-    fn bytes_to_string(&self, _data: &[u8]) -> Result<String, ConversionError> {
-        Err(ConversionError::NotConvertibleError)
+    fn bytes_to_string(&self, data: &[u8]) -> Result<String, ConversionError> {
+        let address = self.address(data).ok_or(ConversionError::SizeError)?;
+        Ok(format!("{address:#X}"))
+    }
+}
+
+impl PointerDataType {
+    /// Reads the little endian address stored in the pointer.
+    pub fn address(&self, data: &[u8]) -> Option<u64> {
+        if data.len() != self.get_size() || data.len() > 8 {
+            return None;
+        }
+        Some(
+            data.iter()
+                .rev()
+                .fold(0u64, |acc, &b| (acc << 8) | u64::from(b)),
+        )
+    }
+    pub fn new(pointed_datatype: DataTypeEnum) -> Self {
+        Self {
+            pointed_datatype: Box::new(pointed_datatype),
+            size: None,
+        }
+    }
+    pub fn get_pointed_datatype(&self) -> &DataTypeEnum {
+        &self.pointed_datatype
+    }
+    pub fn set_pointed_datatype(&mut self, pointed_datatype: DataTypeEnum) {
+        *self.pointed_datatype = pointed_datatype;
+    }
+    pub fn set_size(&mut self, size: usize) {
+        self.size = Some(size);
+    }
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.set_size(size);
+        self
     }
 }
 
@@ -143,8 +197,35 @@ impl DataType for ArrayDataType {
         if data.len() != self.get_size() {
             return Err(ConversionError::SizeError);
         }
-
-        todo!();
+        let element_size = self.element_datatype.get_size();
+        if element_size == 0 {
+            return Ok("[]".into());
+        }
+        let elements = data
+            .chunks_exact(element_size)
+            .map(|e| self.element_datatype.bytes_to_string(e))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("[{}]", elements.join(", ")))
+    }
+}
+impl ArrayDataType {
+    pub fn new(element_datatype: DataTypeEnum, size: usize) -> Self {
+        Self {
+            element_datatype: Box::new(element_datatype),
+            size,
+        }
+    }
+    pub fn get_element_datatype(&self) -> &DataTypeEnum {
+        &self.element_datatype
+    }
+    pub fn set_element_datatype(&mut self, element_datatype: DataTypeEnum) {
+        *self.element_datatype = element_datatype;
+    }
+    pub fn get_length(&self) -> usize {
+        self.size
+    }
+    pub fn set_length(&mut self, length: usize) {
+        self.size = length;
     }
 }
 
@@ -155,6 +236,221 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_struct_editing() {
+        let int = || DataTypeEnum::from(IntegerDataType::default());
+        let byte = || DataTypeEnum::from(IntegerDataType::default().with_size(IntSize::Integer8));
+        let mut s = StructDataType::new("S".into(), vec![]);
+        s.push_entry(StructEntry::new("a".into(), int()));
+        s.push_entry(StructEntry::new("b".into(), int()));
+        s.push_entry(StructEntry::new("c".into(), int()));
+
+        s.insert_entry(1, StructEntry::new("x".into(), byte()));
+        let offsets = |s: &StructDataType| -> Vec<(String, usize)> {
+            s.get_entries()
+                .iter()
+                .map(|e| (e.get_name().clone(), e.get_offset()))
+                .collect()
+        };
+        assert_eq!(
+            offsets(&s),
+            [
+                ("a".into(), 0),
+                ("x".into(), 4),
+                ("b".into(), 5),
+                ("c".into(), 9)
+            ]
+        );
+        assert_eq!(s.get_size(), 13);
+
+        // A larger type swallows the overlapped entries, the size is kept
+        s.set_entry_datatype(1, int());
+        assert_eq!(
+            offsets(&s),
+            [("a".into(), 0), ("x".into(), 4), ("c".into(), 9)]
+        );
+        assert_eq!(s.get_size(), 13);
+
+        assert_eq!(s.remove_entry(0).unwrap().get_name(), "a");
+        assert_eq!(offsets(&s), [("x".into(), 0), ("c".into(), 5)]);
+        assert_eq!(s.get_size(), 9);
+
+        s.insert_bytes(s.get_size(), 8);
+        assert_eq!(s.get_size(), 17);
+
+        s.replace_at(2, StructEntry::new("y".into(), byte()));
+        s.replace_at(12, StructEntry::new("z".into(), int()));
+        assert_eq!(
+            offsets(&s),
+            [("y".into(), 2), ("c".into(), 5), ("z".into(), 12)]
+        );
+        assert_eq!(s.get_size(), 17);
+    }
+
+    #[test]
+    fn test_edit_history() {
+        let int = || DataTypeEnum::from(IntegerDataType::default());
+        let mut structs = vec![StructDataType::new("A".into(), vec![])];
+        let mut typedefs = TypeLibrary::new();
+        let mut history = EditHistory::default();
+        assert!(!history.is_dirty());
+
+        // Edits are recorded once made
+        let before = structs[0].clone();
+        structs[0].push_entry(StructEntry::new("a".into(), int()));
+        structs[0].push_entry(StructEntry::new("b".into(), int()));
+        history.record(Edit::new("add fields").with_modified_struct(0, before, structs[0].clone()));
+        // Keystrokes of a rename are merged
+        for name in ["B", "Bo", "Boo"] {
+            let before = structs[0].clone();
+            structs[0].set_name(name.into());
+            history.record_merged(Edit::new("rename struct").with_modified_struct(
+                0,
+                before,
+                structs[0].clone(),
+            ));
+        }
+        let typedef: Typedef = ("".into(), int());
+        let edit = Edit::new("add typedef").with_typedef("T".into(), None, Some(typedef));
+        edit.apply(&mut structs, &mut typedefs);
+        history.record(edit);
+        history.mark_saved();
+
+        assert_eq!(
+            history.undo(&mut structs, &mut typedefs),
+            Some("add typedef")
+        );
+        assert!(typedefs.is_empty());
+        assert!(history.is_dirty());
+        let renamed = StructChange::Renamed {
+            from: "Boo".into(),
+            to: "A".into(),
+        };
+        assert_eq!(history.undo_struct_changes(), [renamed]);
+        assert_eq!(
+            history.undo(&mut structs, &mut typedefs),
+            Some("rename struct")
+        );
+        assert_eq!(structs[0].get_name(), "A");
+        assert_eq!(
+            history.redo(&mut structs, &mut typedefs),
+            Some("rename struct")
+        );
+        assert_eq!(structs[0].get_name(), "Boo");
+        assert_eq!(
+            history.redo(&mut structs, &mut typedefs),
+            Some("add typedef")
+        );
+        assert!(!history.is_dirty());
+
+        // Swapped fields
+        history.undo(&mut structs, &mut typedefs);
+        let before = structs[0].clone();
+        assert!(structs[0].swap_entries(0));
+        assert_eq!(structs[0].get_entries()[0].get_name(), "b");
+        assert_eq!(structs[0].get_entries()[1].get_offset(), 4);
+        history.record(Edit::new("move field").with_modified_struct(0, before, structs[0].clone()));
+        assert_eq!(history.redo_label(), None);
+        // The saved state was undone and replaced
+        assert!(history.is_dirty());
+        history.undo(&mut structs, &mut typedefs);
+        assert!(history.is_dirty());
+
+        history.record(Edit::new("remove struct").with_removed_struct(0, structs[0].clone()));
+        structs.remove(0);
+        assert_eq!(history.undo_struct_changes(), [StructChange::Inserted(0)]);
+        history.undo(&mut structs, &mut typedefs);
+        assert_eq!(history.redo_struct_changes(), [StructChange::Removed(0)]);
+        assert_eq!(structs[0].get_entries()[0].get_name(), "a");
+    }
+
+    #[test]
+    fn test_strings_and_arrays() -> Result<(), ConversionError> {
+        let wide = StrDataType::default()
+            .with_size(8)
+            .with_encoding(StrEncoding::Utf16);
+        assert_eq!(
+            wide.bytes_to_string(&[b'h', 0, b'i', 0, 0, 0, b'x', 0])?,
+            "hi"
+        );
+        assert!(wide
+            .bytes_to_string(&[b'h', 0, b'i', 0, b'x', 0, b'x', 0])
+            .is_err());
+
+        let bytes = ArrayDataType::new(
+            IntegerDataType::default()
+                .with_size(IntSize::Integer8)
+                .into(),
+            3,
+        );
+        assert_eq!(bytes.bytes_to_string(&[1, 2, 3])?, "[1, 2, 3]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_search() {
+        let mut library = TypeLibrary::new();
+        let int = || DataTypeEnum::from(IntegerDataType::default());
+        for name in [
+            "UInt",
+            "game::Player",
+            "game::PlayerInfo",
+            "game::ui::Panel",
+        ] {
+            library.insert(name.into(), ("".into(), int()));
+        }
+        let s = StructDataType::new("Inventory".into(), vec![]).with_size(0x40);
+        library.insert(
+            "Inventory".into(),
+            ("items held by the player".into(), s.into()),
+        );
+
+        let names = |query: &TypeQuery| -> Vec<String> {
+            search::search(&library, query)
+                .into_iter()
+                .cloned()
+                .collect()
+        };
+        let query = |text: &str| TypeQuery {
+            text: text.into(),
+            ..Default::default()
+        };
+        // Exact and shorter matches first, descriptions last
+        assert_eq!(
+            names(&query("player")),
+            ["game::Player", "game::PlayerInfo", "Inventory"]
+        );
+        assert_eq!(names(&query("gpi")), ["game::PlayerInfo"]);
+        assert!(names(&query("xyz")).is_empty());
+        assert_eq!(search::category("game::ui::Panel"), ["game", "ui"]);
+
+        let mut filtered = query("");
+        filtered.kinds = vec![TypeKind::Struct];
+        assert_eq!(names(&filtered), ["Inventory"]);
+        filtered.kinds.clear();
+        filtered.category = vec!["game".into()];
+        filtered.max_size = Some(4);
+        assert_eq!(
+            names(&filtered),
+            ["game::Player", "game::PlayerInfo", "game::ui::Panel"]
+        );
+        filtered.min_size = Some(8);
+        assert!(names(&filtered).is_empty());
+    }
+
+    #[test]
+    fn test_bitfield() {
+        let bitfield = Bitfield {
+            bit_offset: 6,
+            bit_size: 4,
+        };
+        let mut data = [0xFF, 0x00];
+        assert_eq!(bitfield.extract(&data), 0x3);
+        bitfield.insert(&mut data, 0b1010);
+        assert_eq!(data, [0xBF, 0x02]);
+        assert_eq!(bitfield.extract(&data), 0b1010);
+    }
+
     #[test]
     fn test_boolean_zero() {
         let dt = BooleanDataType::default().with_size(4);
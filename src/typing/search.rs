@@ -0,0 +1,156 @@
+use super::{DataType, DataTypeEnum, TypeLibrary};
+use crate::interop::NAMESPACE_SEPARATOR;
+
+// Matches of descriptions rank below matches of names
+const DESCRIPTION_WEIGHT: u32 = 2;
+
+// Long descriptions match most queries as subsequences, they must contain every word instead
+fn description_matches(query: &str, description: &str) -> bool {
+    let description = description.to_lowercase();
+    query
+        .split_whitespace()
+        .all(|word| description.contains(&word.to_lowercase()))
+}
+
+/// Kind of a type, to filter the search results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypeKind {
+    // Integers, booleans, floats and strings
+    Primitive,
+    Struct,
+    Union,
+    Enum,
+    Pointer,
+    Array,
+    Function,
+}
+
+impl TypeKind {
+    pub const ALL: [TypeKind; 7] = [
+        TypeKind::Primitive,
+        TypeKind::Struct,
+        TypeKind::Union,
+        TypeKind::Enum,
+        TypeKind::Pointer,
+        TypeKind::Array,
+        TypeKind::Function,
+    ];
+
+    pub fn of(dt: &DataTypeEnum) -> Self {
+        match dt {
+            DataTypeEnum::IntegerDataType(_)
+            | DataTypeEnum::BooleanDataType(_)
+            | DataTypeEnum::FloatDataType(_)
+            | DataTypeEnum::StrDataType(_) => TypeKind::Primitive,
+            DataTypeEnum::StructDataType(_) => TypeKind::Struct,
+            DataTypeEnum::UnionDataType(_) => TypeKind::Union,
+            DataTypeEnum::EnumDataType(_) => TypeKind::Enum,
+            DataTypeEnum::PointerDataType(_) => TypeKind::Pointer,
+            DataTypeEnum::ArrayDataType(_) => TypeKind::Array,
+            DataTypeEnum::FunctionDataType(_) => TypeKind::Function,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TypeKind::Primitive => "primitive",
+            TypeKind::Struct => "struct",
+            TypeKind::Union => "union",
+            TypeKind::Enum => "enum",
+            TypeKind::Pointer => "pointer",
+            TypeKind::Array => "array",
+            TypeKind::Function => "function",
+        }
+    }
+}
+
+/// Filters of a type search, the results match all of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeQuery {
+    pub text: String,
+    // Any kind when empty
+    pub kinds: Vec<TypeKind>,
+    pub min_size: Option<usize>,
+    pub max_size: Option<usize>,
+    // Namespaces the type is in, e.g. ["std", "vector"], any category when empty
+    pub category: Vec<String>,
+}
+
+/// Scores how well `query` matches `text`: its characters must appear in order, case is ignored.
+/// Consecutive characters, characters starting a word and matches at the start score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous_match: Option<usize> = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        if !c.to_lowercase().eq(std::iter::once(query[next])) {
+            continue;
+        }
+        let word_start = match i.checked_sub(1).map(|p| chars[p]) {
+            None => true,
+            Some(p) => !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase()),
+        };
+        score += 1;
+        if word_start {
+            score += 8;
+        }
+        if previous_match.is_some_and(|p| p + 1 == i) {
+            score += 5;
+        }
+        if i == 0 {
+            score += 10;
+        }
+        previous_match = Some(i);
+        next += 1;
+    }
+    if next < query.len() {
+        return None;
+    }
+    if query.len() == chars.len() {
+        // Same length and every character matched: the whole text
+        score += 100;
+    }
+    // Shorter texts first among the equal matches
+    Some(score * 64 + 63u32.saturating_sub(chars.len() as u32))
+}
+
+/// Namespaces of a namespaced type name, e.g. ["std", "vector"] for `std::vector::iterator`.
+pub fn category(name: &str) -> Vec<&str> {
+    let mut parts: Vec<&str> = name.split(NAMESPACE_SEPARATOR).collect();
+    parts.pop();
+    parts
+}
+
+/// Names of the types matching a query, the best matches first.
+pub fn search<'a>(library: &'a TypeLibrary, query: &TypeQuery) -> Vec<&'a String> {
+    let text = query.text.trim();
+    let in_category: Vec<&str> = query.category.iter().map(String::as_str).collect();
+    let mut results: Vec<(u32, &String)> = library
+        .iter()
+        .filter(|(name, (_, dt))| {
+            let size = dt.get_size();
+            (query.kinds.is_empty() || query.kinds.contains(&TypeKind::of(dt)))
+                && query.min_size.is_none_or(|min| size >= min)
+                && query.max_size.is_none_or(|max| size <= max)
+                && category(name).starts_with(&in_category)
+        })
+        .filter_map(|(name, (description, _))| {
+            let by_name = fuzzy_score(text, name);
+            let by_description = match description_matches(text, description) {
+                true => fuzzy_score(text, description).map(|score| score / DESCRIPTION_WEIGHT),
+                false => None,
+            };
+            Some((by_name.max(by_description)?, name))
+        })
+        .collect();
+    results.sort_by(|(a, a_name), (b, b_name)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+    results.into_iter().map(|(_, name)| name).collect()
+}
@@ -1,9 +1,20 @@
 use super::{ConversionError, DataType};
 use serde::{Deserialize, Serialize};
 
+/// Encoding of the characters of a string.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum StrEncoding {
+    #[default]
+    Utf8,
+    // Little endian, e.g. the wide strings of Windows
+    Utf16,
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct StrDataType {
     size: usize,
+    #[serde(default)]
+    encoding: StrEncoding,
 }
 impl DataType for StrDataType {
     fn get_size(&self) -> usize {
@@ -11,7 +22,10 @@ impl DataType for StrDataType {
     }
 
     fn get_name(&self) -> String {
-        String::from("Null terminated string")
+        match self.encoding {
+            StrEncoding::Utf8 => String::from("Null terminated string"),
+            StrEncoding::Utf16 => String::from("Null terminated wide string"),
+        }
     }
 
     fn bytes_to_string(&self, data: &[u8]) -> Result<String, ConversionError> {
@@ -19,9 +33,24 @@ impl DataType for StrDataType {
             return Err(ConversionError::SizeError);
         }
 
-        let cstr = std::ffi::CStr::from_bytes_until_nul(data)
-            .map_err(|_e| ConversionError::CStrUntilNullError)?;
-        Ok(cstr.to_string_lossy().into_owned())
+        match self.encoding {
+            StrEncoding::Utf8 => {
+                let cstr = std::ffi::CStr::from_bytes_until_nul(data)
+                    .map_err(|_e| ConversionError::CStrUntilNullError)?;
+                Ok(cstr.to_string_lossy().into_owned())
+            }
+            StrEncoding::Utf16 => {
+                let units: Vec<u16> = data
+                    .chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .collect();
+                let len = units
+                    .iter()
+                    .position(|&u| u == 0)
+                    .ok_or(ConversionError::CStrUntilNullError)?;
+                Ok(String::from_utf16_lossy(&units[..len]))
+            }
+        }
     }
 }
 impl StrDataType {
@@ -32,4 +61,14 @@ impl StrDataType {
         self.set_size(size);
         self
     }
+    pub fn get_encoding(&self) -> StrEncoding {
+        self.encoding
+    }
+    pub fn set_encoding(&mut self, encoding: StrEncoding) {
+        self.encoding = encoding;
+    }
+    pub fn with_encoding(mut self, encoding: StrEncoding) -> Self {
+        self.set_encoding(encoding);
+        self
+    }
 }
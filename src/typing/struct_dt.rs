@@ -5,18 +5,31 @@ use serde::{Deserialize, Serialize};
 pub struct StructDataType {
     name: String,
     entries: Vec<StructEntry>,
+    // Declared size, for structures with trailing padding (e.g. imported ones)
+    #[serde(default)]
+    size: Option<usize>,
+    #[serde(default)]
+    comment: String,
 }
 impl Default for StructDataType {
     fn default() -> Self {
         Self {
             name: "STRUCT".into(),
             entries: Vec::new(),
+            size: None,
+            comment: String::new(),
         }
     }
 }
 impl DataType for StructDataType {
     fn get_size(&self) -> usize {
-        self.entries.iter().map(|e| e.size).sum()
+        let end = self
+            .entries
+            .iter()
+            .map(|e| e.offset + e.size)
+            .max()
+            .unwrap_or(0);
+        self.size.map_or(end, |s| s.max(end))
     }
     fn get_name(&self) -> String {
         self.name.clone()
@@ -27,7 +40,11 @@ impl DataType for StructDataType {
 }
 impl StructDataType {
     pub fn new(name: String, entries: Vec<StructEntry>) -> Self {
-        StructDataType { name, entries }
+        StructDataType {
+            name,
+            entries,
+            ..Default::default()
+        }
     }
     pub fn get_entries(&self) -> &Vec<StructEntry> {
         &self.entries
@@ -39,8 +56,120 @@ impl StructDataType {
             .map_or(0, |e| e.offset + e.datatype.get_size());
         self.entries.push(e);
     }
-    pub fn insert_entry(&mut self, _idx: usize, _e: StructEntry) {
-        todo!("Insert entry")
+    /// Adds an entry at a fixed offset, keeping the entries sorted by offset.
+    pub fn add_entry_at(&mut self, offset: usize, mut e: StructEntry) {
+        e.offset = offset;
+        let idx = self.entries.partition_point(|x| x.offset <= offset);
+        self.entries.insert(idx, e);
+    }
+    /// Inserts an entry before the entry at `idx` (at the end past the last one),
+    /// moving the following entries by its size.
+    pub fn insert_entry(&mut self, idx: usize, e: StructEntry) {
+        let offset = self
+            .entries
+            .get(idx)
+            .map_or_else(|| self.get_size(), |x| x.offset);
+        self.insert_bytes(offset, e.size);
+        self.add_entry_at(offset, e);
+    }
+    /// Removes an entry and its bytes, moving the following entries back.
+    /// The bytes are kept when other entries share them (bitfields).
+    pub fn remove_entry(&mut self, idx: usize) -> Option<StructEntry> {
+        if idx >= self.entries.len() {
+            return None;
+        }
+        let size = self.get_size();
+        let e = self.entries.remove(idx);
+        let end = e.offset + e.size;
+        let shared = self
+            .entries
+            .iter()
+            .any(|x| x.offset < end && e.offset < x.offset + x.size);
+        if !shared {
+            for x in self.entries.iter_mut().filter(|x| x.offset >= end) {
+                x.offset -= e.size;
+            }
+            self.size = Some(size - e.size);
+        }
+        Some(e)
+    }
+    /// Inserts `len` undefined bytes at `offset`, moving the following entries.
+    pub fn insert_bytes(&mut self, offset: usize, len: usize) {
+        let size = self.get_size();
+        for e in self.entries.iter_mut().filter(|e| e.offset >= offset) {
+            e.offset += len;
+        }
+        if offset <= size {
+            self.size = Some(size + len);
+        }
+    }
+    /// Changes the type of an entry in place: the entries overlapped by a larger type
+    /// are removed, a smaller type leaves undefined bytes.
+    pub fn set_entry_datatype(&mut self, idx: usize, datatype: DataTypeEnum) {
+        let size = self.get_size();
+        let Some(e) = self.entries.get_mut(idx) else {
+            return;
+        };
+        e.set_dataype(datatype);
+        e.bitfield = None;
+        let (start, end) = (e.offset, e.offset + e.size);
+        let mut i = 0;
+        self.entries.retain(|x| {
+            let keep = i == idx || x.offset + x.size <= start || x.offset >= end;
+            i += 1;
+            keep
+        });
+        self.size = Some(size);
+    }
+    /// Adds an entry at `offset` in place of the entries it overlaps, e.g. to type undefined bytes.
+    pub fn replace_at(&mut self, offset: usize, e: StructEntry) {
+        let size = self.get_size();
+        let end = offset + e.size;
+        self.entries
+            .retain(|x| x.offset + x.size <= offset || x.offset >= end);
+        self.add_entry_at(offset, e);
+        self.size = Some(size);
+    }
+    /// Swaps an entry with the next one, keeping the bytes between them.
+    /// Returns false when there is no next entry or they share bytes (bitfields).
+    pub fn swap_entries(&mut self, idx: usize) -> bool {
+        let (Some(a), Some(b)) = (self.entries.get(idx), self.entries.get(idx + 1)) else {
+            return false;
+        };
+        if a.offset + a.size > b.offset {
+            return false;
+        }
+        let (start, end, a_size) = (a.offset, b.offset + b.size, a.size);
+        self.entries[idx + 1].offset = start;
+        self.entries[idx].offset = end - a_size;
+        self.entries.swap(idx, idx + 1);
+        true
+    }
+    pub fn get_entry_mut(&mut self, idx: usize) -> Option<&mut StructEntry> {
+        self.entries.get_mut(idx)
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn set_size(&mut self, size: usize) {
+        self.size = Some(size);
+    }
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.set_size(size);
+        self
+    }
+
+    pub fn set_comment(&mut self, comment: String) {
+        self.comment = comment;
+    }
+    pub fn get_comment(&self) -> &String {
+        &self.comment
+    }
+    pub fn with_comment(mut self, comment: String) -> Self {
+        self.set_comment(comment);
+        self
     }
 }
 
@@ -50,7 +179,49 @@ pub struct StructEntry {
     size: usize,
     offset: usize,
     datatype: DataTypeEnum,
+    #[serde(default)]
+    comment: String,
+    #[serde(default)]
+    bitfield: Option<Bitfield>,
+}
+
+/// Position of a bitfield in the storage unit of its entry, bits are counted from the least significant one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bitfield {
+    pub bit_offset: u8,
+    pub bit_size: u8,
+}
+impl Bitfield {
+    /// Extracts the bitfield value from its little endian storage unit.
+    pub fn extract(&self, data: &[u8]) -> u64 {
+        let unit = data
+            .iter()
+            .take(8)
+            .rev()
+            .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+        let mask = match self.bit_size {
+            64.. => u64::MAX,
+            n => (1u64 << n) - 1,
+        };
+        unit.checked_shr(u32::from(self.bit_offset)).unwrap_or(0) & mask
+    }
+
+    /// Replaces the bitfield value in its little endian storage unit, keeping the other bits.
+    pub fn insert(&self, data: &mut [u8], value: u64) {
+        let mask = match self.bit_size {
+            64.. => u64::MAX,
+            n => (1u64 << n) - 1,
+        };
+        let shift = u32::from(self.bit_offset);
+        let bits = mask.checked_shl(shift).unwrap_or(0);
+        let value = (value & mask).checked_shl(shift).unwrap_or(0);
+        for (i, byte) in data.iter_mut().take(8).enumerate() {
+            let byte_bits = (bits >> (8 * i)) as u8;
+            *byte = (*byte & !byte_bits) | ((value >> (8 * i)) as u8 & byte_bits);
+        }
+    }
 }
+
 impl StructEntry {
     pub fn new(name: String, datatype: DataTypeEnum) -> Self {
         Self {
@@ -58,6 +229,8 @@ impl StructEntry {
             size: datatype.get_size(),
             offset: 0usize,
             datatype,
+            comment: String::new(),
+            bitfield: None,
         }
     }
     pub fn set_name(&mut self, name: String) {
@@ -76,4 +249,32 @@ impl StructEntry {
     pub fn get_size(&self) -> usize {
         self.size
     }
+    pub fn get_offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn set_comment(&mut self, comment: String) {
+        self.comment = comment;
+    }
+    pub fn get_comment(&self) -> &String {
+        &self.comment
+    }
+    pub fn with_comment(mut self, comment: String) -> Self {
+        self.set_comment(comment);
+        self
+    }
+
+    pub fn get_bitfield(&self) -> Option<Bitfield> {
+        self.bitfield
+    }
+    pub fn set_bitfield(&mut self, bitfield: Option<Bitfield>) {
+        self.bitfield = bitfield;
+    }
+    pub fn with_bitfield(mut self, bit_offset: u8, bit_size: u8) -> Self {
+        self.set_bitfield(Some(Bitfield {
+            bit_offset,
+            bit_size,
+        }));
+        self
+    }
 }
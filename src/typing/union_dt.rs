@@ -0,0 +1,56 @@
+use super::{ConversionError, DataType, StructEntry};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnionDataType {
+    name: String,
+    entries: Vec<StructEntry>,
+    #[serde(default)]
+    comment: String,
+}
+impl Default for UnionDataType {
+    fn default() -> Self {
+        Self {
+            name: "UNION".into(),
+            entries: Vec::new(),
+            comment: String::new(),
+        }
+    }
+}
+impl DataType for UnionDataType {
+    fn get_size(&self) -> usize {
+        self.entries.iter().map(|e| e.get_size()).max().unwrap_or(0)
+    }
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    fn bytes_to_string(&self, _data: &[u8]) -> Result<String, ConversionError> {
+        Err(ConversionError::NotConvertibleError)
+    }
+}
+impl UnionDataType {
+    pub fn new(name: String, entries: Vec<StructEntry>) -> Self {
+        Self {
+            name,
+            entries,
+            comment: String::new(),
+        }
+    }
+    pub fn get_entries(&self) -> &Vec<StructEntry> {
+        &self.entries
+    }
+    pub fn push_entry(&mut self, e: StructEntry) {
+        self.entries.push(e);
+    }
+
+    pub fn set_comment(&mut self, comment: String) {
+        self.comment = comment;
+    }
+    pub fn get_comment(&self) -> &String {
+        &self.comment
+    }
+    pub fn with_comment(mut self, comment: String) -> Self {
+        self.set_comment(comment);
+        self
+    }
+}
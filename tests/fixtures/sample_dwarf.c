@@ -0,0 +1,19 @@
+enum State { Alive, Dead };
+typedef int Health;
+union Value {
+    int i;
+    float f;
+};
+typedef struct {
+    short x, y;
+} Point;
+struct Player {
+    struct Player *next;
+    unsigned int team : 3;
+    unsigned int flags : 5;
+    enum State state;
+    Health health;
+    union Value value;
+    Point position;
+};
+struct Player player;
@@ -17,7 +17,7 @@ fn test_linux_read_with_default_type() {
         .expect("Could not read memory");
     assert_eq!(read_mem.len(), dt.get_size(), "length should be equal");
     assert_eq!(
-        dt.from_bytes(&read_mem)
+        dt.bytes_to_string(&read_mem)
             .expect("Could not convert to bytes"),
         "3735928559",
         "Values should be the same"
@@ -40,7 +40,7 @@ fn test_linux_read_with_custom_type() {
         .expect("Could not read memory");
     assert_eq!(read_mem.len(), dt.get_size(), "length should be equal");
     assert_eq!(
-        dt.from_bytes(&read_mem)
+        dt.bytes_to_string(&read_mem)
             .expect("Could not convert to bytes"),
         "0xDEADBEEF",
         "Values should be the same"